@@ -0,0 +1,1313 @@
+use std::collections::VecDeque;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+use tokio::sync::{broadcast, mpsc, oneshot};
+
+use crate::{
+    asynchronous::{connect, read_messages, sleep_until, write_message, Stream},
+    correlation::{CorrelationEvent, DiagnosticCorrelator, RequestId},
+    definitions::DOIP_DIAG_COMMON_TARGET_LEN,
+    error::{Error, Result},
+    keep_alive::TesterPresentScheduler,
+    message::DoipMessage,
+    payload::{ActivationCode, DiagnosticMessage, DoipPayload},
+    session::{SessionEvent, SessionManager, RESET_DELAY},
+    tester::{TesterConfig, TesterConnection, TesterEvent},
+    timing::{ServerTimings, A_DOIP_CTRL, A_PROCESSING_TIME, DELTA_P2, P2_STAR_SERVER_MAX},
+    uds::{
+        check_response, KeyAlgorithm, ProgrammingSequence, Progress, SecurityAccess, SessionType,
+        UdsRequest, UdsResponse,
+    },
+};
+
+#[cfg(feature = "tls")]
+use crate::asynchronous::{connect_tls, TlsClientConfig};
+
+/// Number of unsolicited messages buffered for each subscriber.
+const UNSOLICITED_CAPACITY: usize = 64;
+
+/// Configuration of a [`DoipClient`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct DoipClientConfig {
+    /// Configuration of the underlying [`TesterConnection`]
+    pub tester: TesterConfig,
+
+    /// Time to wait for the response of a target after the acknowledgement
+    pub response_timeout: Duration,
+
+    /// Time to wait for the final response after each
+    /// `requestCorrectlyReceived-ResponsePending` of a target
+    pub response_pending_timeout: Duration,
+
+    /// Margin added to the `P2Server_max` and `P2*Server_max` reported by a
+    /// target, which replace the response and response pending timeouts of
+    /// its requests once it entered a session
+    pub p2_margin: Duration,
+
+    /// Time waited after a target reset before its session is entered again
+    pub session_reset_delay: Duration,
+
+    /// Delay between reconnection attempts, `None` disables reconnection
+    pub reconnect_delay: Option<Duration>,
+}
+
+impl Default for DoipClientConfig {
+    /// Constructs a `DoipClientConfig` with:
+    /// - The default `TesterConfig`
+    /// - A response timeout of `A_Processing_Time`
+    /// - A response pending timeout of `P2*Server_max`
+    /// - A margin of `ΔP2` on the timings reported by the targets
+    /// - Sessions entered again 1 second after a reset
+    /// - Reconnection every `A_DoIP_Ctrl`
+    fn default() -> Self {
+        Self {
+            tester: TesterConfig::default(),
+            response_timeout: A_PROCESSING_TIME,
+            response_pending_timeout: P2_STAR_SERVER_MAX,
+            p2_margin: DELTA_P2,
+            session_reset_delay: RESET_DELAY,
+            reconnect_delay: Some(A_DOIP_CTRL),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Request {
+    target_address: [u8; DOIP_DIAG_COMMON_TARGET_LEN],
+    message: Vec<u8>,
+    functional: bool,
+    reply: Option<oneshot::Sender<Result<Vec<DiagnosticMessage>>>>,
+}
+
+/// A command passed from a [`DoipClient`] to its background task.
+#[derive(Debug)]
+enum Command {
+    Request(Request),
+    StartTesterPresent {
+        target_address: [u8; DOIP_DIAG_COMMON_TARGET_LEN],
+        interval: Duration,
+    },
+    StopTesterPresent {
+        target_address: [u8; DOIP_DIAG_COMMON_TARGET_LEN],
+    },
+    Session {
+        target_address: [u8; DOIP_DIAG_COMMON_TARGET_LEN],
+        reply: oneshot::Sender<(SessionType, ServerTimings)>,
+    },
+}
+
+/// An async `DoIP` client sharing one routing activated socket between tasks.
+///
+/// A background task owns the socket, answers alive checks and reconnects when
+/// the socket fails, requests sent while reconnecting fail with
+/// [`Error::NotConnected`]. Requests to different targets are sent concurrently, while
+/// requests to the same target are sent one after the other as required by UDS.
+/// Diagnostic messages which do not answer a request are published to every
+/// receiver returned by [`DoipClient::subscribe`].
+///
+/// The client tracks the session of every target. Once a target entered a
+/// session, its requests wait for the `P2Server_max` and `P2*Server_max` it
+/// reported instead of the configured timeouts, and the session is entered
+/// again after the target resets or the socket is reconnected.
+///
+/// The client is cheap to clone, the background task stops once every clone
+/// has been dropped.
+///
+/// ```rust,no_run
+/// use doip_definitions::asynchronous::{DoipClient, DoipClientConfig};
+///
+/// # async fn run() -> doip_definitions::error::Result<()> {
+/// let client =
+///     DoipClient::connect("192.168.0.10:13400".parse().unwrap(), DoipClientConfig::default())
+///         .await?;
+///
+/// let (engine, gearbox) = tokio::join!(
+///     client.send_diagnostic([0x10, 0x01], &[0x22, 0xF1, 0x90]),
+///     client.send_diagnostic([0x10, 0x02], &[0x22, 0xF1, 0x90]),
+/// );
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct DoipClient {
+    requests: mpsc::UnboundedSender<Command>,
+    unsolicited: broadcast::Sender<DiagnosticMessage>,
+}
+
+impl DoipClient {
+    /// Connects to the entity at `address`, activates routing and spawns the
+    /// background task on the current tokio runtime.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`] when the socket cannot be established,
+    /// [`Error::RoutingActivationDenied`] when the entity denies routing and
+    /// [`Error::Timeout`] when it does not respond.
+    pub async fn connect(address: SocketAddr, config: DoipClientConfig) -> Result<Self> {
+        let (worker, client) = Worker::new(address, &config);
+
+        client.start(worker).await
+    }
+
+    /// Connects like [`DoipClient::connect`], switching to the TLS socket of the
+    /// entity when it denies routing with `DeniedRequestEncryptedTLSConnection`,
+    /// or right away when `tls.immediate` is set.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`] when a socket cannot be established,
+    /// [`Error::RoutingActivationDenied`] when the entity denies routing and
+    /// [`Error::Timeout`] when it does not respond.
+    #[cfg(feature = "tls")]
+    pub async fn connect_tls(
+        address: SocketAddr,
+        config: DoipClientConfig,
+        tls: TlsClientConfig,
+    ) -> Result<Self> {
+        let (mut worker, client) = Worker::new(address, &config);
+        worker.encrypted = tls.immediate;
+        worker.tls = Some(tls);
+
+        client.start(worker).await
+    }
+
+    /// Establishes the first socket and spawns the background task.
+    async fn start(self, mut worker: Worker) -> Result<Self> {
+        let stream = worker.establish().await?;
+        tokio::spawn(worker.run(stream));
+
+        Ok(self)
+    }
+
+    /// Sends the UDS request `message` to `target_address` and returns the
+    /// response of the target.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::DiagnosticNack`] when the entity rejects the message,
+    /// [`Error::Timeout`] when no acknowledgement or response is received in time
+    /// and [`Error::NotConnected`] when the socket is lost before the response or
+    /// is being reconnected.
+    pub async fn send_diagnostic(
+        &self,
+        target_address: [u8; DOIP_DIAG_COMMON_TARGET_LEN],
+        message: &[u8],
+    ) -> Result<Vec<u8>> {
+        let responses = self.submit(target_address, message, false).await?;
+
+        Ok(responses
+            .into_iter()
+            .next()
+            .map(|response| response.message)
+            .unwrap_or_default())
+    }
+
+    /// Sends the UDS request `message` to the functional `target_address` and
+    /// returns the final responses of every target received until the response
+    /// timeout expired, in order of arrival.
+    ///
+    /// A `requestCorrectlyReceived-ResponsePending` of a target extends the wait
+    /// by its response pending timeout. No response is an empty list, e.g. when
+    /// the positive responses are suppressed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::DiagnosticNack`] when the entity rejects the message,
+    /// [`Error::Timeout`] when no acknowledgement is received in time and
+    /// [`Error::NotConnected`] when the socket is lost before the responses.
+    pub async fn send_functional(
+        &self,
+        target_address: [u8; DOIP_DIAG_COMMON_TARGET_LEN],
+        message: &[u8],
+    ) -> Result<Vec<DiagnosticMessage>> {
+        self.submit(target_address, message, true).await
+    }
+
+    /// Passes a request to the background task and waits for its responses.
+    async fn submit(
+        &self,
+        target_address: [u8; DOIP_DIAG_COMMON_TARGET_LEN],
+        message: &[u8],
+        functional: bool,
+    ) -> Result<Vec<DiagnosticMessage>> {
+        let (reply, response) = oneshot::channel();
+
+        self.requests
+            .send(Command::Request(Request {
+                target_address,
+                message: message.to_vec(),
+                functional,
+                reply: Some(reply),
+            }))
+            .map_err(|_| Error::NotConnected)?;
+
+        response.await.map_err(|_| Error::NotConnected)?
+    }
+
+    /// Switches `target_address` to `session_type` and returns the timings of
+    /// the session, which are applied to the following requests to the target.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`DoipClient::send_diagnostic`],
+    /// [`Error::NegativeResponse`] when the target rejects the session and
+    /// [`Error::UnexpectedResponse`] when it answers another service.
+    pub async fn enter_session(
+        &self,
+        target_address: [u8; DOIP_DIAG_COMMON_TARGET_LEN],
+        session_type: SessionType,
+    ) -> Result<ServerTimings> {
+        let request: Vec<u8> = UdsRequest::DiagnosticSessionControl {
+            session_type,
+            suppress_positive_response: false,
+        }
+        .into();
+        let response = self.send_diagnostic(target_address, &request).await?;
+
+        match UdsResponse::try_from(check_response(&response)?)? {
+            UdsResponse::DiagnosticSessionControl {
+                p2_server_max,
+                p2_star_server_max,
+                ..
+            } => Ok(ServerTimings::from_response(
+                p2_server_max,
+                p2_star_server_max,
+            )),
+            _ => Err(Error::UnexpectedResponse {
+                service_id: response[0],
+            }),
+        }
+    }
+
+    /// Returns the active session of `target_address` and its timings.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::NotConnected`] when the background task has stopped.
+    pub async fn session(
+        &self,
+        target_address: [u8; DOIP_DIAG_COMMON_TARGET_LEN],
+    ) -> Result<(SessionType, ServerTimings)> {
+        let (reply, session) = oneshot::channel();
+
+        self.requests
+            .send(Command::Session {
+                target_address,
+                reply,
+            })
+            .map_err(|_| Error::NotConnected)?;
+
+        session.await.map_err(|_| Error::NotConnected)
+    }
+
+    /// Runs the `SecurityAccess` exchange `access` with `target_address`,
+    /// sleeping through the security access delays of its retry policy.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`DoipClient::send_diagnostic`] and
+    /// [`SecurityAccess::run`].
+    pub async fn security_access<A: KeyAlgorithm>(
+        &self,
+        target_address: [u8; DOIP_DIAG_COMMON_TARGET_LEN],
+        access: &mut SecurityAccess<A>,
+    ) -> Result<()> {
+        access.start(Instant::now());
+
+        loop {
+            if let Some(result) = access.poll_result() {
+                return result;
+            }
+
+            if let Some(request) = access.poll_transmit() {
+                let response = self.send_diagnostic(target_address, &request).await?;
+                access.handle_response(Instant::now(), &response);
+                continue;
+            }
+
+            if let Some(deadline) = access.poll_timeout() {
+                tokio::time::sleep_until(deadline.into()).await;
+                access.handle_timeout(Instant::now());
+                continue;
+            }
+
+            return Err(Error::Timeout);
+        }
+    }
+
+    /// Runs the download `sequence` to `target_address`, passing the progress
+    /// after each acknowledged block to `progress`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`DoipClient::send_diagnostic`] and
+    /// [`ProgrammingSequence::run`].
+    pub async fn program<R: std::io::Read>(
+        &self,
+        target_address: [u8; DOIP_DIAG_COMMON_TARGET_LEN],
+        sequence: &mut ProgrammingSequence<R>,
+        mut progress: impl FnMut(Progress),
+    ) -> Result<()> {
+        sequence.start();
+
+        loop {
+            if let Some(current) = sequence.poll_progress() {
+                progress(current);
+            }
+
+            if let Some(result) = sequence.poll_result() {
+                return result;
+            }
+
+            let Some(request) = sequence.poll_transmit() else {
+                return Err(Error::Timeout);
+            };
+
+            let response = self.send_diagnostic(target_address, &request).await?;
+            sequence.handle_response(&response);
+        }
+    }
+
+    /// Sends a `TesterPresent` with suppressed positive response to the
+    /// physical or functional `target_address` every `interval`, keeping its
+    /// non-default session alive.
+    ///
+    /// No `TesterPresent` is sent while other requests to `target_address` are
+    /// in flight, requests to other targets do not pause it. The requests pause
+    /// once a physical target returns to the default session or resets, and
+    /// resume when it enters a non-default session again.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::NotConnected`] when the background task has stopped.
+    pub fn start_tester_present(
+        &self,
+        target_address: [u8; DOIP_DIAG_COMMON_TARGET_LEN],
+        interval: Duration,
+    ) -> Result<()> {
+        self.requests
+            .send(Command::StartTesterPresent {
+                target_address,
+                interval,
+            })
+            .map_err(|_| Error::NotConnected)
+    }
+
+    /// Stops the `TesterPresent` requests to `target_address`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::NotConnected`] when the background task has stopped.
+    pub fn stop_tester_present(
+        &self,
+        target_address: [u8; DOIP_DIAG_COMMON_TARGET_LEN],
+    ) -> Result<()> {
+        self.requests
+            .send(Command::StopTesterPresent { target_address })
+            .map_err(|_| Error::NotConnected)
+    }
+
+    /// Returns a receiver of every diagnostic message which does not answer a
+    /// request, e.g. periodic data.
+    #[must_use]
+    pub fn subscribe(&self) -> broadcast::Receiver<DiagnosticMessage> {
+        self.unsolicited.subscribe()
+    }
+}
+
+/// The background task of a [`DoipClient`].
+#[derive(Debug)]
+struct Worker {
+    address: SocketAddr,
+    connection: TesterConnection,
+    correlator: DiagnosticCorrelator,
+    reconnect_delay: Option<Duration>,
+    requests: mpsc::UnboundedReceiver<Command>,
+    unsolicited: broadcast::Sender<DiagnosticMessage>,
+    queued: VecDeque<Request>,
+    in_flight: Vec<(RequestId, Request)>,
+    keep_alive: Vec<TesterPresentScheduler>,
+    tester_present: Vec<RequestId>,
+    sessions: SessionManager,
+    p2_margin: Duration,
+    buffer: Vec<u8>,
+    #[cfg(feature = "tls")]
+    tls: Option<TlsClientConfig>,
+    #[cfg(feature = "tls")]
+    encrypted: bool,
+}
+
+impl Worker {
+    fn new(address: SocketAddr, config: &DoipClientConfig) -> (Self, DoipClient) {
+        let (requests, receiver) = mpsc::unbounded_channel();
+        let (unsolicited, _) = broadcast::channel(UNSOLICITED_CAPACITY);
+
+        let worker = Self {
+            address,
+            connection: TesterConnection::new(config.tester.clone()),
+            correlator: DiagnosticCorrelator::new()
+                .ack_timeout(config.tester.timings.diagnostic_message)
+                .response_timeout(config.response_timeout)
+                .response_pending_timeout(config.response_pending_timeout),
+            reconnect_delay: config.reconnect_delay,
+            requests: receiver,
+            unsolicited: unsolicited.clone(),
+            queued: VecDeque::new(),
+            in_flight: Vec::new(),
+            keep_alive: Vec::new(),
+            tester_present: Vec::new(),
+            sessions: SessionManager::new().reset_delay(config.session_reset_delay),
+            p2_margin: config.p2_margin,
+            buffer: Vec::new(),
+            #[cfg(feature = "tls")]
+            tls: None,
+            #[cfg(feature = "tls")]
+            encrypted: false,
+        };
+
+        (
+            worker,
+            DoipClient {
+                requests,
+                unsolicited,
+            },
+        )
+    }
+
+    async fn run(mut self, mut stream: Box<dyn Stream>) {
+        loop {
+            if self.serve(&mut stream).await.is_ok() {
+                return;
+            }
+
+            self.connection.disconnect();
+            for (id, request) in self.in_flight.drain(..) {
+                self.correlator.cancel(id);
+                if let Some(reply) = request.reply {
+                    let _ = reply.send(Err(Error::NotConnected));
+                }
+
+                for scheduler in &mut self.keep_alive {
+                    if scheduler.target_address() == request.target_address {
+                        scheduler.handle_request_completed(Instant::now(), None);
+                    }
+                }
+            }
+
+            for id in self.tester_present.drain(..) {
+                self.correlator.cancel(id);
+            }
+
+            self.fail_queued();
+            self.sessions.handle_disconnect();
+            self.apply_sessions();
+
+            let Some(delay) = self.reconnect_delay else {
+                return;
+            };
+
+            stream = loop {
+                if !self.wait_disconnected(delay).await {
+                    return;
+                }
+
+                if let Ok(stream) = self.establish().await {
+                    break stream;
+                }
+            };
+
+            self.sessions.handle_connect(Instant::now());
+        }
+    }
+
+    /// Handles the commands received within `delay` while disconnected, failing
+    /// every request. Returns false once every [`DoipClient`] has been dropped.
+    async fn wait_disconnected(&mut self, delay: Duration) -> bool {
+        let deadline = Instant::now() + delay;
+
+        loop {
+            tokio::select! {
+                () = tokio::time::sleep_until(deadline.into()) => return true,
+                command = self.requests.recv() => match command {
+                    Some(command) => {
+                        self.handle_command(command);
+                        self.fail_queued();
+                    }
+                    None => return false,
+                },
+            }
+        }
+    }
+
+    /// Fails the queued requests of the application with
+    /// [`Error::NotConnected`], keeping those entering a session again.
+    fn fail_queued(&mut self) {
+        for request in std::mem::take(&mut self.queued) {
+            match request.reply {
+                Some(reply) => {
+                    let _ = reply.send(Err(Error::NotConnected));
+                }
+                None => self.queued.push_back(request),
+            }
+        }
+    }
+
+    /// Connects the socket and activates routing, upgrading to TLS when the
+    /// entity requests it.
+    async fn establish(&mut self) -> Result<Box<dyn Stream>> {
+        let result = self.activate().await;
+
+        #[cfg(feature = "tls")]
+        if let Err(Error::RoutingActivationDenied {
+            activation_code: ActivationCode::DeniedRequestEncryptedTLSConnection,
+        }) = result
+        {
+            if self.tls.is_some() && !self.encrypted {
+                self.encrypted = true;
+                return self.activate().await;
+            }
+        }
+
+        result
+    }
+
+    /// Opens the plain or TLS socket to the entity.
+    async fn open(&self) -> Result<Box<dyn Stream>> {
+        let deadline = Instant::now() + self.connection.config().timings.ctrl;
+
+        #[cfg(feature = "tls")]
+        if let Some(tls) = self.tls.as_ref().filter(|_| self.encrypted) {
+            return connect_tls(self.address, tls, deadline).await;
+        }
+
+        Ok(Box::new(connect(self.address, deadline).await?))
+    }
+
+    async fn activate(&mut self) -> Result<Box<dyn Stream>> {
+        let mut stream = self.open().await?;
+
+        self.buffer.clear();
+        self.connection.connect(Instant::now());
+
+        loop {
+            while let Some(message) = self.connection.poll_transmit() {
+                write_message(&mut stream, message).await?;
+            }
+
+            while let Some(event) = self.connection.poll_event() {
+                match event {
+                    TesterEvent::RoutingActivated { .. } => return Ok(stream),
+                    TesterEvent::RoutingActivationDenied { activation_code } => {
+                        return Err(Error::RoutingActivationDenied { activation_code });
+                    }
+                    TesterEvent::AuthenticationRequired { .. } => {
+                        return Err(Error::RoutingActivationDenied {
+                            activation_code: ActivationCode::DeniedMissingAuthentication,
+                        });
+                    }
+                    TesterEvent::RoutingActivationTimeout => return Err(Error::Timeout),
+                    TesterEvent::GenericNack { nack_code } => {
+                        return Err(Error::GenericNack { nack_code });
+                    }
+                    _ => {}
+                }
+            }
+
+            let deadline = self.connection.poll_timeout().unwrap_or_else(Instant::now);
+
+            tokio::select! {
+                messages = read_messages(&mut stream, &mut self.buffer) => {
+                    for message in messages? {
+                        self.connection.handle_message(Instant::now(), message);
+                    }
+                }
+                () = tokio::time::sleep_until(deadline.into()) => {
+                    self.connection.handle_timeout(Instant::now());
+                }
+            }
+        }
+    }
+
+    /// Serves requests until the socket fails, or returns `Ok` once every
+    /// [`DoipClient`] has been dropped.
+    async fn serve(&mut self, stream: &mut Box<dyn Stream>) -> Result<()> {
+        loop {
+            self.restore_sessions();
+            self.dispatch(Instant::now());
+            self.keep_alive(Instant::now());
+
+            while let Some(message) = self.connection.poll_transmit() {
+                write_message(stream, message).await?;
+            }
+
+            let timeout = [
+                self.connection.poll_timeout(),
+                self.correlator.poll_timeout(),
+                self.sessions.poll_timeout(),
+            ]
+            .into_iter()
+            .chain(
+                self.keep_alive
+                    .iter()
+                    .map(TesterPresentScheduler::poll_timeout),
+            )
+            .flatten()
+            .min();
+
+            tokio::select! {
+                messages = read_messages(stream, &mut self.buffer) => {
+                    for message in messages? {
+                        self.handle_message(message);
+                    }
+                }
+                command = self.requests.recv() => {
+                    match command {
+                        Some(command) => self.handle_command(command),
+                        None => return Ok(()),
+                    }
+                }
+                () = sleep_until(timeout) => {
+                    let now = Instant::now();
+                    self.connection.handle_timeout(now);
+                    self.correlator.handle_timeout(now);
+                    self.sessions.handle_timeout(now);
+
+                    for scheduler in &mut self.keep_alive {
+                        scheduler.handle_timeout(now);
+                    }
+                }
+            }
+
+            self.handle_events()?;
+        }
+    }
+
+    fn handle_command(&mut self, command: Command) {
+        match command {
+            Command::Request(request) => self.queued.push_back(request),
+            Command::StartTesterPresent {
+                target_address,
+                interval,
+            } => {
+                self.keep_alive
+                    .retain(|scheduler| scheduler.target_address() != target_address);
+
+                let mut scheduler = TesterPresentScheduler::new(target_address, interval);
+                for (_, request) in &self.in_flight {
+                    if request.target_address == target_address {
+                        scheduler.handle_request_sent(Instant::now());
+                    }
+                }
+                scheduler.start(Instant::now());
+                self.keep_alive.push(scheduler);
+            }
+            Command::StopTesterPresent { target_address } => {
+                self.keep_alive
+                    .retain(|scheduler| scheduler.target_address() != target_address);
+            }
+            Command::Session {
+                target_address,
+                reply,
+            } => {
+                let _ = reply.send((
+                    self.sessions.session(target_address),
+                    self.sessions.timings(target_address),
+                ));
+            }
+        }
+    }
+
+    /// Queues the requests entering the session of a target again ahead of
+    /// every other request, their responses are only used to track the session.
+    fn restore_sessions(&mut self) {
+        while let Some((target_address, message)) = self.sessions.poll_transmit() {
+            self.queued.push_front(Request {
+                target_address,
+                message,
+                functional: false,
+                reply: None,
+            });
+        }
+    }
+
+    /// Applies the timings of the active session of each target to the
+    /// following requests to it.
+    fn apply_sessions(&mut self) {
+        while let Some(event) = self.sessions.poll_event() {
+            match event {
+                SessionEvent::Entered {
+                    target_address,
+                    timings,
+                    ..
+                } => self.correlator.set_target_timeouts(
+                    target_address,
+                    timings.p2_server_max + self.p2_margin,
+                    timings.p2_star_server_max + self.p2_margin,
+                ),
+                SessionEvent::Lost { target_address } => {
+                    self.correlator.clear_target_timeouts(target_address);
+                }
+                SessionEvent::Rejected { .. } => {}
+            }
+        }
+    }
+
+    /// Sends the due `TesterPresent` requests. They are registered with the
+    /// correlator so that their acknowledgement cannot be taken for that of a
+    /// request of the application, their events are only used to forget them.
+    fn keep_alive(&mut self, now: Instant) {
+        for scheduler in &mut self.keep_alive {
+            while let Some((target_address, message)) = scheduler.poll_transmit() {
+                if self
+                    .connection
+                    .send_diagnostic(now, target_address, &message)
+                    .is_err()
+                {
+                    continue;
+                }
+
+                let id = self.correlator.register(
+                    now,
+                    &DiagnosticMessage {
+                        source_address: self.connection.config().source_address,
+                        target_address,
+                        message,
+                    },
+                );
+                self.tester_present.push(id);
+            }
+        }
+    }
+
+    /// Sends every queued request whose target has no request in flight.
+    fn dispatch(&mut self, now: Instant) {
+        let mut waiting = VecDeque::new();
+
+        while let Some(request) = self.queued.pop_front() {
+            if request
+                .reply
+                .as_ref()
+                .is_some_and(oneshot::Sender::is_closed)
+            {
+                continue;
+            }
+
+            let busy = self
+                .in_flight
+                .iter()
+                .map(|(_, other)| other)
+                .chain(&waiting)
+                .any(|other| other.target_address == request.target_address);
+
+            if busy {
+                waiting.push_back(request);
+                continue;
+            }
+
+            if let Err(err) =
+                self.connection
+                    .send_diagnostic(now, request.target_address, &request.message)
+            {
+                if let Some(reply) = request.reply {
+                    let _ = reply.send(Err(err));
+                }
+                continue;
+            }
+
+            let message = DiagnosticMessage {
+                source_address: self.connection.config().source_address,
+                target_address: request.target_address,
+                message: request.message.clone(),
+            };
+            let id = if request.functional {
+                self.correlator.register_functional(now, &message)
+            } else {
+                self.correlator.register(now, &message)
+            };
+            for scheduler in &mut self.keep_alive {
+                if scheduler.target_address() == request.target_address {
+                    scheduler.handle_request_sent(now);
+                }
+            }
+
+            self.in_flight.push((id, request));
+        }
+
+        self.queued = waiting;
+    }
+
+    fn handle_message(&mut self, message: DoipMessage) {
+        let now = Instant::now();
+
+        if matches!(
+            message.payload,
+            DoipPayload::DiagnosticMessage(_)
+                | DoipPayload::DiagnosticMessageAck(_)
+                | DoipPayload::DiagnosticMessageNack(_)
+        ) {
+            self.correlator.handle_message(now, message.clone());
+        }
+
+        self.connection.handle_message(now, message);
+    }
+
+    fn handle_events(&mut self) -> Result<()> {
+        while let Some(event) = self.connection.poll_event() {
+            match event {
+                TesterEvent::RoutingActivationDenied { activation_code } => {
+                    return Err(Error::RoutingActivationDenied { activation_code });
+                }
+                TesterEvent::RoutingActivationTimeout => return Err(Error::Timeout),
+                _ => {}
+            }
+        }
+
+        let now = Instant::now();
+
+        while let Some(event) = self.correlator.poll_event() {
+            let (id, result) = match event {
+                CorrelationEvent::Response { id, message } => (id, Ok(vec![message])),
+                CorrelationEvent::Responses { id, messages } => (id, Ok(messages)),
+                CorrelationEvent::Rejected { id, nack_code } => {
+                    (id, Err(Error::DiagnosticNack { nack_code }))
+                }
+                CorrelationEvent::AckTimeout { id } | CorrelationEvent::ResponseTimeout { id } => {
+                    (id, Err(Error::Timeout))
+                }
+                CorrelationEvent::Unsolicited(message) => {
+                    let _ = self.unsolicited.send(message);
+                    continue;
+                }
+                CorrelationEvent::Acknowledged { .. }
+                | CorrelationEvent::ResponsePending { .. } => continue,
+            };
+
+            // A response to a `TesterPresent`, e.g. a negative one, answers no
+            // request of the application.
+            if let Some(index) = self.tester_present.iter().position(|other| *other == id) {
+                self.tester_present.remove(index);
+                for message in result.unwrap_or_default() {
+                    let _ = self.unsolicited.send(message);
+                }
+                continue;
+            }
+
+            let Some(index) = self.in_flight.iter().position(|(other, _)| *other == id) else {
+                continue;
+            };
+            let (_, request) = self.in_flight.remove(index);
+            let responses = result.as_deref().unwrap_or_default();
+
+            for scheduler in &mut self.keep_alive {
+                if scheduler.target_address() == request.target_address {
+                    let response = responses
+                        .iter()
+                        .find(|response| response.source_address == scheduler.target_address());
+                    scheduler.handle_request_completed(now, response);
+                } else {
+                    for response in responses {
+                        scheduler.handle_response(now, response);
+                    }
+                }
+            }
+
+            for response in responses {
+                self.sessions
+                    .handle_response(now, response.source_address, &response.message);
+            }
+
+            if let Some(reply) = request.reply {
+                let _ = reply.send(result);
+            }
+        }
+
+        self.apply_sessions();
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::net::SocketAddr;
+    use std::time::Duration;
+
+    use tokio::io::AsyncWriteExt;
+    use tokio::net::TcpListener;
+    use tokio::time::timeout;
+
+    use crate::{
+        asynchronous::{read_messages, DoipClient, DoipClientConfig},
+        builder::DoipMessageBuilder,
+        error::Error,
+        message::DoipMessage,
+        mock::MockEntity,
+        payload::{
+            DiagnosticAckCode, DiagnosticMessage, DiagnosticMessageAck, DiagnosticMessageNack,
+            DiagnosticNackCode, DoipPayload,
+        },
+        timing::ServerTimings,
+        uds::SessionType,
+    };
+
+    /// Functional address of the group in [`test_functional_request`].
+    const FUNCTIONAL: [u8; 2] = [0xE4, 0x00];
+
+    async fn serve(mut ecu: MockEntity) -> SocketAddr {
+        serve_with(move |message| ecu.handle_message(message)).await
+    }
+
+    /// Serves `ecu` and answers every request to `target` with an
+    /// acknowledgement followed by the `responses`.
+    async fn serve_target(
+        mut ecu: MockEntity,
+        target: [u8; 2],
+        responses: Vec<DiagnosticMessage>,
+    ) -> SocketAddr {
+        serve_with(move |message| match &message.payload {
+            DoipPayload::DiagnosticMessage(request) if request.target_address == target => {
+                let ack = DoipPayload::DiagnosticMessageAck(DiagnosticMessageAck {
+                    source_address: target,
+                    target_address: request.source_address,
+                    ack_code: DiagnosticAckCode::Acknowledged,
+                    previous_message: Vec::new(),
+                });
+
+                std::iter::once(ack)
+                    .chain(
+                        responses
+                            .iter()
+                            .cloned()
+                            .map(DoipPayload::DiagnosticMessage),
+                    )
+                    .map(|payload| DoipMessageBuilder::new().payload(payload).build())
+                    .collect()
+            }
+            _ => ecu.handle_message(message),
+        })
+        .await
+    }
+
+    /// Accepts one tester and writes the replies of `answer` to each message.
+    async fn serve_with(
+        mut answer: impl FnMut(DoipMessage) -> Vec<DoipMessage> + Send + 'static,
+    ) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buffer = Vec::new();
+
+            while let Ok(messages) = read_messages(&mut stream, &mut buffer).await {
+                for message in messages {
+                    for reply in answer(message) {
+                        let reply: Vec<u8> = reply.try_into().unwrap();
+                        stream.write_all(&reply).await.unwrap();
+                    }
+                }
+            }
+        });
+
+        address
+    }
+
+    fn response(source: u8, message: &[u8]) -> DiagnosticMessage {
+        DiagnosticMessage {
+            source_address: [0x10, source],
+            target_address: [0x0E, 0x00],
+            message: message.to_vec(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_requests() {
+        let address = serve(
+            MockEntity::new()
+                .response(&[0x3E, 0x00], &[0x7E, 0x00])
+                .response(&[0x22, 0xF1, 0x90], &[0x62, 0xF1, 0x90]),
+        )
+        .await;
+
+        let client = DoipClient::connect(address, DoipClientConfig::default())
+            .await
+            .unwrap();
+
+        let (first, second, unknown) = tokio::join!(
+            client.send_diagnostic([0x10, 0x00], &[0x3E, 0x00]),
+            client.send_diagnostic([0x10, 0x00], &[0x22, 0xF1, 0x90]),
+            client.send_diagnostic([0x10, 0x01], &[0x3E, 0x00]),
+        );
+
+        assert_eq!(first.unwrap(), vec![0x7E, 0x00]);
+        assert_eq!(second.unwrap(), vec![0x62, 0xF1, 0x90]);
+        assert!(matches!(unknown, Err(Error::DiagnosticNack { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_functional_request() {
+        let address = serve_target(
+            MockEntity::new(),
+            FUNCTIONAL,
+            vec![
+                response(0x01, &[0x62, 0xF1, 0x90, 0x01]),
+                response(0x02, &[0x7F, 0x22, 0x78]),
+                response(0x02, &[0x62, 0xF1, 0x90, 0x02]),
+            ],
+        )
+        .await;
+
+        let client = DoipClient::connect(
+            address,
+            DoipClientConfig {
+                response_timeout: Duration::from_millis(100),
+                response_pending_timeout: Duration::from_millis(200),
+                ..DoipClientConfig::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            client
+                .send_functional(FUNCTIONAL, &[0x22, 0xF1, 0x90])
+                .await
+                .unwrap(),
+            vec![
+                response(0x01, &[0x62, 0xF1, 0x90, 0x01]),
+                response(0x02, &[0x62, 0xF1, 0x90, 0x02]),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_request_while_reconnecting() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+
+        // Activates routing, then closes the socket and stops listening.
+        tokio::spawn(async move {
+            let mut ecu = MockEntity::new();
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buffer = Vec::new();
+
+            for message in read_messages(&mut stream, &mut buffer).await.unwrap() {
+                for reply in ecu.handle_message(message) {
+                    let reply: Vec<u8> = reply.try_into().unwrap();
+                    stream.write_all(&reply).await.unwrap();
+                }
+            }
+        });
+
+        let client = DoipClient::connect(
+            address,
+            DoipClientConfig {
+                reconnect_delay: Some(Duration::from_millis(50)),
+                ..DoipClientConfig::default()
+            },
+        )
+        .await
+        .unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        assert!(matches!(
+            timeout(
+                Duration::from_secs(1),
+                client.send_diagnostic([0x10, 0x00], &[0x22, 0xF1, 0x90])
+            )
+            .await
+            .unwrap(),
+            Err(Error::NotConnected)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_tester_present() {
+        let address = serve(
+            MockEntity::new()
+                .response(&[0x3E, 0x80], &[0x7E, 0x00])
+                .response(&[0x10, 0x01], &[0x50, 0x01, 0x00, 0x32, 0x01, 0xF4]),
+        )
+        .await;
+
+        let client = DoipClient::connect(address, DoipClientConfig::default())
+            .await
+            .unwrap();
+        let mut unsolicited = client.subscribe();
+
+        client
+            .start_tester_present([0x10, 0x00], Duration::from_millis(20))
+            .unwrap();
+
+        let keep_alive = timeout(Duration::from_secs(1), unsolicited.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(keep_alive.message, vec![0x7E, 0x00]);
+
+        client
+            .send_diagnostic([0x10, 0x00], &[0x10, 0x01])
+            .await
+            .unwrap();
+        while unsolicited.try_recv().is_ok() {}
+
+        assert!(timeout(Duration::from_millis(100), unsolicited.recv())
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_tester_present_during_other_request() {
+        // The routine of the second target stays pending until its timeout.
+        let address = serve_target(
+            MockEntity::new().response(&[0x3E, 0x80], &[0x7E, 0x00]),
+            [0x10, 0x02],
+            vec![response(0x02, &[0x7F, 0x31, 0x78])],
+        )
+        .await;
+
+        let client = DoipClient::connect(
+            address,
+            DoipClientConfig {
+                response_pending_timeout: Duration::from_millis(500),
+                ..DoipClientConfig::default()
+            },
+        )
+        .await
+        .unwrap();
+        let mut unsolicited = client.subscribe();
+
+        client
+            .start_tester_present([0x10, 0x00], Duration::from_millis(20))
+            .unwrap();
+
+        let routine = tokio::spawn({
+            let client = client.clone();
+            async move {
+                client
+                    .send_diagnostic([0x10, 0x02], &[0x31, 0x01, 0xFF, 0x00])
+                    .await
+            }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        while unsolicited.try_recv().is_ok() {}
+
+        let keep_alive = timeout(Duration::from_millis(200), unsolicited.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(keep_alive.source_address, [0x10, 0x00]);
+        assert!(!routine.is_finished());
+
+        assert!(matches!(routine.await.unwrap(), Err(Error::Timeout)));
+    }
+
+    #[tokio::test]
+    async fn test_tester_present_nack() {
+        // The NACKs of the `TesterPresent` requests are held back until the
+        // next request of the application.
+        let mut ecu = MockEntity::new().response(&[0x22, 0xF1, 0x90], &[0x62, 0xF1, 0x90]);
+        let mut withheld = 0;
+        let address = serve_with(move |message| match &message.payload {
+            DoipPayload::DiagnosticMessage(request) if request.message == [0x3E, 0x80] => {
+                withheld += 1;
+                Vec::new()
+            }
+            DoipPayload::DiagnosticMessage(request) => {
+                let nack = DoipPayload::DiagnosticMessageNack(DiagnosticMessageNack {
+                    source_address: request.target_address,
+                    target_address: request.source_address,
+                    nack_code: DiagnosticNackCode::OutOfMemory,
+                });
+                let mut replies = Vec::new();
+                for _ in 0..std::mem::take(&mut withheld) {
+                    replies.push(DoipMessageBuilder::new().payload(nack.clone()).build());
+                }
+
+                replies.extend(ecu.handle_message(message));
+                replies
+            }
+            _ => ecu.handle_message(message),
+        })
+        .await;
+
+        let client = DoipClient::connect(address, DoipClientConfig::default())
+            .await
+            .unwrap();
+
+        client
+            .start_tester_present([0x10, 0x00], Duration::from_millis(20))
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        client.stop_tester_present([0x10, 0x00]).unwrap();
+
+        assert_eq!(
+            client
+                .send_diagnostic([0x10, 0x00], &[0x22, 0xF1, 0x90])
+                .await
+                .unwrap(),
+            vec![0x62, 0xF1, 0x90]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_session_reentry() {
+        let address = serve(
+            MockEntity::new()
+                .response(&[0x10, 0x03], &[0x50, 0x03, 0x00, 0x64, 0x00, 0xC8])
+                .response(&[0x11, 0x01], &[0x51, 0x01]),
+        )
+        .await;
+
+        let client = DoipClient::connect(
+            address,
+            DoipClientConfig {
+                session_reset_delay: Duration::from_millis(20),
+                ..DoipClientConfig::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        let timings = ServerTimings {
+            p2_server_max: Duration::from_millis(100),
+            p2_star_server_max: Duration::from_secs(2),
+        };
+        assert_eq!(
+            client
+                .enter_session([0x10, 0x00], SessionType::ExtendedDiagnostic)
+                .await
+                .unwrap(),
+            timings
+        );
+        assert_eq!(
+            client.session([0x10, 0x00]).await.unwrap(),
+            (SessionType::ExtendedDiagnostic, timings)
+        );
+
+        client
+            .send_diagnostic([0x10, 0x00], &[0x11, 0x01])
+            .await
+            .unwrap();
+        assert_eq!(
+            client.session([0x10, 0x00]).await.unwrap(),
+            (SessionType::Default, ServerTimings::default())
+        );
+
+        timeout(Duration::from_secs(1), async {
+            while client.session([0x10, 0x00]).await.unwrap().0 != SessionType::ExtendedDiagnostic {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        assert!(matches!(
+            client
+                .enter_session([0x10, 0x00], SessionType::Programming)
+                .await,
+            Err(Error::NegativeResponse { .. })
+        ));
+    }
+}
@@ -0,0 +1,85 @@
+// region:      --- Modules
+
+mod client;
+mod server;
+#[cfg(test)]
+mod test_support;
+#[cfg(feature = "tls")]
+mod tls;
+
+// -- Flatten
+
+pub use client::*;
+pub use server::*;
+#[cfg(feature = "tls")]
+pub use tls::*;
+
+// endregion:      --- Modules
+
+use std::net::SocketAddr;
+use std::time::Instant;
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+use crate::{
+    error::{Error, Result},
+    message::DoipMessage,
+    transport::take_message,
+};
+
+/// Size reserved in the read buffer before each read from the socket.
+const READ_CHUNK_LEN: usize = 4096;
+
+/// A byte stream to a `DoIP` peer, either plain TCP or TLS.
+trait Stream: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> Stream for T {}
+
+/// Writes `message` to `stream`.
+async fn write_message(stream: &mut (impl AsyncWrite + Unpin), message: DoipMessage) -> Result<()> {
+    let bytes: Vec<u8> = message.try_into()?;
+    stream.write_all(&bytes).await?;
+    stream.flush().await?;
+
+    Ok(())
+}
+
+/// Reads from `stream` once, returning every message completed by the read.
+///
+/// Cancel safe, bytes of an incomplete message are kept in `buffer`.
+async fn read_messages(
+    stream: &mut (impl AsyncRead + Unpin),
+    buffer: &mut Vec<u8>,
+) -> Result<Vec<DoipMessage>> {
+    buffer.reserve(READ_CHUNK_LEN);
+
+    if stream.read_buf(buffer).await? == 0 {
+        return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+    }
+
+    let mut messages = Vec::new();
+    while let Some(message) = take_message(buffer)? {
+        messages.push(message);
+    }
+
+    Ok(messages)
+}
+
+/// Connects to `address`, failing with [`Error::Timeout`] after `deadline`.
+async fn connect(address: SocketAddr, deadline: Instant) -> Result<TcpStream> {
+    let stream = tokio::time::timeout_at(deadline.into(), TcpStream::connect(address))
+        .await
+        .map_err(|_| Error::Timeout)??;
+    stream.set_nodelay(true)?;
+
+    Ok(stream)
+}
+
+/// Sleeps until `deadline`, or forever without a deadline.
+async fn sleep_until(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline.into()).await,
+        None => std::future::pending().await,
+    }
+}
@@ -0,0 +1,644 @@
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::io;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV6};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use tokio::io::AsyncWriteExt;
+use tokio::net::{TcpListener, TcpStream, UdpSocket};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::{
+    asynchronous::{read_messages, sleep_until, write_message, Stream},
+    entity::{DoipEntity, EntityConfig, EntityEvent, SocketId, VehicleAnnouncer},
+    error::Result,
+    gateway::{LogicalAddress, RoutingDecision, RoutingTable},
+    message::DoipMessage,
+    payload::{DiagnosticMessage, DoipPayload, VehicleAnnouncementMessage},
+    transport::{DOIP_IPV6_MULTICAST, DOIP_NET_PORT},
+};
+
+#[cfg(feature = "tls")]
+use crate::asynchronous::TlsServerConfig;
+
+/// Largest UDP datagram accepted by the server.
+const DATAGRAM_LEN: usize = 1500;
+
+/// The answer of a [`DiagnosticHandler`] to a UDS request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Response {
+    /// Sends the UDS response to the tester
+    Uds(Vec<u8>),
+
+    /// Sends no response, e.g. when the positive response is suppressed
+    None,
+}
+
+/// Answers the UDS requests received by a [`DoipServer`].
+///
+/// The handler is only called for messages which have been acknowledged, the
+/// server rejects messages of inactive sockets, unknown targets and oversized
+/// requests itself. Implementations may use `async fn`.
+pub trait DiagnosticHandler: Send + Sync + 'static {
+    /// Answers the UDS request `uds` sent by `source` to `target`.
+    fn handle(
+        &self,
+        source: LogicalAddress,
+        target: LogicalAddress,
+        uds: &[u8],
+    ) -> impl Future<Output = Response> + Send;
+}
+
+/// Configuration of a [`DoipServer`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct DoipServerConfig {
+    /// Configuration of the underlying [`DoipEntity`]
+    pub entity: EntityConfig,
+
+    /// Sent on startup and in response to vehicle identification requests
+    pub announcement: VehicleAnnouncementMessage,
+
+    /// Target addresses passed to the handler, other targets are rejected with
+    /// `UnknownTargetAddress`
+    pub targets: Vec<LogicalAddress>,
+
+    /// Destination of the vehicle announcements, `None` disables them. For an
+    /// IPv6 multicast address the UDP socket joins the group on the interface of
+    /// its scope id, see [`DoipServerConfig::ipv6`].
+    pub announce_address: Option<SocketAddr>,
+}
+
+impl DoipServerConfig {
+    /// Creates a `DoipServerConfig` serving the logical address of `entity` and
+    /// broadcasting announcements to `255.255.255.255:13400`.
+    #[must_use]
+    pub fn new(entity: EntityConfig, announcement: VehicleAnnouncementMessage) -> Self {
+        Self {
+            targets: vec![entity.logical_address],
+            entity,
+            announcement,
+            announce_address: Some(SocketAddr::from((Ipv4Addr::BROADCAST, DOIP_NET_PORT))),
+        }
+    }
+
+    /// Creates a `DoipServerConfig` serving the logical address of `entity` and
+    /// announcing to `FF02::1` on the interface identified by `scope_id`, port
+    /// 13400.
+    #[must_use]
+    pub fn ipv6(
+        entity: EntityConfig,
+        announcement: VehicleAnnouncementMessage,
+        scope_id: u32,
+    ) -> Self {
+        Self {
+            announce_address: Some(
+                SocketAddrV6::new(DOIP_IPV6_MULTICAST, DOIP_NET_PORT, 0, scope_id).into(),
+            ),
+            ..Self::new(entity, announcement)
+        }
+    }
+}
+
+/// Completes the TLS handshake of an accepted socket, if any.
+type Handshake = Pin<Box<dyn Future<Output = io::Result<Box<dyn Stream>>> + Send>>;
+
+#[derive(Debug)]
+enum Inbound {
+    Message(SocketId, DoipMessage),
+    Closed(SocketId),
+}
+
+/// The TCP listener and the optional TLS listener of a [`DoipServer`].
+#[derive(Debug)]
+struct Listeners {
+    tcp: TcpListener,
+    #[cfg(feature = "tls")]
+    tls: Option<(TcpListener, Arc<rustls::ServerConfig>)>,
+}
+
+impl Listeners {
+    /// Waits for the next tester on either listener, returning whether it
+    /// connected with TLS.
+    async fn accept(&self) -> Result<(Handshake, bool)> {
+        #[cfg(feature = "tls")]
+        if let Some((listener, config)) = &self.tls {
+            tokio::select! {
+                accepted = listener.accept() => {
+                    let (stream, _) = accepted?;
+                    stream.set_nodelay(true)?;
+                    let handshake = tokio_rustls::TlsAcceptor::from(Arc::clone(config)).accept(stream);
+
+                    return Ok((
+                        Box::pin(async move { Ok(Box::new(handshake.await?) as Box<dyn Stream>) }),
+                        true,
+                    ));
+                }
+                accepted = self.tcp.accept() => return Ok((plain(accepted?.0)?, false)),
+            }
+        }
+
+        let (stream, _) = self.tcp.accept().await?;
+
+        Ok((plain(stream)?, false))
+    }
+}
+
+#[derive(Debug)]
+struct Peer {
+    writer: mpsc::UnboundedSender<DoipMessage>,
+    reader: JoinHandle<()>,
+}
+
+/// An async `DoIP` server for ECU simulators.
+///
+/// The server listens on TCP and UDP, drives a [`DoipEntity`] and a
+/// [`VehicleAnnouncer`] and passes every acknowledged UDS request to a
+/// [`DiagnosticHandler`]. Routing activation, socket limits, alive checks,
+/// acknowledgements and announcements are handled by the server.
+///
+/// ```rust,no_run
+/// use doip_definitions::asynchronous::{DiagnosticHandler, DoipServer, DoipServerConfig, Response};
+/// use doip_definitions::entity::EntityConfig;
+/// use doip_definitions::gateway::LogicalAddress;
+/// use doip_definitions::payload::{ActionCode, VehicleAnnouncementMessage};
+///
+/// struct Ecu;
+///
+/// impl DiagnosticHandler for Ecu {
+///     async fn handle(&self, _: LogicalAddress, _: LogicalAddress, uds: &[u8]) -> Response {
+///         match uds {
+///             [0x3E, 0x00] => Response::Uds(vec![0x7E, 0x00]),
+///             [sid, ..] => Response::Uds(vec![0x7F, *sid, 0x11]),
+///             [] => Response::None,
+///         }
+///     }
+/// }
+///
+/// # async fn run() -> doip_definitions::error::Result<()> {
+/// let announcement = VehicleAnnouncementMessage {
+///     vin: *b"WVWZZZ1JZXW000001",
+///     logical_address: [0x10, 0x00],
+///     eid: [0x00, 0x1A, 0x2B, 0x3C, 0x4D, 0x5E],
+///     gid: [0x00, 0x1A, 0x2B, 0x3C, 0x4D, 0x5E],
+///     further_action: ActionCode::NoFurtherActionRequired,
+///     vin_gid_sync: None,
+/// };
+///
+/// let config = DoipServerConfig::new(EntityConfig::default(), announcement);
+/// DoipServer::bind("0.0.0.0:13400".parse().unwrap(), config, Ecu)
+///     .await?
+///     .run()
+///     .await
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct DoipServer<H> {
+    listeners: Listeners,
+    udp: UdpSocket,
+    entity: DoipEntity,
+    announcer: VehicleAnnouncer,
+    routing: RoutingTable<LogicalAddress>,
+    announce_address: Option<SocketAddr>,
+    handler: Arc<H>,
+    peers: BTreeMap<SocketId, Peer>,
+}
+
+impl<H: DiagnosticHandler> DoipServer<H> {
+    /// Binds the TCP listener to `address` and the UDP socket to the same IP
+    /// address and port.
+    ///
+    /// When announcing to an IPv6 multicast group the UDP socket is instead
+    /// bound to `[::]` and joins the group, so that it also receives the vehicle
+    /// identification requests sent to it. Binding to port `0` picks a free port
+    /// for both sockets.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`](crate::error::Error::Io) when a socket cannot be
+    /// bound.
+    pub async fn bind(address: SocketAddr, config: DoipServerConfig, handler: H) -> Result<Self> {
+        let listener = TcpListener::bind(address).await?;
+        let local = listener.local_addr()?;
+
+        let udp = match config.announce_address {
+            Some(SocketAddr::V6(group)) if group.ip().is_multicast() => {
+                let udp =
+                    UdpSocket::bind(SocketAddrV6::new(Ipv6Addr::UNSPECIFIED, local.port(), 0, 0))
+                        .await?;
+                udp.join_multicast_v6(group.ip(), group.scope_id())?;
+                udp
+            }
+            Some(SocketAddr::V4(_)) => {
+                let udp = UdpSocket::bind(local).await?;
+                udp.set_broadcast(true)?;
+                udp
+            }
+            _ => UdpSocket::bind(local).await?,
+        };
+
+        let mut routing =
+            RoutingTable::new().max_message_size(config.entity.max_data_size as usize);
+        for target in config.targets {
+            routing.insert_physical(target, target);
+        }
+
+        let protocol_version = config.entity.protocol_version;
+        let timings = config.entity.timings;
+
+        Ok(Self {
+            listeners: Listeners {
+                tcp: listener,
+                #[cfg(feature = "tls")]
+                tls: None,
+            },
+            udp,
+            entity: DoipEntity::new(config.entity),
+            announcer: VehicleAnnouncer::new(config.announcement, timings)
+                .protocol_version(protocol_version),
+            routing,
+            announce_address: config.announce_address,
+            handler: Arc::new(handler),
+            peers: BTreeMap::new(),
+        })
+    }
+
+    /// Additionally listens for TLS connections on the IP address of the TCP
+    /// listener and `config.port`.
+    ///
+    /// Sockets accepted on the TLS listener may activate the routing types in
+    /// `EntityConfig::encrypted_activation_types`, which are denied with
+    /// `DeniedRequestEncryptedTLSConnection` on plain sockets.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`](crate::error::Error::Io) when the listener cannot
+    /// be bound.
+    #[cfg(feature = "tls")]
+    pub async fn listen_tls(mut self, config: TlsServerConfig) -> Result<Self> {
+        let address = SocketAddr::new(self.listeners.tcp.local_addr()?.ip(), config.port);
+        let listener = TcpListener::bind(address).await?;
+
+        self.listeners.tls = Some((listener, config.rustls));
+
+        Ok(self)
+    }
+
+    /// Returns the address of the TLS listener, if any.
+    #[cfg(feature = "tls")]
+    #[must_use]
+    pub fn local_tls_addr(&self) -> Option<SocketAddr> {
+        self.listeners
+            .tls
+            .as_ref()
+            .and_then(|(listener, _)| listener.local_addr().ok())
+    }
+
+    /// Returns the address of the TCP listener.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`](crate::error::Error::Io) when the address cannot be
+    /// retrieved.
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.listeners.tcp.local_addr()?)
+    }
+
+    /// Sends the vehicle announcements and serves testers until an IO error
+    /// occurs on the listening sockets.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`](crate::error::Error::Io) when accepting a
+    /// connection or receiving a datagram fails.
+    pub async fn run(mut self) -> Result<()> {
+        let (inbound, mut receiver) = mpsc::unbounded_channel();
+        let (responses, mut responded) = mpsc::unbounded_channel();
+        let mut datagram = [0u8; DATAGRAM_LEN];
+
+        if self.announce_address.is_some() {
+            self.announcer.start(Instant::now(), random());
+        }
+
+        loop {
+            let timeout = match (self.entity.poll_timeout(), self.announcer.poll_timeout()) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, b) => a.or(b),
+            };
+
+            tokio::select! {
+                accepted = self.listeners.accept() => {
+                    let (handshake, encrypted) = accepted?;
+                    self.accept(handshake, encrypted, inbound.clone());
+                }
+                datagram_len = self.udp.recv_from(&mut datagram) => {
+                    let (len, origin) = datagram_len?;
+                    self.handle_datagram(&datagram[..len], origin).await;
+                }
+                Some(inbound) = receiver.recv() => {
+                    match inbound {
+                        Inbound::Message(socket, message) => {
+                            self.entity.handle_message(Instant::now(), socket, message);
+                        }
+                        Inbound::Closed(socket) => {
+                            self.entity.close(Instant::now(), socket);
+                            self.drop_peer(socket);
+                        }
+                    }
+                }
+                Some((socket, message)) = responded.recv() => {
+                    self.entity.send(socket, DoipPayload::DiagnosticMessage(message));
+                }
+                () = sleep_until(timeout) => {
+                    let now = Instant::now();
+                    self.entity.handle_timeout(now);
+                    self.announcer.handle_timeout(now);
+                }
+            }
+
+            self.flush(&responses).await;
+        }
+    }
+
+    fn accept(
+        &mut self,
+        handshake: Handshake,
+        encrypted: bool,
+        inbound: mpsc::UnboundedSender<Inbound>,
+    ) {
+        let socket = if encrypted {
+            self.entity.accept_encrypted(Instant::now())
+        } else {
+            self.entity.accept(Instant::now())
+        };
+        let (writer, mut outbound) = mpsc::unbounded_channel::<DoipMessage>();
+
+        let reader = tokio::spawn(async move {
+            let Ok(stream) = handshake.await else {
+                let _ = inbound.send(Inbound::Closed(socket));
+                return;
+            };
+            let (mut read_half, mut write_half) = tokio::io::split(stream);
+
+            tokio::spawn(async move {
+                while let Some(message) = outbound.recv().await {
+                    if write_message(&mut write_half, message).await.is_err() {
+                        break;
+                    }
+                }
+
+                let _ = write_half.shutdown().await;
+            });
+
+            let mut buffer = Vec::new();
+
+            while let Ok(messages) = read_messages(&mut read_half, &mut buffer).await {
+                for message in messages {
+                    if inbound.send(Inbound::Message(socket, message)).is_err() {
+                        return;
+                    }
+                }
+            }
+
+            let _ = inbound.send(Inbound::Closed(socket));
+        });
+
+        self.peers.insert(socket, Peer { writer, reader });
+    }
+
+    async fn handle_datagram(&mut self, datagram: &[u8], origin: SocketAddr) {
+        let Ok(message) = DoipMessage::try_from(datagram) else {
+            return;
+        };
+
+        if self
+            .announcer
+            .handle_message(Instant::now(), random(), origin, &message)
+        {
+            return;
+        }
+
+        if let Some(response) = self.entity.handle_datagram(&message) {
+            send_datagram(&self.udp, response, origin).await;
+        }
+    }
+
+    /// Writes the queued messages to their sockets and processes the events of
+    /// the entity until both are drained.
+    async fn flush(&mut self, responses: &mpsc::UnboundedSender<(SocketId, DiagnosticMessage)>) {
+        while let Some((origin, message)) = self.announcer.poll_transmit() {
+            if let Some(address) = origin.or(self.announce_address) {
+                send_datagram(&self.udp, message, address).await;
+            }
+        }
+
+        loop {
+            while let Some((socket, message)) = self.entity.poll_transmit() {
+                if let Some(peer) = self.peers.get(&socket) {
+                    let _ = peer.writer.send(message);
+                }
+            }
+
+            let Some(event) = self.entity.poll_event() else {
+                break;
+            };
+
+            match event {
+                EntityEvent::DiagnosticMessage { socket, message } => {
+                    self.handle_diagnostic_message(socket, message, responses);
+                }
+                EntityEvent::SocketClosed { socket } => self.drop_peer(socket),
+                EntityEvent::RoutingActivated { .. } => {}
+            }
+        }
+    }
+
+    fn handle_diagnostic_message(
+        &mut self,
+        socket: SocketId,
+        message: DiagnosticMessage,
+        responses: &mpsc::UnboundedSender<(SocketId, DiagnosticMessage)>,
+    ) {
+        let decision = self.routing.route_for(&self.entity, socket, &message);
+        self.entity.send(socket, decision.response());
+
+        if let RoutingDecision::Reject { .. } = decision {
+            return;
+        }
+
+        let handler = Arc::clone(&self.handler);
+        let responses = responses.clone();
+
+        tokio::spawn(async move {
+            let response = handler
+                .handle(
+                    message.source_address,
+                    message.target_address,
+                    &message.message,
+                )
+                .await;
+
+            if let Response::Uds(response) = response {
+                let _ = responses.send((
+                    socket,
+                    DiagnosticMessage {
+                        source_address: message.target_address,
+                        target_address: message.source_address,
+                        message: response,
+                    },
+                ));
+            }
+        });
+    }
+
+    /// Stops reading from `socket` and closes it once the queued messages are
+    /// written.
+    fn drop_peer(&mut self, socket: SocketId) {
+        if let Some(peer) = self.peers.remove(&socket) {
+            peer.reader.abort();
+        }
+    }
+}
+
+fn plain(stream: TcpStream) -> Result<Handshake> {
+    stream.set_nodelay(true)?;
+
+    Ok(Box::pin(std::future::ready(Ok(
+        Box::new(stream) as Box<dyn Stream>
+    ))))
+}
+
+/// Returns a pseudo random number for `A_DoIP_Announce_Wait` derived from the
+/// system clock.
+fn random() -> u32 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.subsec_nanos())
+}
+
+async fn send_datagram(udp: &UdpSocket, message: DoipMessage, address: SocketAddr) {
+    if let Ok(bytes) = Vec::<u8>::try_from(message) {
+        let _ = udp.send_to(&bytes, address).await;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::net::{SocketAddr, SocketAddrV6};
+    use std::time::Duration;
+
+    use tokio::net::UdpSocket;
+
+    use crate::{
+        asynchronous::{
+            test_support::Echo, DoipClient, DoipClientConfig, DoipServer, DoipServerConfig,
+        },
+        builder::DoipMessageBuilder,
+        entity::EntityConfig,
+        error::Error,
+        message::DoipMessage,
+        payload::{
+            ActionCode, DoipPayload, VehicleAnnouncementMessage, VehicleIdentificationRequest,
+        },
+        transport::DOIP_IPV6_MULTICAST,
+    };
+
+    fn announcement() -> VehicleAnnouncementMessage {
+        VehicleAnnouncementMessage {
+            vin: *b"WVWZZZ1JZXW000001",
+            logical_address: [0x10, 0x00],
+            eid: [0, 0, 0, 0, 0, 1],
+            gid: [0, 0, 0, 0, 0, 1],
+            further_action: ActionCode::NoFurtherActionRequired,
+            vin_gid_sync: None,
+        }
+    }
+
+    fn identification_request() -> Vec<u8> {
+        DoipMessageBuilder::new()
+            .payload(DoipPayload::VehicleIdentificationRequest(
+                VehicleIdentificationRequest {},
+            ))
+            .build()
+            .try_into()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_serve_handler() {
+        let config = DoipServerConfig {
+            announce_address: None,
+            ..DoipServerConfig::new(EntityConfig::default(), announcement())
+        };
+
+        let server = DoipServer::bind("127.0.0.1:0".parse().unwrap(), config, Echo)
+            .await
+            .unwrap();
+        let address = server.local_addr().unwrap();
+        tokio::spawn(server.run());
+
+        let udp = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        udp.send_to(&identification_request(), address)
+            .await
+            .unwrap();
+
+        let mut datagram = [0u8; 64];
+        let len = udp.recv(&mut datagram).await.unwrap();
+        assert!(matches!(
+            DoipMessage::try_from(&datagram[..len]).unwrap().payload,
+            DoipPayload::VehicleAnnouncementMessage(_)
+        ));
+
+        let client = DoipClient::connect(address, DoipClientConfig::default())
+            .await
+            .unwrap();
+        assert_eq!(
+            client
+                .send_diagnostic([0x10, 0x00], &[0x22, 0xF1, 0x90])
+                .await
+                .unwrap(),
+            vec![0x62, 0xF1, 0x90]
+        );
+        assert!(matches!(
+            client.send_diagnostic([0x10, 0x01], &[0x3E, 0x00]).await,
+            Err(Error::DiagnosticNack { .. })
+        ));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs an IPv6 multicast capable interface, whose index is set in DOIP_TEST_SCOPE_ID"]
+    async fn test_ipv6_multicast() {
+        let scope_id: u32 = std::env::var("DOIP_TEST_SCOPE_ID")
+            .expect("DOIP_TEST_SCOPE_ID is not set")
+            .parse()
+            .unwrap();
+
+        let config = DoipServerConfig::ipv6(EntityConfig::default(), announcement(), scope_id);
+        assert_eq!(
+            config.announce_address,
+            Some(SocketAddrV6::new(DOIP_IPV6_MULTICAST, 13400, 0, scope_id).into())
+        );
+
+        let server = DoipServer::bind("[::1]:0".parse().unwrap(), config, Echo)
+            .await
+            .unwrap();
+        let port = server.local_addr().unwrap().port();
+        tokio::spawn(server.run());
+
+        let udp = UdpSocket::bind("[::]:0").await.unwrap();
+        let group = SocketAddr::from(SocketAddrV6::new(DOIP_IPV6_MULTICAST, port, 0, scope_id));
+        udp.send_to(&identification_request(), group).await.unwrap();
+
+        let mut datagram = [0u8; 64];
+        let len = tokio::time::timeout(Duration::from_secs(2), udp.recv(&mut datagram))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(
+            DoipMessage::try_from(&datagram[..len]).unwrap().payload,
+            DoipPayload::VehicleAnnouncementMessage(_)
+        ));
+    }
+}
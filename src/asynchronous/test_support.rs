@@ -0,0 +1,65 @@
+use crate::{
+    asynchronous::{DiagnosticHandler, Response},
+    gateway::LogicalAddress,
+    uds::{NegativeResponse, NegativeResponseCode, POSITIVE_RESPONSE_OFFSET},
+};
+
+/// Answers every request with its positive response, echoing the request.
+///
+/// Empty requests and service identifiers without a positive response
+/// identifier are answered with a negative response.
+pub(crate) struct Echo;
+
+impl DiagnosticHandler for Echo {
+    async fn handle(&self, _: LogicalAddress, _: LogicalAddress, uds: &[u8]) -> Response {
+        let negative = |service_id, response_code| {
+            let response: [u8; 3] = NegativeResponse {
+                service_id,
+                response_code,
+            }
+            .into();
+            Response::Uds(response.to_vec())
+        };
+
+        match uds.first() {
+            None => negative(
+                0x00,
+                NegativeResponseCode::IncorrectMessageLengthOrInvalidFormat,
+            ),
+            Some(&service_id) => match service_id.checked_add(POSITIVE_RESPONSE_OFFSET) {
+                Some(response_id) => {
+                    let mut response = uds.to_vec();
+                    response[0] = response_id;
+                    Response::Uds(response)
+                }
+                None => negative(service_id, NegativeResponseCode::ServiceNotSupported),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        asynchronous::{test_support::Echo, DiagnosticHandler, Response},
+        uds::NEGATIVE_RESPONSE_SID,
+    };
+
+    #[tokio::test]
+    async fn test_echo() {
+        let address = [0x10, 0x00];
+
+        assert!(matches!(
+            Echo.handle(address, address, &[0x22, 0xF1, 0x90]).await,
+            Response::Uds(response) if response == [0x62, 0xF1, 0x90]
+        ));
+        assert!(matches!(
+            Echo.handle(address, address, &[]).await,
+            Response::Uds(response) if response == [NEGATIVE_RESPONSE_SID, 0x00, 0x13]
+        ));
+        assert!(matches!(
+            Echo.handle(address, address, &[0xC0]).await,
+            Response::Uds(response) if response == [NEGATIVE_RESPONSE_SID, 0xC0, 0x11]
+        ));
+    }
+}
@@ -0,0 +1,284 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Instant;
+
+use rustls::{
+    client::WantsClientCert,
+    crypto::{ring, CryptoProvider},
+    pki_types::{CertificateDer, PrivateKeyDer, ServerName},
+    server::WebPkiClientVerifier,
+    ClientConfig, ConfigBuilder, RootCertStore, ServerConfig,
+};
+use tokio_rustls::TlsConnector;
+
+use crate::{
+    asynchronous::{connect, Stream},
+    error::{Error, Result},
+    transport::DOIP_TLS_NET_PORT,
+};
+
+pub use rustls;
+
+/// TLS configuration of a [`DoipClient`](crate::asynchronous::DoipClient).
+#[derive(Debug, Clone)]
+pub struct TlsClientConfig {
+    /// rustls configuration holding the trusted roots and, for mutual
+    /// authentication, the certificate of the tester
+    pub rustls: Arc<ClientConfig>,
+
+    /// Name the certificate of the entity is verified against
+    pub server_name: ServerName<'static>,
+
+    /// Port of the TLS socket of the entity
+    pub port: u16,
+
+    /// Connects with TLS right away instead of after the entity denies routing
+    /// with `DeniedRequestEncryptedTLSConnection`
+    pub immediate: bool,
+}
+
+impl TlsClientConfig {
+    /// Creates a `TlsClientConfig` verifying the certificate of `server_name`
+    /// against `roots`, connecting to `DOIP_TLS_PORT` once the entity requests
+    /// TLS.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Tls`] when a root certificate is invalid.
+    pub fn new(
+        server_name: ServerName<'static>,
+        roots: Vec<CertificateDer<'static>>,
+    ) -> Result<Self> {
+        let config = client_builder(roots)?.with_no_client_auth();
+
+        Ok(Self::from_rustls(config, server_name))
+    }
+
+    /// Creates a `TlsClientConfig` like [`TlsClientConfig::new`] which
+    /// authenticates the tester with `certificates` for mutual authentication.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Tls`] when a root certificate or the private key is
+    /// invalid.
+    pub fn mutual(
+        server_name: ServerName<'static>,
+        roots: Vec<CertificateDer<'static>>,
+        certificates: Vec<CertificateDer<'static>>,
+        private_key: PrivateKeyDer<'static>,
+    ) -> Result<Self> {
+        let config = client_builder(roots)?.with_client_auth_cert(certificates, private_key)?;
+
+        Ok(Self::from_rustls(config, server_name))
+    }
+
+    fn from_rustls(config: ClientConfig, server_name: ServerName<'static>) -> Self {
+        Self {
+            rustls: Arc::new(config),
+            server_name,
+            port: DOIP_TLS_NET_PORT,
+            immediate: false,
+        }
+    }
+}
+
+/// TLS configuration of a [`DoipServer`](crate::asynchronous::DoipServer).
+///
+/// Which activation types require TLS is configured in
+/// [`EntityConfig::encrypted_activation_types`](crate::entity::EntityConfig::encrypted_activation_types).
+#[derive(Debug, Clone)]
+pub struct TlsServerConfig {
+    /// rustls configuration holding the certificate of the entity and, for
+    /// mutual authentication, the roots trusted for tester certificates
+    pub rustls: Arc<ServerConfig>,
+
+    /// Port of the TLS listener, `0` picks a free port
+    pub port: u16,
+}
+
+impl TlsServerConfig {
+    /// Creates a `TlsServerConfig` presenting `certificates` on `DOIP_TLS_PORT`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Tls`] when the private key is invalid.
+    pub fn new(
+        certificates: Vec<CertificateDer<'static>>,
+        private_key: PrivateKeyDer<'static>,
+    ) -> Result<Self> {
+        let config = ServerConfig::builder_with_provider(provider())
+            .with_safe_default_protocol_versions()?
+            .with_no_client_auth()
+            .with_single_cert(certificates, private_key)?;
+
+        Ok(Self::from_rustls(config))
+    }
+
+    /// Creates a `TlsServerConfig` like [`TlsServerConfig::new`] which only
+    /// accepts testers presenting a certificate issued by `client_roots`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Tls`] when a root certificate or the private key is
+    /// invalid.
+    pub fn mutual(
+        certificates: Vec<CertificateDer<'static>>,
+        private_key: PrivateKeyDer<'static>,
+        client_roots: Vec<CertificateDer<'static>>,
+    ) -> Result<Self> {
+        let verifier = WebPkiClientVerifier::builder_with_provider(
+            Arc::new(root_store(client_roots)?),
+            provider(),
+        )
+        .build()
+        .map_err(|err| rustls::Error::General(err.to_string()))?;
+
+        let config = ServerConfig::builder_with_provider(provider())
+            .with_safe_default_protocol_versions()?
+            .with_client_cert_verifier(verifier)
+            .with_single_cert(certificates, private_key)?;
+
+        Ok(Self::from_rustls(config))
+    }
+
+    fn from_rustls(config: ServerConfig) -> Self {
+        Self {
+            rustls: Arc::new(config),
+            port: DOIP_TLS_NET_PORT,
+        }
+    }
+}
+
+/// Connects to the TLS socket of the entity at `address` and completes the
+/// handshake, failing with [`Error::Timeout`] after `deadline`.
+pub(super) async fn connect_tls(
+    address: SocketAddr,
+    config: &TlsClientConfig,
+    deadline: Instant,
+) -> Result<Box<dyn Stream>> {
+    let stream = connect(SocketAddr::new(address.ip(), config.port), deadline).await?;
+    let handshake =
+        TlsConnector::from(Arc::clone(&config.rustls)).connect(config.server_name.clone(), stream);
+
+    let stream = tokio::time::timeout_at(deadline.into(), handshake)
+        .await
+        .map_err(|_| Error::Timeout)??;
+
+    Ok(Box::new(stream))
+}
+
+fn provider() -> Arc<CryptoProvider> {
+    Arc::new(ring::default_provider())
+}
+
+fn root_store(roots: Vec<CertificateDer<'static>>) -> Result<RootCertStore> {
+    let mut store = RootCertStore::empty();
+    for root in roots {
+        store.add(root)?;
+    }
+
+    Ok(store)
+}
+
+fn client_builder(
+    roots: Vec<CertificateDer<'static>>,
+) -> Result<ConfigBuilder<ClientConfig, WantsClientCert>> {
+    Ok(ClientConfig::builder_with_provider(provider())
+        .with_safe_default_protocol_versions()?
+        .with_root_certificates(root_store(roots)?))
+}
+
+#[cfg(test)]
+mod test {
+    use rcgen::{generate_simple_self_signed, CertifiedKey};
+    use rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer};
+
+    use crate::{
+        asynchronous::{
+            test_support::Echo, DoipClient, DoipClientConfig, DoipServer, DoipServerConfig,
+            TlsClientConfig, TlsServerConfig,
+        },
+        entity::EntityConfig,
+        error::Error,
+        payload::{ActionCode, ActivationCode, ActivationType, VehicleAnnouncementMessage},
+    };
+
+    fn identity(name: &str) -> (CertificateDer<'static>, PrivateKeyDer<'static>) {
+        let CertifiedKey { cert, key_pair } = generate_simple_self_signed([name.into()]).unwrap();
+
+        (
+            cert.der().clone(),
+            PrivatePkcs8KeyDer::from(key_pair.serialize_der()).into(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_upgrade_to_mutual_tls() {
+        let (entity_cert, entity_key) = identity("localhost");
+        let (tester_cert, tester_key) = identity("tester");
+
+        let announcement = VehicleAnnouncementMessage {
+            vin: *b"WVWZZZ1JZXW000001",
+            logical_address: [0x10, 0x00],
+            eid: [0, 0, 0, 0, 0, 1],
+            gid: [0, 0, 0, 0, 0, 1],
+            further_action: ActionCode::NoFurtherActionRequired,
+            vin_gid_sync: None,
+        };
+        let entity = EntityConfig {
+            encrypted_activation_types: vec![ActivationType::Default],
+            ..EntityConfig::default()
+        };
+        let config = DoipServerConfig {
+            announce_address: None,
+            ..DoipServerConfig::new(entity, announcement)
+        };
+
+        let server = DoipServer::bind("127.0.0.1:0".parse().unwrap(), config, Echo)
+            .await
+            .unwrap()
+            .listen_tls(TlsServerConfig {
+                port: 0,
+                ..TlsServerConfig::mutual(
+                    vec![entity_cert.clone()],
+                    entity_key,
+                    vec![tester_cert.clone()],
+                )
+                .unwrap()
+            })
+            .await
+            .unwrap();
+        let address = server.local_addr().unwrap();
+        let tls_port = server.local_tls_addr().unwrap().port();
+        tokio::spawn(server.run());
+
+        assert!(matches!(
+            DoipClient::connect(address, DoipClientConfig::default()).await,
+            Err(Error::RoutingActivationDenied {
+                activation_code: ActivationCode::DeniedRequestEncryptedTLSConnection
+            })
+        ));
+
+        let tls = TlsClientConfig {
+            port: tls_port,
+            ..TlsClientConfig::mutual(
+                "localhost".try_into().unwrap(),
+                vec![entity_cert],
+                vec![tester_cert],
+                tester_key,
+            )
+            .unwrap()
+        };
+        let client = DoipClient::connect_tls(address, DoipClientConfig::default(), tls)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            client
+                .send_diagnostic([0x10, 0x00], &[0x3E, 0x00])
+                .await
+                .unwrap(),
+            vec![0x7E, 0x00]
+        );
+    }
+}
@@ -0,0 +1,689 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use crate::{
+    definitions::{DOIP_DIAG_COMMON_SOURCE_LEN, DOIP_DIAG_COMMON_TARGET_LEN},
+    message::DoipMessage,
+    payload::{
+        DiagnosticMessage, DiagnosticMessageAck, DiagnosticMessageNack, DiagnosticNackCode,
+        DoipPayload,
+    },
+    timing::{A_DOIP_DIAGNOSTIC_MESSAGE, A_PROCESSING_TIME, P2_STAR_SERVER_MAX},
+    uds::is_response_pending,
+};
+
+/// UDS service identifier of a negative response.
+const NEGATIVE_RESPONSE_SID: u8 = 0x7F;
+
+/// Offset between a UDS request and its positive response service identifier.
+const POSITIVE_RESPONSE_OFFSET: u8 = 0x40;
+
+/// Identifies a request registered with a [`DiagnosticCorrelator`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RequestId(pub u64);
+
+/// Outcome of a registered request, raised by a [`DiagnosticCorrelator`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum CorrelationEvent {
+    /// The request was positively acknowledged, a response is now awaited
+    Acknowledged {
+        /// The acknowledged request
+        id: RequestId,
+    },
+
+    /// The request was negatively acknowledged and is completed
+    Rejected {
+        /// The rejected request
+        id: RequestId,
+
+        /// The negative acknowledgement code
+        nack_code: DiagnosticNackCode,
+    },
+
+    /// The target answered with `requestCorrectlyReceived-ResponsePending`,
+    /// the final response is now awaited for the response pending timeout
+    ResponsePending {
+        /// The request still being processed
+        id: RequestId,
+    },
+
+    /// The target responded, the request is completed
+    Response {
+        /// The answered request
+        id: RequestId,
+
+        /// The response of the target
+        message: DiagnosticMessage,
+    },
+
+    /// The response timeout of a functional request expired, the request is
+    /// completed with every response received until then
+    Responses {
+        /// The answered functional request
+        id: RequestId,
+
+        /// The final responses of the targets, in order of arrival
+        messages: Vec<DiagnosticMessage>,
+    },
+
+    /// No acknowledgement was received within `A_DoIP_Diagnostic_Message`
+    AckTimeout {
+        /// The unacknowledged request
+        id: RequestId,
+    },
+
+    /// No response was received within the response timeout
+    ResponseTimeout {
+        /// The unanswered request
+        id: RequestId,
+    },
+
+    /// A `DiagnosticMessage` was received which matches no outstanding request
+    Unsolicited(DiagnosticMessage),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stage {
+    AwaitingAck,
+    AwaitingResponse,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct PendingRequest {
+    id: RequestId,
+    source_address: [u8; DOIP_DIAG_COMMON_SOURCE_LEN],
+    target_address: [u8; DOIP_DIAG_COMMON_TARGET_LEN],
+    request: Vec<u8>,
+    stage: Stage,
+    deadline: Instant,
+    functional: Option<Vec<DiagnosticMessage>>,
+}
+
+impl PendingRequest {
+    /// Returns true when `ack` or `nack` addresses travel in the opposite
+    /// direction of this request.
+    fn is_answered_by(
+        &self,
+        source_address: [u8; DOIP_DIAG_COMMON_SOURCE_LEN],
+        target_address: [u8; DOIP_DIAG_COMMON_TARGET_LEN],
+    ) -> bool {
+        self.source_address == target_address && self.target_address == source_address
+    }
+
+    /// Returns true when a response from any target to `target_address` may
+    /// belong to this functional request.
+    fn collects(&self, target_address: [u8; DOIP_DIAG_COMMON_SOURCE_LEN]) -> bool {
+        self.functional.is_some() && self.source_address == target_address
+    }
+
+    /// Returns true when the UDS `response` belongs to this request's service.
+    fn is_response(&self, response: &[u8]) -> bool {
+        let (Some(sid), Some(response_sid)) = (self.request.first(), response.first()) else {
+            return true;
+        };
+
+        if *response_sid == NEGATIVE_RESPONSE_SID {
+            return response.get(1) == Some(sid);
+        }
+
+        *response_sid == sid.wrapping_add(POSITIVE_RESPONSE_OFFSET)
+    }
+}
+
+/// Pairs outgoing `DiagnosticMessage`s with their acknowledgement and response.
+///
+/// Each sent request is registered with [`DiagnosticCorrelator::register`].
+/// Received `DiagnosticMessageAck`, `DiagnosticMessageNack` and `DiagnosticMessage`
+/// frames are matched on their source and target addresses, the echo in
+/// `previous_message` and the UDS service identifier of the response, so that
+/// several targets may be addressed concurrently.
+///
+/// A response with the negative response code
+/// `requestCorrectlyReceived-ResponsePending` (0x78) does not complete its
+/// request, instead the deadline is extended by the response pending timeout,
+/// `P2*Server_max` by default, for every such response until the final
+/// positive or negative response arrives.
+///
+/// Requests to a functional address are registered with
+/// [`DiagnosticCorrelator::register_functional`]. As every ECU of the group
+/// answers from its own physical address, responses of the same service from
+/// any target are collected until the response timeout expires, extended by
+/// the response pending timeout of each pending response, and the request is
+/// completed with [`CorrelationEvent::Responses`]. Responses to a request sent
+/// to the physical address of their target take precedence.
+///
+/// ```rust
+/// use std::time::Instant;
+/// use doip_definitions::correlation::{CorrelationEvent, DiagnosticCorrelator};
+/// use doip_definitions::payload::DiagnosticMessage;
+///
+/// let now = Instant::now();
+/// let mut correlator = DiagnosticCorrelator::new();
+///
+/// let id = correlator.register(
+///     now,
+///     &DiagnosticMessage {
+///         source_address: [0x0E, 0x00],
+///         target_address: [0x10, 0x01],
+///         message: vec![0x22, 0xF1, 0x90],
+///     },
+/// );
+///
+/// correlator.handle_diagnostic_message(
+///     now,
+///     DiagnosticMessage {
+///         source_address: [0x10, 0x01],
+///         target_address: [0x0E, 0x00],
+///         message: vec![0x62, 0xF1, 0x90],
+///     },
+/// );
+///
+/// assert!(matches!(
+///     correlator.poll_event(),
+///     Some(CorrelationEvent::Response { id: response, .. }) if response == id
+/// ));
+/// ```
+#[derive(Debug)]
+pub struct DiagnosticCorrelator {
+    ack_timeout: Duration,
+    response_timeout: Duration,
+    response_pending_timeout: Duration,
+    target_timeouts: BTreeMap<[u8; DOIP_DIAG_COMMON_TARGET_LEN], (Duration, Duration)>,
+    next_id: u64,
+    pending: Vec<PendingRequest>,
+    events: VecDeque<CorrelationEvent>,
+}
+
+impl Default for DiagnosticCorrelator {
+    fn default() -> Self {
+        Self {
+            ack_timeout: A_DOIP_DIAGNOSTIC_MESSAGE,
+            response_timeout: A_PROCESSING_TIME,
+            response_pending_timeout: P2_STAR_SERVER_MAX,
+            target_timeouts: BTreeMap::new(),
+            next_id: 0,
+            pending: Vec::new(),
+            events: VecDeque::new(),
+        }
+    }
+}
+
+impl DiagnosticCorrelator {
+    /// Creates a new `DiagnosticCorrelator` waiting `A_DoIP_Diagnostic_Message`
+    /// for acknowledgements, `A_Processing_Time` for responses and
+    /// `P2*Server_max` after each pending response.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the time waited for a `DiagnosticMessageAck` or `DiagnosticMessageNack`.
+    #[must_use]
+    pub fn ack_timeout(mut self, ack_timeout: Duration) -> Self {
+        self.ack_timeout = ack_timeout;
+        self
+    }
+
+    /// Sets the time waited for the response after the acknowledgement.
+    #[must_use]
+    pub fn response_timeout(mut self, response_timeout: Duration) -> Self {
+        self.response_timeout = response_timeout;
+        self
+    }
+
+    /// Sets the time waited for the final response after each
+    /// `requestCorrectlyReceived-ResponsePending` response.
+    #[must_use]
+    pub fn response_pending_timeout(mut self, response_pending_timeout: Duration) -> Self {
+        self.response_pending_timeout = response_pending_timeout;
+        self
+    }
+
+    /// Overrides the response and response pending timeouts of requests to
+    /// `target_address`, e.g. with the timings of its active session.
+    pub fn set_target_timeouts(
+        &mut self,
+        target_address: [u8; DOIP_DIAG_COMMON_TARGET_LEN],
+        response_timeout: Duration,
+        response_pending_timeout: Duration,
+    ) {
+        self.target_timeouts
+            .insert(target_address, (response_timeout, response_pending_timeout));
+    }
+
+    /// Returns the requests to `target_address` to the default timeouts.
+    pub fn clear_target_timeouts(&mut self, target_address: [u8; DOIP_DIAG_COMMON_TARGET_LEN]) {
+        self.target_timeouts.remove(&target_address);
+    }
+
+    /// Returns the response and response pending timeouts of `target_address`.
+    fn timeouts(&self, target_address: [u8; DOIP_DIAG_COMMON_TARGET_LEN]) -> (Duration, Duration) {
+        self.target_timeouts
+            .get(&target_address)
+            .copied()
+            .unwrap_or((self.response_timeout, self.response_pending_timeout))
+    }
+
+    /// Returns the number of outstanding requests.
+    #[must_use]
+    pub fn outstanding(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Registers a request which is sent at `now`.
+    pub fn register(&mut self, now: Instant, request: &DiagnosticMessage) -> RequestId {
+        self.push(now, request, None)
+    }
+
+    /// Registers a request to a functional address which is sent at `now`,
+    /// collecting the responses of every target until the response timeout
+    /// expires.
+    pub fn register_functional(&mut self, now: Instant, request: &DiagnosticMessage) -> RequestId {
+        self.push(now, request, Some(Vec::new()))
+    }
+
+    fn push(
+        &mut self,
+        now: Instant,
+        request: &DiagnosticMessage,
+        functional: Option<Vec<DiagnosticMessage>>,
+    ) -> RequestId {
+        let id = RequestId(self.next_id);
+        self.next_id = self.next_id.wrapping_add(1);
+
+        self.pending.push(PendingRequest {
+            id,
+            source_address: request.source_address,
+            target_address: request.target_address,
+            request: request.message.clone(),
+            stage: Stage::AwaitingAck,
+            deadline: now + self.ack_timeout,
+            functional,
+        });
+
+        id
+    }
+
+    /// Stops tracking the request `id`, no further events are raised for it.
+    pub fn cancel(&mut self, id: RequestId) {
+        self.pending.retain(|pending| pending.id != id);
+    }
+
+    /// Processes a received message, ignoring every payload type other than
+    /// `DiagnosticMessage`, `DiagnosticMessageAck` and `DiagnosticMessageNack`.
+    pub fn handle_message(&mut self, now: Instant, message: DoipMessage) {
+        match message.payload {
+            DoipPayload::DiagnosticMessage(diagnostic) => {
+                self.handle_diagnostic_message(now, diagnostic);
+            }
+            DoipPayload::DiagnosticMessageAck(ack) => self.handle_ack(now, &ack),
+            DoipPayload::DiagnosticMessageNack(nack) => self.handle_nack(&nack),
+            _ => {}
+        }
+    }
+
+    /// Matches a `DiagnosticMessageAck` to the oldest request awaiting its
+    /// acknowledgement, preferring requests echoed in `previous_message`.
+    pub fn handle_ack(&mut self, now: Instant, ack: &DiagnosticMessageAck) {
+        let candidates = |pending: &&mut PendingRequest| {
+            pending.stage == Stage::AwaitingAck
+                && pending.is_answered_by(ack.source_address, ack.target_address)
+        };
+
+        let echoed = !ack.previous_message.is_empty();
+        let (response_timeout, _) = self.timeouts(ack.source_address);
+
+        let found = self
+            .pending
+            .iter_mut()
+            .filter(candidates)
+            .find(|pending| !echoed || pending.request.starts_with(&ack.previous_message));
+
+        if let Some(pending) = found {
+            pending.stage = Stage::AwaitingResponse;
+            pending.deadline = now + response_timeout;
+
+            self.events
+                .push_back(CorrelationEvent::Acknowledged { id: pending.id });
+        }
+    }
+
+    /// Matches a `DiagnosticMessageNack` to the oldest request awaiting its
+    /// acknowledgement and completes it.
+    pub fn handle_nack(&mut self, nack: &DiagnosticMessageNack) {
+        let index = self.pending.iter().position(|pending| {
+            pending.stage == Stage::AwaitingAck
+                && pending.is_answered_by(nack.source_address, nack.target_address)
+        });
+
+        if let Some(index) = index {
+            let pending = self.pending.remove(index);
+
+            self.events.push_back(CorrelationEvent::Rejected {
+                id: pending.id,
+                nack_code: nack.nack_code,
+            });
+        }
+    }
+
+    /// Matches a response from a target to the oldest request of the same
+    /// service, raising [`CorrelationEvent::Unsolicited`] when none matches.
+    ///
+    /// A response completes its request even when the acknowledgement was lost,
+    /// except for `requestCorrectlyReceived-ResponsePending` which keeps the
+    /// request outstanding and raises [`CorrelationEvent::ResponsePending`].
+    /// Responses to a functional request are collected until it times out.
+    pub fn handle_diagnostic_message(&mut self, now: Instant, message: DiagnosticMessage) {
+        let physical = self.pending.iter().position(|pending| {
+            pending.functional.is_none()
+                && pending.is_answered_by(message.source_address, message.target_address)
+                && pending.is_response(&message.message)
+        });
+        let functional = || {
+            self.pending.iter().position(|pending| {
+                pending.collects(message.target_address) && pending.is_response(&message.message)
+            })
+        };
+
+        let Some(index) = physical.or_else(functional) else {
+            self.events
+                .push_back(CorrelationEvent::Unsolicited(message));
+            return;
+        };
+
+        if self.pending[index].functional.is_some() {
+            self.collect(now, index, message);
+            return;
+        }
+
+        if is_response_pending(&message.message) {
+            let (_, response_pending_timeout) = self.timeouts(message.source_address);
+            let pending = &mut self.pending[index];
+            pending.stage = Stage::AwaitingResponse;
+            pending.deadline = now + response_pending_timeout;
+
+            self.events
+                .push_back(CorrelationEvent::ResponsePending { id: pending.id });
+            return;
+        }
+
+        let pending = self.pending.remove(index);
+        self.events.push_back(CorrelationEvent::Response {
+            id: pending.id,
+            message,
+        });
+    }
+
+    /// Adds a response to the functional request at `index`, a pending response
+    /// extends its deadline by the response pending timeout of the target.
+    fn collect(&mut self, now: Instant, index: usize, message: DiagnosticMessage) {
+        let (response_timeout, _) = self.timeouts(self.pending[index].target_address);
+        let (_, response_pending_timeout) = self.timeouts(message.source_address);
+        let pending = &mut self.pending[index];
+
+        if pending.stage == Stage::AwaitingAck {
+            pending.stage = Stage::AwaitingResponse;
+            pending.deadline = now + response_timeout;
+        }
+
+        if is_response_pending(&message.message) {
+            pending.deadline = pending.deadline.max(now + response_pending_timeout);
+
+            self.events
+                .push_back(CorrelationEvent::ResponsePending { id: pending.id });
+            return;
+        }
+
+        if let Some(messages) = &mut pending.functional {
+            messages.push(message);
+        }
+    }
+
+    /// Processes every timer which expired at or before `now`.
+    pub fn handle_timeout(&mut self, now: Instant) {
+        let (expired, pending): (Vec<_>, Vec<_>) = self
+            .pending
+            .drain(..)
+            .partition(|pending| pending.deadline <= now);
+        self.pending = pending;
+
+        for pending in expired {
+            self.events
+                .push_back(match (pending.stage, pending.functional) {
+                    (Stage::AwaitingAck, _) => CorrelationEvent::AckTimeout { id: pending.id },
+                    (Stage::AwaitingResponse, Some(messages)) => CorrelationEvent::Responses {
+                        id: pending.id,
+                        messages,
+                    },
+                    (Stage::AwaitingResponse, None) => {
+                        CorrelationEvent::ResponseTimeout { id: pending.id }
+                    }
+                });
+        }
+    }
+
+    /// Returns the next instant at which [`DiagnosticCorrelator::handle_timeout`]
+    /// must be called.
+    #[must_use]
+    pub fn poll_timeout(&self) -> Option<Instant> {
+        self.pending.iter().map(|pending| pending.deadline).min()
+    }
+
+    /// Returns the next event for the application.
+    pub fn poll_event(&mut self) -> Option<CorrelationEvent> {
+        self.events.pop_front()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, Instant};
+
+    use crate::{
+        correlation::{CorrelationEvent, DiagnosticCorrelator},
+        payload::{DiagnosticAckCode, DiagnosticMessage, DiagnosticMessageAck},
+    };
+
+    fn request(target: u8, message: &[u8]) -> DiagnosticMessage {
+        DiagnosticMessage {
+            source_address: [0x0E, 0x00],
+            target_address: [0x10, target],
+            message: message.to_vec(),
+        }
+    }
+
+    fn response(source: u8, message: &[u8]) -> DiagnosticMessage {
+        DiagnosticMessage {
+            source_address: [0x10, source],
+            target_address: [0x0E, 0x00],
+            message: message.to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_concurrent_targets() {
+        let now = Instant::now();
+        let mut correlator = DiagnosticCorrelator::new();
+
+        let first = correlator.register(now, &request(1, &[0x22, 0xF1, 0x90]));
+        let second = correlator.register(now, &request(2, &[0x10, 0x03]));
+
+        correlator.handle_ack(
+            now,
+            &DiagnosticMessageAck {
+                source_address: [0x10, 0x02],
+                target_address: [0x0E, 0x00],
+                ack_code: DiagnosticAckCode::Acknowledged,
+                previous_message: vec![0x10, 0x03],
+            },
+        );
+        assert_eq!(
+            correlator.poll_event(),
+            Some(CorrelationEvent::Acknowledged { id: second })
+        );
+
+        correlator.handle_diagnostic_message(now, response(2, &[0x50, 0x03]));
+        correlator.handle_diagnostic_message(now, response(1, &[0x7F, 0x22, 0x31]));
+        correlator.handle_diagnostic_message(now, response(1, &[0x62, 0xF1, 0x90]));
+
+        assert!(matches!(
+            correlator.poll_event(),
+            Some(CorrelationEvent::Response { id, .. }) if id == second
+        ));
+        assert!(matches!(
+            correlator.poll_event(),
+            Some(CorrelationEvent::Response { id, .. }) if id == first
+        ));
+        assert!(matches!(
+            correlator.poll_event(),
+            Some(CorrelationEvent::Unsolicited(_))
+        ));
+        assert_eq!(correlator.outstanding(), 0);
+    }
+
+    #[test]
+    fn test_functional_request() {
+        let now = Instant::now();
+        let mut correlator = DiagnosticCorrelator::new()
+            .response_timeout(Duration::from_secs(2))
+            .response_pending_timeout(Duration::from_secs(5));
+
+        let functional = DiagnosticMessage {
+            source_address: [0x0E, 0x00],
+            target_address: [0xE4, 0x00],
+            message: vec![0x22, 0xF1, 0x90],
+        };
+        let id = correlator.register_functional(now, &functional);
+        let physical = correlator.register(now, &request(2, &[0x22, 0xF1, 0x90]));
+
+        correlator.handle_ack(
+            now,
+            &DiagnosticMessageAck {
+                source_address: [0xE4, 0x00],
+                target_address: [0x0E, 0x00],
+                ack_code: DiagnosticAckCode::Acknowledged,
+                previous_message: vec![0x22, 0xF1, 0x90],
+            },
+        );
+        assert_eq!(
+            correlator.poll_event(),
+            Some(CorrelationEvent::Acknowledged { id })
+        );
+
+        // The physical request takes the response of its own target.
+        correlator.handle_diagnostic_message(now, response(2, &[0x62, 0xF1, 0x90, 0x02]));
+        assert!(matches!(
+            correlator.poll_event(),
+            Some(CorrelationEvent::Response { id, .. }) if id == physical
+        ));
+
+        let at = now + Duration::from_secs(1);
+        correlator.handle_diagnostic_message(at, response(1, &[0x62, 0xF1, 0x90, 0x01]));
+        correlator.handle_diagnostic_message(at, response(3, &[0x7F, 0x22, 0x78]));
+        correlator.handle_diagnostic_message(at, response(4, &[0x7F, 0x22, 0x31]));
+        correlator.handle_diagnostic_message(at, response(5, &[0x50, 0x03]));
+        assert_eq!(
+            correlator.poll_event(),
+            Some(CorrelationEvent::ResponsePending { id })
+        );
+        assert!(matches!(
+            correlator.poll_event(),
+            Some(CorrelationEvent::Unsolicited(message)) if message.source_address == [0x10, 0x05]
+        ));
+        assert_eq!(correlator.poll_timeout(), Some(at + Duration::from_secs(5)));
+
+        correlator.handle_diagnostic_message(
+            now + Duration::from_secs(4),
+            response(3, &[0x62, 0xF1, 0x90, 0x03]),
+        );
+        correlator.handle_timeout(now + Duration::from_secs(5));
+        assert_eq!(correlator.poll_event(), None);
+
+        correlator.handle_timeout(at + Duration::from_secs(5));
+        assert_eq!(
+            correlator.poll_event(),
+            Some(CorrelationEvent::Responses {
+                id,
+                messages: vec![
+                    response(1, &[0x62, 0xF1, 0x90, 0x01]),
+                    response(4, &[0x7F, 0x22, 0x31]),
+                    response(3, &[0x62, 0xF1, 0x90, 0x03]),
+                ],
+            })
+        );
+        assert_eq!(correlator.outstanding(), 0);
+    }
+
+    #[test]
+    fn test_timeouts() {
+        let now = Instant::now();
+        let mut correlator = DiagnosticCorrelator::new();
+
+        let id = correlator.register(now, &request(1, &[0x3E, 0x00]));
+        correlator.handle_timeout(now + Duration::from_secs(1));
+        assert_eq!(correlator.poll_event(), None);
+
+        correlator.handle_timeout(now + Duration::from_secs(2));
+        assert_eq!(
+            correlator.poll_event(),
+            Some(CorrelationEvent::AckTimeout { id })
+        );
+    }
+
+    #[test]
+    fn test_response_pending() {
+        let now = Instant::now();
+        let mut correlator = DiagnosticCorrelator::new()
+            .response_timeout(Duration::from_secs(2))
+            .response_pending_timeout(Duration::from_secs(5));
+
+        let id = correlator.register(now, &request(1, &[0x31, 0x01, 0xFF, 0x00]));
+        correlator.handle_ack(
+            now,
+            &DiagnosticMessageAck {
+                source_address: [0x10, 0x01],
+                target_address: [0x0E, 0x00],
+                ack_code: DiagnosticAckCode::Acknowledged,
+                previous_message: vec![],
+            },
+        );
+        assert_eq!(
+            correlator.poll_event(),
+            Some(CorrelationEvent::Acknowledged { id })
+        );
+
+        for elapsed in [1, 5, 9] {
+            let at = now + Duration::from_secs(elapsed);
+            correlator.handle_timeout(at);
+            correlator.handle_diagnostic_message(at, response(1, &[0x7F, 0x31, 0x78]));
+
+            assert_eq!(
+                correlator.poll_event(),
+                Some(CorrelationEvent::ResponsePending { id })
+            );
+            assert_eq!(correlator.poll_timeout(), Some(at + Duration::from_secs(5)));
+        }
+
+        correlator.handle_diagnostic_message(
+            now + Duration::from_secs(12),
+            response(1, &[0x71, 0x01, 0xFF, 0x00]),
+        );
+        assert!(matches!(
+            correlator.poll_event(),
+            Some(CorrelationEvent::Response { id: response, .. }) if response == id
+        ));
+        assert_eq!(correlator.outstanding(), 0);
+
+        let id = correlator.register(now, &request(1, &[0x11, 0x01]));
+        correlator.handle_diagnostic_message(now, response(1, &[0x7F, 0x11, 0x78]));
+        correlator.handle_timeout(now + Duration::from_secs(5));
+        assert_eq!(
+            correlator.poll_event(),
+            Some(CorrelationEvent::ResponsePending { id })
+        );
+        assert_eq!(
+            correlator.poll_event(),
+            Some(CorrelationEvent::ResponseTimeout { id })
+        );
+    }
+}
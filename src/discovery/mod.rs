@@ -0,0 +1,377 @@
+use std::collections::VecDeque;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+use crate::{
+    builder::DoipMessageBuilder,
+    definitions::{
+        DOIP_COMMON_EID_LEN, DOIP_COMMON_VIN_LEN, DOIP_DIAG_COMMON_SOURCE_LEN,
+        DOIP_VEHICLE_ANNOUNCEMENT_GID_LEN,
+    },
+    header::ProtocolVersion,
+    message::DoipMessage,
+    payload::{
+        ActionCode, DoipPayload, SyncStatus, VehicleAnnouncementMessage,
+        VehicleIdentificationRequest, VehicleIdentificationRequestEid,
+        VehicleIdentificationRequestVin,
+    },
+    timing::A_VEHICLE_DISCOVERY_TIMER,
+};
+
+/// Selects which vehicle identification request is sent by a [`VehicleDiscovery`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiscoveryFilter {
+    /// Sends a `VehicleIdentificationRequest`, every entity answers
+    All,
+
+    /// Sends a `VehicleIdentificationRequestEid`, only the matching entity answers
+    Eid([u8; DOIP_COMMON_EID_LEN]),
+
+    /// Sends a `VehicleIdentificationRequestVin`, only entities of the matching
+    /// vehicle answer
+    Vin([u8; DOIP_COMMON_VIN_LEN]),
+}
+
+/// A `DoIP` entity which answered during a [`VehicleDiscovery`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiscoveredEntity {
+    /// Address the announcement was received from
+    pub address: SocketAddr,
+
+    /// Logical address of the entity
+    pub logical_address: [u8; DOIP_DIAG_COMMON_SOURCE_LEN],
+
+    /// Entity Identification
+    pub eid: [u8; DOIP_COMMON_EID_LEN],
+
+    /// Further actions required before diagnostic communication
+    pub further_action: ActionCode,
+
+    /// Status of VIN/GID Synchronisation, only sent by some entities
+    pub sync_status: Option<SyncStatus>,
+}
+
+impl DiscoveredEntity {
+    /// Returns true when the entity requires a routing activation for central
+    /// security before diagnostic communication.
+    #[must_use]
+    pub fn routing_activation_required(&self) -> bool {
+        self.further_action == ActionCode::RoutingActivationRequired
+    }
+}
+
+/// A vehicle grouping every entity which announced the same GID, or the same VIN
+/// when no valid GID was announced.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiscoveredVehicle {
+    /// Vehicle Identification Number
+    pub vin: [u8; DOIP_COMMON_VIN_LEN],
+
+    /// Group Identification
+    pub gid: [u8; DOIP_VEHICLE_ANNOUNCEMENT_GID_LEN],
+
+    /// Entities of the vehicle, deduplicated by EID
+    pub entities: Vec<DiscoveredEntity>,
+}
+
+impl DiscoveredVehicle {
+    /// Returns false when any entity reported that the VIN/GID are not yet
+    /// synchronised, the VIN and GID may then be incomplete.
+    #[must_use]
+    pub fn is_synchronised(&self) -> bool {
+        self.entities
+            .iter()
+            .all(|entity| entity.sync_status != Some(SyncStatus::VinGidNotSynchronised))
+    }
+
+    /// Returns true when any entity of the vehicle requires a routing
+    /// activation for central security.
+    #[must_use]
+    pub fn routing_activation_required(&self) -> bool {
+        self.entities
+            .iter()
+            .any(DiscoveredEntity::routing_activation_required)
+    }
+
+    fn matches(&self, announcement: &VehicleAnnouncementMessage) -> bool {
+        if is_valid_id(&announcement.gid) && is_valid_id(&self.gid) {
+            return self.gid == announcement.gid;
+        }
+
+        self.vin == announcement.vin
+    }
+}
+
+/// Transport agnostic vehicle discovery.
+///
+/// The discovery sends one vehicle identification request and collects every
+/// `VehicleAnnouncementMessage` received until `A_Vehicle_Discovery_Timer`
+/// expires. Announcements are deduplicated by EID and grouped into vehicles.
+///
+/// ```rust
+/// use std::time::Instant;
+/// use doip_definitions::discovery::{DiscoveryFilter, VehicleDiscovery};
+/// use doip_definitions::payload::DoipPayload;
+///
+/// let mut discovery = VehicleDiscovery::new(DiscoveryFilter::All);
+/// discovery.start(Instant::now());
+///
+/// let request = discovery.poll_transmit().unwrap();
+/// assert!(matches!(request.payload, DoipPayload::VehicleIdentificationRequest(_)));
+/// ```
+#[derive(Debug)]
+pub struct VehicleDiscovery {
+    filter: DiscoveryFilter,
+    protocol_version: ProtocolVersion,
+    timeout: Duration,
+    deadline: Option<Instant>,
+    finished: bool,
+    vehicles: Vec<DiscoveredVehicle>,
+    transmit: VecDeque<DoipMessage>,
+}
+
+impl VehicleDiscovery {
+    /// Creates a new `VehicleDiscovery` using `A_Vehicle_Discovery_Timer` and
+    /// the default protocol version `0xFF`.
+    #[must_use]
+    pub fn new(filter: DiscoveryFilter) -> Self {
+        Self {
+            filter,
+            protocol_version: ProtocolVersion::DefaultValue,
+            timeout: A_VEHICLE_DISCOVERY_TIMER,
+            deadline: None,
+            finished: false,
+            vehicles: Vec::new(),
+            transmit: VecDeque::new(),
+        }
+    }
+
+    /// Sets the time announcements are collected after the request is sent.
+    #[must_use]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets the protocol version used in the header of the request.
+    #[must_use]
+    pub fn protocol_version(mut self, protocol_version: ProtocolVersion) -> Self {
+        self.protocol_version = protocol_version;
+        self
+    }
+
+    /// Queues the vehicle identification request and starts the discovery timer,
+    /// discarding the results of a previous run.
+    pub fn start(&mut self, now: Instant) {
+        let payload = match self.filter {
+            DiscoveryFilter::All => {
+                DoipPayload::VehicleIdentificationRequest(VehicleIdentificationRequest {})
+            }
+            DiscoveryFilter::Eid(eid) => {
+                DoipPayload::VehicleIdentificationRequestEid(VehicleIdentificationRequestEid {
+                    eid,
+                })
+            }
+            DiscoveryFilter::Vin(vin) => {
+                DoipPayload::VehicleIdentificationRequestVin(VehicleIdentificationRequestVin {
+                    vin,
+                })
+            }
+        };
+
+        self.transmit.clear();
+        self.transmit.push_back(
+            DoipMessageBuilder::new()
+                .protocol_version(self.protocol_version)
+                .payload(payload)
+                .build(),
+        );
+
+        self.vehicles.clear();
+        self.finished = false;
+        self.deadline = Some(now + self.timeout);
+    }
+
+    /// Processes a message received from `address`.
+    ///
+    /// Only `VehicleAnnouncementMessage`s matching the filter are collected while
+    /// the discovery timer is running.
+    pub fn handle_message(&mut self, now: Instant, address: SocketAddr, message: &DoipMessage) {
+        if self.deadline.is_none_or(|deadline| deadline <= now) {
+            return;
+        }
+
+        if let DoipPayload::VehicleAnnouncementMessage(announcement) = message.payload {
+            self.handle_announcement(address, announcement);
+        }
+    }
+
+    /// Processes the discovery timer, finishing the discovery once it expired.
+    pub fn handle_timeout(&mut self, now: Instant) {
+        if self.deadline.is_some_and(|deadline| deadline <= now) {
+            self.deadline = None;
+            self.finished = true;
+        }
+    }
+
+    /// Returns the instant at which [`VehicleDiscovery::handle_timeout`] must be
+    /// called.
+    #[must_use]
+    pub fn poll_timeout(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Returns the next message to be sent.
+    pub fn poll_transmit(&mut self) -> Option<DoipMessage> {
+        self.transmit.pop_front()
+    }
+
+    /// Returns true once the discovery timer has expired.
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Returns the vehicles discovered so far.
+    #[must_use]
+    pub fn vehicles(&self) -> &[DiscoveredVehicle] {
+        &self.vehicles
+    }
+
+    /// Consumes the discovery, returning the discovered vehicles.
+    #[must_use]
+    pub fn into_vehicles(self) -> Vec<DiscoveredVehicle> {
+        self.vehicles
+    }
+
+    fn handle_announcement(
+        &mut self,
+        address: SocketAddr,
+        announcement: VehicleAnnouncementMessage,
+    ) {
+        let accepted = match self.filter {
+            DiscoveryFilter::All => true,
+            DiscoveryFilter::Eid(eid) => announcement.eid == eid,
+            DiscoveryFilter::Vin(vin) => announcement.vin == vin,
+        };
+
+        if !accepted {
+            return;
+        }
+
+        let entity = DiscoveredEntity {
+            address,
+            logical_address: announcement.logical_address,
+            eid: announcement.eid,
+            further_action: announcement.further_action,
+            sync_status: announcement.vin_gid_sync,
+        };
+
+        for vehicle in &mut self.vehicles {
+            vehicle.entities.retain(|known| known.eid != entity.eid);
+        }
+        self.vehicles.retain(|vehicle| !vehicle.entities.is_empty());
+
+        if let Some(vehicle) = self
+            .vehicles
+            .iter_mut()
+            .find(|vehicle| vehicle.matches(&announcement))
+        {
+            // A synchronised entity may report the VIN or GID the others lack.
+            if !is_valid_id(&vehicle.vin) {
+                vehicle.vin = announcement.vin;
+            }
+            if !is_valid_id(&vehicle.gid) {
+                vehicle.gid = announcement.gid;
+            }
+
+            vehicle.entities.push(entity);
+            return;
+        }
+
+        self.vehicles.push(DiscoveredVehicle {
+            vin: announcement.vin,
+            gid: announcement.gid,
+            entities: vec![entity],
+        });
+    }
+}
+
+/// VINs and GIDs set to all `0x00` or all `0xFF` are placeholders of entities
+/// which have not been configured or synchronised.
+fn is_valid_id(id: &[u8]) -> bool {
+    !id.iter().all(|byte| *byte == 0x00) && !id.iter().all(|byte| *byte == 0xFF)
+}
+
+#[cfg(test)]
+mod test {
+    use std::net::SocketAddr;
+    use std::time::{Duration, Instant};
+
+    use crate::{
+        builder::DoipMessageBuilder,
+        discovery::{DiscoveryFilter, VehicleDiscovery},
+        message::DoipMessage,
+        payload::{ActionCode, DoipPayload, SyncStatus, VehicleAnnouncementMessage},
+    };
+
+    fn announcement(eid: u8, gid: u8, further_action: ActionCode) -> DoipMessage {
+        DoipMessageBuilder::new()
+            .payload(DoipPayload::VehicleAnnouncementMessage(
+                VehicleAnnouncementMessage {
+                    vin: *b"WVWZZZ1JZXW000001",
+                    logical_address: [0x10, eid],
+                    eid: [0, 0, 0, 0, 0, eid],
+                    gid: [0, 0, 0, 0, 0, gid],
+                    further_action,
+                    vin_gid_sync: Some(SyncStatus::VinGidSynchronized),
+                },
+            ))
+            .build()
+    }
+
+    #[test]
+    fn test_grouping_and_deduplication() {
+        let now = Instant::now();
+        let address: SocketAddr = "127.0.0.1:13400".parse().unwrap();
+        let mut discovery = VehicleDiscovery::new(DiscoveryFilter::All);
+
+        discovery.start(now);
+        discovery.handle_message(
+            now,
+            address,
+            &announcement(1, 1, ActionCode::NoFurtherActionRequired),
+        );
+        discovery.handle_message(
+            now,
+            address,
+            &announcement(2, 1, ActionCode::RoutingActivationRequired),
+        );
+        discovery.handle_message(
+            now,
+            address,
+            &announcement(1, 1, ActionCode::NoFurtherActionRequired),
+        );
+        discovery.handle_message(
+            now,
+            address,
+            &announcement(3, 2, ActionCode::NoFurtherActionRequired),
+        );
+
+        discovery.handle_timeout(now + Duration::from_secs(5));
+        discovery.handle_message(
+            now + Duration::from_secs(5),
+            address,
+            &announcement(4, 3, ActionCode::NoFurtherActionRequired),
+        );
+
+        assert!(discovery.is_finished());
+
+        let vehicles = discovery.vehicles();
+        assert_eq!(vehicles.len(), 2);
+        assert_eq!(vehicles[0].entities.len(), 2);
+        assert!(vehicles[0].routing_activation_required());
+        assert!(vehicles[0].is_synchronised());
+        assert!(!vehicles[1].routing_activation_required());
+    }
+}
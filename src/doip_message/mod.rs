@@ -4,6 +4,16 @@ use crate::{
     payload::DoipPayload,
 };
 
+#[cfg(feature = "std")]
+use crate::{
+    definitions::DOIP_HEADER_LEN,
+    header::PayloadType,
+    payload::{
+        AliveCheckRequest, EntityStatusRequest, PowerInformationRequest,
+        VehicleIdentificationRequest,
+    },
+};
+
 /// The decoded struct of a `DoIP` packet.
 ///
 /// Each `DoIP` packet contains a header which describes the message, this is outlined
@@ -68,14 +78,107 @@ impl TryFrom<DoipMessage> for Vec<u8> {
     type Error = Error;
 
     fn try_from(value: DoipMessage) -> Result<Self> {
-        let mut buffer = Vec::<u8>::new();
-
-        let header: [u8; 8] = value.header.into();
-        buffer[0..8].copy_from_slice(&header);
-
+        let header: [u8; DOIP_HEADER_LEN] = value.header.into();
         let payload: Vec<u8> = value.payload.into();
-        buffer[8..].copy_from_slice(&payload);
+
+        let mut buffer = Vec::<u8>::with_capacity(DOIP_HEADER_LEN + payload.len());
+        buffer.extend_from_slice(&header);
+        buffer.extend_from_slice(&payload);
 
         Ok(buffer)
     }
 }
+
+#[cfg(feature = "std")]
+impl TryFrom<&[u8]> for DoipMessage {
+    type Error = Error;
+
+    fn try_from(value: &[u8]) -> Result<Self> {
+        let header: [u8; DOIP_HEADER_LEN] = value
+            .get(..DOIP_HEADER_LEN)
+            .ok_or(Error::OutOfBounds {
+                source: "DoipMessage",
+                variable: "Header",
+            })?
+            .try_into()?;
+        let header = DoipHeader::try_from(header)?;
+
+        let payload = value
+            .get(DOIP_HEADER_LEN..DOIP_HEADER_LEN + header.payload_length as usize)
+            .ok_or(Error::OutOfBounds {
+                source: "DoipMessage",
+                variable: "Payload",
+            })?;
+
+        let payload = match header.payload_type {
+            PayloadType::GenericNack => DoipPayload::GenericNack(payload.try_into()?),
+            PayloadType::VehicleIdentificationRequest => {
+                DoipPayload::VehicleIdentificationRequest(VehicleIdentificationRequest {})
+            }
+            PayloadType::VehicleIdentificationRequestEid => {
+                DoipPayload::VehicleIdentificationRequestEid(payload.try_into()?)
+            }
+            PayloadType::VehicleIdentificationRequestVin => {
+                DoipPayload::VehicleIdentificationRequestVin(payload.try_into()?)
+            }
+            PayloadType::VehicleAnnouncementMessage => {
+                DoipPayload::VehicleAnnouncementMessage(payload.try_into()?)
+            }
+            PayloadType::RoutingActivationRequest => {
+                DoipPayload::RoutingActivationRequest(payload.try_into()?)
+            }
+            PayloadType::RoutingActivationResponse => {
+                DoipPayload::RoutingActivationResponse(payload.try_into()?)
+            }
+            PayloadType::AliveCheckRequest => DoipPayload::AliveCheckRequest(AliveCheckRequest {}),
+            PayloadType::AliveCheckResponse => DoipPayload::AliveCheckResponse(payload.try_into()?),
+            PayloadType::EntityStatusRequest => {
+                DoipPayload::EntityStatusRequest(EntityStatusRequest {})
+            }
+            PayloadType::EntityStatusResponse => {
+                DoipPayload::EntityStatusResponse(payload.try_into()?)
+            }
+            PayloadType::PowerInformationRequest => {
+                DoipPayload::PowerInformationRequest(PowerInformationRequest {})
+            }
+            PayloadType::PowerInformationResponse => {
+                DoipPayload::PowerInformationResponse(payload.try_into()?)
+            }
+            PayloadType::DiagnosticMessage => DoipPayload::DiagnosticMessage(payload.try_into()?),
+            PayloadType::DiagnosticMessageAck => {
+                DoipPayload::DiagnosticMessageAck(payload.try_into()?)
+            }
+            PayloadType::DiagnosticMessageNack => {
+                DoipPayload::DiagnosticMessageNack(payload.try_into()?)
+            }
+        };
+
+        Ok(DoipMessage { header, payload })
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "builder")]
+mod test {
+    use crate::{
+        builder::DoipMessageBuilder,
+        message::DoipMessage,
+        payload::{DiagnosticMessage, DoipPayload},
+    };
+
+    #[test]
+    fn test_bytes_roundtrip() {
+        let message = DoipMessageBuilder::new()
+            .payload(DoipPayload::DiagnosticMessage(DiagnosticMessage {
+                source_address: [0x0E, 0x00],
+                target_address: [0x10, 0x00],
+                message: vec![0x22, 0xF1, 0x90],
+            }))
+            .build();
+
+        let bytes: Vec<u8> = message.clone().try_into().unwrap();
+        assert_eq!(bytes.len(), 15);
+        assert_eq!(DoipMessage::try_from(bytes.as_slice()).unwrap(), message);
+        assert!(DoipMessage::try_from(&bytes[..14]).is_err());
+    }
+}
@@ -85,8 +85,8 @@ impl<const N: usize> From<DiagnosticMessage<N>> for [u8; N] {
 /// A UDS Message to a specific target address.
 ///
 /// `DiagnosticMessage` is the most utilised payload type due to the amount of actions
-/// a diagnostic tester can do using the UDS protocol. This crate will not handle the UDS
-/// protocol however, one will be developed to enhance developer tooling.
+/// a diagnostic tester can do using the UDS protocol. The `message` can be decoded with
+/// [`UdsRequest`](crate::uds::UdsRequest) and [`UdsResponse`](crate::uds::UdsResponse).
 #[cfg(feature = "std")]
 #[derive(Clone, Debug, PartialEq)]
 pub struct DiagnosticMessage {
@@ -39,8 +39,9 @@ use crate::definitions::{
     DOIP_COMMON_EID_LEN, DOIP_COMMON_VIN_LEN, DOIP_DIAG_COMMON_SOURCE_LEN,
     DOIP_DIAG_COMMON_TARGET_LEN, DOIP_ENTITY_STATUS_RESPONSE_MCTS_LEN,
     DOIP_ENTITY_STATUS_RESPONSE_MDS_LEN, DOIP_ENTITY_STATUS_RESPONSE_NCTS_LEN,
-    DOIP_ROUTING_ACTIVATION_REQ_ISO_LEN, DOIP_ROUTING_ACTIVATION_REQ_SRC_LEN,
-    DOIP_ROUTING_ACTIVATION_RES_ENTITY_LEN, DOIP_ROUTING_ACTIVATION_RES_ISO_LEN,
+    DOIP_ROUTING_ACTIVATION_REQ_ISO_LEN, DOIP_ROUTING_ACTIVATION_REQ_OEM_LEN,
+    DOIP_ROUTING_ACTIVATION_REQ_SRC_LEN, DOIP_ROUTING_ACTIVATION_RES_ENTITY_LEN,
+    DOIP_ROUTING_ACTIVATION_RES_ISO_LEN, DOIP_ROUTING_ACTIVATION_RES_OEM_LEN,
     DOIP_ROUTING_ACTIVATION_RES_TESTER_LEN, DOIP_VEHICLE_ANNOUNCEMENT_GID_LEN,
 };
 
@@ -291,17 +292,21 @@ impl From<DoipPayload> for Vec<u8> {
                 }
             }
             DoipPayload::RoutingActivationRequest(payload) => {
+                let size = payload.size_of();
                 let payload: [u8; DOIP_ROUTING_ACTIVATION_REQ_SRC_LEN
                     + 1
-                    + DOIP_ROUTING_ACTIVATION_REQ_ISO_LEN] = payload.into();
-                payload.to_vec()
+                    + DOIP_ROUTING_ACTIVATION_REQ_ISO_LEN
+                    + DOIP_ROUTING_ACTIVATION_REQ_OEM_LEN] = payload.into();
+                payload[..size].to_vec()
             }
             DoipPayload::RoutingActivationResponse(payload) => {
+                let size = payload.size_of();
                 let payload: [u8; DOIP_ROUTING_ACTIVATION_RES_TESTER_LEN
                     + DOIP_ROUTING_ACTIVATION_RES_ENTITY_LEN
                     + 1
-                    + DOIP_ROUTING_ACTIVATION_RES_ISO_LEN] = payload.into();
-                payload.to_vec()
+                    + DOIP_ROUTING_ACTIVATION_RES_ISO_LEN
+                    + DOIP_ROUTING_ACTIVATION_RES_OEM_LEN] = payload.into();
+                payload[..size].to_vec()
             }
             DoipPayload::AliveCheckRequest(payload) => {
                 let payload: [u8; 0] = payload.into();
@@ -1,5 +1,8 @@
 use crate::{
-    definitions::{DOIP_ROUTING_ACTIVATION_REQ_ISO_LEN, DOIP_ROUTING_ACTIVATION_REQ_SRC_LEN},
+    definitions::{
+        DOIP_ROUTING_ACTIVATION_REQ_ISO_LEN, DOIP_ROUTING_ACTIVATION_REQ_OEM_LEN,
+        DOIP_ROUTING_ACTIVATION_REQ_SRC_LEN,
+    },
     doip_payload::SizedDoipPayload,
     error::{Error, Result},
     payload::ActivationType,
@@ -20,6 +23,9 @@ pub struct RoutingActivationRequest {
 
     /// ISO reserved buffer, currently left empty
     pub buffer: [u8; DOIP_ROUTING_ACTIVATION_REQ_ISO_LEN],
+
+    /// Optional OEM specific data, e.g. for authentication
+    pub oem_specific: Option<[u8; DOIP_ROUTING_ACTIVATION_REQ_OEM_LEN]>,
 }
 
 impl From<RoutingActivationRequest>
@@ -44,6 +50,37 @@ impl From<RoutingActivationRequest>
     }
 }
 
+impl From<RoutingActivationRequest>
+    for [u8; DOIP_ROUTING_ACTIVATION_REQ_SRC_LEN
+        + 1
+        + DOIP_ROUTING_ACTIVATION_REQ_ISO_LEN
+        + DOIP_ROUTING_ACTIVATION_REQ_OEM_LEN]
+{
+    fn from(value: RoutingActivationRequest) -> Self {
+        let mut buffer = [0u8; DOIP_ROUTING_ACTIVATION_REQ_SRC_LEN
+            + 1
+            + DOIP_ROUTING_ACTIVATION_REQ_ISO_LEN
+            + DOIP_ROUTING_ACTIVATION_REQ_OEM_LEN];
+
+        let mut offset = 0;
+
+        buffer[offset..offset + DOIP_ROUTING_ACTIVATION_REQ_SRC_LEN]
+            .copy_from_slice(&value.source_address);
+        offset += DOIP_ROUTING_ACTIVATION_REQ_SRC_LEN;
+
+        buffer[offset] = value.activation_type.into();
+        offset += 1;
+
+        buffer[offset..offset + DOIP_ROUTING_ACTIVATION_REQ_ISO_LEN].copy_from_slice(&value.buffer);
+        offset += DOIP_ROUTING_ACTIVATION_REQ_ISO_LEN;
+
+        buffer[offset..offset + DOIP_ROUTING_ACTIVATION_REQ_OEM_LEN]
+            .copy_from_slice(&value.oem_specific.unwrap_or_default());
+
+        buffer
+    }
+}
+
 impl TryFrom<&[u8]> for RoutingActivationRequest {
     type Error = Error;
 
@@ -78,10 +115,22 @@ impl TryFrom<&[u8]> for RoutingActivationRequest {
             })?
             .try_into()?;
 
+        offset += DOIP_ROUTING_ACTIVATION_REQ_ISO_LEN;
+
+        // The OEM specific bytes are either absent or complete
+        let oem_specific = match value.get(offset..).unwrap_or_default() {
+            [] => None,
+            oem_specific => Some(oem_specific.try_into().map_err(|_| Error::OutOfBounds {
+                source: "RoutingActivationRequest",
+                variable: "OEM Specific",
+            })?),
+        };
+
         Ok(RoutingActivationRequest {
             source_address,
             activation_type,
             buffer,
+            oem_specific,
         })
     }
 }
@@ -92,5 +141,31 @@ impl SizedDoipPayload for RoutingActivationRequest {
         DOIP_ROUTING_ACTIVATION_REQ_SRC_LEN
             + std::mem::size_of::<ActivationType>()
             + DOIP_ROUTING_ACTIVATION_REQ_ISO_LEN
+            + self
+                .oem_specific
+                .map_or(0, |_| DOIP_ROUTING_ACTIVATION_REQ_OEM_LEN)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{error::Error, payload::RoutingActivationRequest};
+
+    #[test]
+    fn test_oem_specific() {
+        let bytes = [0x0E, 0x00, 0x00, 0, 0, 0, 0, 0xCA, 0xFE, 0x00, 0x01];
+
+        let request = RoutingActivationRequest::try_from(&bytes[..7]).unwrap();
+        assert_eq!(request.oem_specific, None);
+
+        let request = RoutingActivationRequest::try_from(&bytes[..]).unwrap();
+        assert_eq!(request.oem_specific, Some([0xCA, 0xFE, 0x00, 0x01]));
+
+        for len in 8..11 {
+            assert!(matches!(
+                RoutingActivationRequest::try_from(&bytes[..len]),
+                Err(Error::OutOfBounds { .. })
+            ));
+        }
     }
 }
@@ -1,7 +1,7 @@
 use crate::{
     definitions::{
         DOIP_ROUTING_ACTIVATION_RES_ENTITY_LEN, DOIP_ROUTING_ACTIVATION_RES_ISO_LEN,
-        DOIP_ROUTING_ACTIVATION_RES_TESTER_LEN,
+        DOIP_ROUTING_ACTIVATION_RES_OEM_LEN, DOIP_ROUTING_ACTIVATION_RES_TESTER_LEN,
     },
     doip_payload::SizedDoipPayload,
     error::{Error, Result},
@@ -25,6 +25,9 @@ pub struct RoutingActivationResponse {
 
     /// ISO reserved buffer
     pub buffer: [u8; DOIP_ROUTING_ACTIVATION_RES_ISO_LEN],
+
+    /// Optional OEM specific data
+    pub oem_specific: Option<[u8; DOIP_ROUTING_ACTIVATION_RES_OEM_LEN]>,
 }
 
 impl From<RoutingActivationResponse>
@@ -58,6 +61,43 @@ impl From<RoutingActivationResponse>
     }
 }
 
+impl From<RoutingActivationResponse>
+    for [u8; DOIP_ROUTING_ACTIVATION_RES_TESTER_LEN
+        + DOIP_ROUTING_ACTIVATION_RES_ENTITY_LEN
+        + 1
+        + DOIP_ROUTING_ACTIVATION_RES_ISO_LEN
+        + DOIP_ROUTING_ACTIVATION_RES_OEM_LEN]
+{
+    fn from(value: RoutingActivationResponse) -> Self {
+        let mut buffer = [0u8; DOIP_ROUTING_ACTIVATION_RES_TESTER_LEN
+            + DOIP_ROUTING_ACTIVATION_RES_ENTITY_LEN
+            + 1
+            + DOIP_ROUTING_ACTIVATION_RES_ISO_LEN
+            + DOIP_ROUTING_ACTIVATION_RES_OEM_LEN];
+
+        let mut offset = 0;
+
+        buffer[offset..offset + DOIP_ROUTING_ACTIVATION_RES_TESTER_LEN]
+            .copy_from_slice(&value.logical_address);
+        offset += DOIP_ROUTING_ACTIVATION_RES_TESTER_LEN;
+
+        buffer[offset..offset + DOIP_ROUTING_ACTIVATION_RES_ENTITY_LEN]
+            .copy_from_slice(&value.source_address);
+        offset += DOIP_ROUTING_ACTIVATION_RES_ENTITY_LEN;
+
+        buffer[offset] = value.activation_code.into();
+        offset += 1;
+
+        buffer[offset..offset + DOIP_ROUTING_ACTIVATION_RES_ISO_LEN].copy_from_slice(&value.buffer);
+        offset += DOIP_ROUTING_ACTIVATION_RES_ISO_LEN;
+
+        buffer[offset..offset + DOIP_ROUTING_ACTIVATION_RES_OEM_LEN]
+            .copy_from_slice(&value.oem_specific.unwrap_or_default());
+
+        buffer
+    }
+}
+
 impl TryFrom<&[u8]> for RoutingActivationResponse {
     type Error = Error;
 
@@ -102,11 +142,23 @@ impl TryFrom<&[u8]> for RoutingActivationResponse {
             })?
             .try_into()?;
 
+        offset += DOIP_ROUTING_ACTIVATION_RES_ISO_LEN;
+
+        // The OEM specific bytes are either absent or complete
+        let oem_specific = match value.get(offset..).unwrap_or_default() {
+            [] => None,
+            oem_specific => Some(oem_specific.try_into().map_err(|_| Error::OutOfBounds {
+                source: "RoutingActivationResponse",
+                variable: "OEM Specific",
+            })?),
+        };
+
         Ok(RoutingActivationResponse {
             logical_address,
             source_address,
             activation_code,
             buffer,
+            oem_specific,
         })
     }
 }
@@ -118,5 +170,33 @@ impl SizedDoipPayload for RoutingActivationResponse {
             + DOIP_ROUTING_ACTIVATION_RES_ENTITY_LEN
             + std::mem::size_of::<ActivationCode>()
             + DOIP_ROUTING_ACTIVATION_RES_ISO_LEN
+            + self
+                .oem_specific
+                .map_or(0, |_| DOIP_ROUTING_ACTIVATION_RES_OEM_LEN)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{error::Error, payload::RoutingActivationResponse};
+
+    #[test]
+    fn test_oem_specific() {
+        let bytes = [
+            0x0E, 0x00, 0x10, 0x00, 0x10, 0, 0, 0, 0, 0xCA, 0xFE, 0x00, 0x01,
+        ];
+
+        let response = RoutingActivationResponse::try_from(&bytes[..9]).unwrap();
+        assert_eq!(response.oem_specific, None);
+
+        let response = RoutingActivationResponse::try_from(&bytes[..]).unwrap();
+        assert_eq!(response.oem_specific, Some([0xCA, 0xFE, 0x00, 0x01]));
+
+        for len in 10..13 {
+            assert!(matches!(
+                RoutingActivationResponse::try_from(&bytes[..len]),
+                Err(Error::OutOfBounds { .. })
+            ));
+        }
     }
 }
@@ -0,0 +1,291 @@
+use std::collections::VecDeque;
+use std::net::SocketAddr;
+use std::time::Instant;
+
+use crate::{
+    builder::DoipMessageBuilder,
+    header::ProtocolVersion,
+    message::DoipMessage,
+    payload::{DoipPayload, VehicleAnnouncementMessage},
+    timing::DoipTimings,
+};
+
+/// Schedules the vehicle announcements of a `DoIP` entity and answers vehicle
+/// identification requests.
+///
+/// After [`VehicleAnnouncer::start`] the announcer sends `A_DoIP_Announce_Num`
+/// announcements, the first after a random `A_DoIP_Announce_Wait` and each
+/// following one `A_DoIP_Announce_Interval` later. Requests handled with
+/// [`VehicleAnnouncer::handle_message`] are answered after a random
+/// `A_DoIP_Announce_Wait` when they address the entity's own VIN or EID.
+///
+/// ```rust
+/// use std::time::Instant;
+/// use doip_definitions::entity::VehicleAnnouncer;
+/// use doip_definitions::payload::{ActionCode, VehicleAnnouncementMessage};
+/// use doip_definitions::timing::DoipTimings;
+///
+/// let mut announcer = VehicleAnnouncer::new(
+///     VehicleAnnouncementMessage {
+///         vin: *b"WVWZZZ1JZXW000001",
+///         logical_address: [0x10, 0x00],
+///         eid: [0x00, 0x1A, 0x2B, 0x3C, 0x4D, 0x5E],
+///         gid: [0x00, 0x1A, 0x2B, 0x3C, 0x4D, 0x5E],
+///         further_action: ActionCode::NoFurtherActionRequired,
+///         vin_gid_sync: None,
+///     },
+///     DoipTimings::default(),
+/// );
+///
+/// let now = Instant::now();
+/// announcer.start(now, 0);
+/// announcer.handle_timeout(now);
+///
+/// assert!(announcer.poll_transmit().is_some());
+/// ```
+#[derive(Debug)]
+pub struct VehicleAnnouncer {
+    announcement: VehicleAnnouncementMessage,
+    protocol_version: ProtocolVersion,
+    timings: DoipTimings,
+    next: Option<Instant>,
+    remaining: u8,
+    responses: Vec<(Instant, SocketAddr, DoipMessage)>,
+    transmit: VecDeque<(Option<SocketAddr>, DoipMessage)>,
+}
+
+impl VehicleAnnouncer {
+    /// Creates a new `VehicleAnnouncer` sending `announcement`.
+    #[must_use]
+    pub fn new(announcement: VehicleAnnouncementMessage, timings: DoipTimings) -> Self {
+        Self {
+            announcement,
+            protocol_version: ProtocolVersion::Iso13400_2012,
+            timings,
+            next: None,
+            remaining: 0,
+            responses: Vec::new(),
+            transmit: VecDeque::new(),
+        }
+    }
+
+    /// Sets the protocol version used in the header of each sent message.
+    #[must_use]
+    pub fn protocol_version(mut self, protocol_version: ProtocolVersion) -> Self {
+        self.protocol_version = protocol_version;
+        self
+    }
+
+    /// Returns the announcement sent by the entity.
+    #[must_use]
+    pub fn announcement(&self) -> &VehicleAnnouncementMessage {
+        &self.announcement
+    }
+
+    /// Replaces the announcement, e.g. once the VIN/GID have been synchronised.
+    pub fn set_announcement(&mut self, announcement: VehicleAnnouncementMessage) {
+        self.announcement = announcement;
+    }
+
+    /// Schedules the announcements after startup or a change of IP address.
+    ///
+    /// `random` is mapped onto the `A_DoIP_Announce_Wait` range, see
+    /// [`DoipTimings::announce_wait`].
+    pub fn start(&mut self, now: Instant, random: u32) {
+        self.remaining = self.timings.announce_num;
+        self.next = (self.remaining > 0).then(|| now + self.timings.announce_wait(random));
+    }
+
+    /// Cancels the outstanding announcements and identification responses,
+    /// e.g. when the IP address is lost.
+    pub fn stop(&mut self) {
+        self.remaining = 0;
+        self.next = None;
+        self.responses.clear();
+        self.transmit.clear();
+    }
+
+    /// Schedules the answer to a vehicle identification request received from
+    /// `origin`, due after a random `A_DoIP_Announce_Wait`.
+    ///
+    /// `random` is mapped onto the `A_DoIP_Announce_Wait` range, see
+    /// [`DoipTimings::announce_wait`]. Returns `false` for requests addressing a
+    /// different VIN or EID and for any other payload type.
+    pub fn handle_message(
+        &mut self,
+        now: Instant,
+        random: u32,
+        origin: SocketAddr,
+        message: &DoipMessage,
+    ) -> bool {
+        let matches = match &message.payload {
+            DoipPayload::VehicleIdentificationRequest(_) => true,
+            DoipPayload::VehicleIdentificationRequestEid(request) => {
+                request.eid == self.announcement.eid
+            }
+            DoipPayload::VehicleIdentificationRequestVin(request) => {
+                request.vin == self.announcement.vin
+            }
+            _ => false,
+        };
+
+        if matches {
+            let due = now + self.timings.announce_wait(random);
+            self.responses.push((due, origin, self.build()));
+        }
+
+        matches
+    }
+
+    /// Queues every identification response and the announcement which are due
+    /// at or before `now`.
+    pub fn handle_timeout(&mut self, now: Instant) {
+        self.responses.sort_by_key(|(due, ..)| *due);
+        let due = self.responses.partition_point(|(due, ..)| *due <= now);
+        for (_, origin, response) in self.responses.drain(..due) {
+            self.transmit.push_back((Some(origin), response));
+        }
+
+        // The following announcement is spaced from this call, so that a late
+        // call does not send the missed announcements at once.
+        if self.next.is_some_and(|next| next <= now) {
+            self.transmit.push_back((None, self.build()));
+            self.remaining = self.remaining.saturating_sub(1);
+            self.next = (self.remaining > 0).then(|| now + self.timings.announce_interval);
+        }
+    }
+
+    /// Returns the instant of the next announcement or identification response.
+    #[must_use]
+    pub fn poll_timeout(&self) -> Option<Instant> {
+        self.responses
+            .iter()
+            .map(|(due, ..)| *due)
+            .chain(self.next)
+            .min()
+    }
+
+    /// Returns the next message to be sent, paired with the address of the
+    /// tester for identification responses and `None` for announcements to be
+    /// broadcast.
+    pub fn poll_transmit(&mut self) -> Option<(Option<SocketAddr>, DoipMessage)> {
+        self.transmit.pop_front()
+    }
+
+    fn build(&self) -> DoipMessage {
+        DoipMessageBuilder::new()
+            .protocol_version(self.protocol_version)
+            .payload(DoipPayload::VehicleAnnouncementMessage(self.announcement))
+            .build()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::net::SocketAddr;
+    use std::time::{Duration, Instant};
+
+    use crate::{
+        builder::DoipMessageBuilder,
+        entity::VehicleAnnouncer,
+        payload::{
+            ActionCode, DoipPayload, VehicleAnnouncementMessage, VehicleIdentificationRequestEid,
+            VehicleIdentificationRequestVin,
+        },
+        timing::DoipTimings,
+    };
+
+    fn announcer() -> VehicleAnnouncer {
+        VehicleAnnouncer::new(
+            VehicleAnnouncementMessage {
+                vin: *b"WVWZZZ1JZXW000001",
+                logical_address: [0x10, 0x00],
+                eid: [0, 0, 0, 0, 0, 1],
+                gid: [0, 0, 0, 0, 0, 1],
+                further_action: ActionCode::NoFurtherActionRequired,
+                vin_gid_sync: None,
+            },
+            DoipTimings::default(),
+        )
+    }
+
+    #[test]
+    fn test_announcement_schedule() {
+        let now = Instant::now();
+        let mut announcer = announcer();
+
+        announcer.start(now, 100);
+        assert_eq!(
+            announcer.poll_timeout(),
+            Some(now + Duration::from_millis(100))
+        );
+
+        announcer.handle_timeout(now + Duration::from_millis(99));
+        assert!(announcer.poll_transmit().is_none());
+
+        // A late call sends one announcement and schedules the next one
+        // A_DoIP_Announce_Interval later.
+        announcer.handle_timeout(now + Duration::from_millis(600));
+        assert!(announcer.poll_transmit().is_some());
+        assert!(announcer.poll_transmit().is_none());
+        assert_eq!(
+            announcer.poll_timeout(),
+            Some(now + Duration::from_millis(1100))
+        );
+
+        announcer.handle_timeout(now + Duration::from_millis(1099));
+        assert!(announcer.poll_transmit().is_none());
+
+        announcer.handle_timeout(now + Duration::from_millis(1100));
+        assert!(announcer.poll_transmit().is_some());
+        assert!(announcer.poll_transmit().is_none());
+
+        announcer.handle_timeout(now + Duration::from_secs(10));
+        assert!(announcer.poll_transmit().is_some());
+        assert!(announcer.poll_transmit().is_none());
+        assert_eq!(announcer.poll_timeout(), None);
+    }
+
+    #[test]
+    fn test_identification_requests() {
+        let now = Instant::now();
+        let origin: SocketAddr = "192.168.0.2:50000".parse().unwrap();
+        let mut announcer = announcer();
+
+        let own = DoipMessageBuilder::new()
+            .payload(DoipPayload::VehicleIdentificationRequestVin(
+                VehicleIdentificationRequestVin {
+                    vin: *b"WVWZZZ1JZXW000001",
+                },
+            ))
+            .build();
+        let other = DoipMessageBuilder::new()
+            .payload(DoipPayload::VehicleIdentificationRequestEid(
+                VehicleIdentificationRequestEid {
+                    eid: [0, 0, 0, 0, 0, 2],
+                },
+            ))
+            .build();
+
+        assert!(!announcer.handle_message(now, 0, origin, &other));
+        assert!(announcer.handle_message(now, 100, origin, &own));
+        assert!(announcer.poll_transmit().is_none());
+        assert_eq!(
+            announcer.poll_timeout(),
+            Some(now + Duration::from_millis(100))
+        );
+
+        announcer.handle_timeout(now + Duration::from_millis(99));
+        assert!(announcer.poll_transmit().is_none());
+
+        announcer.handle_timeout(now + Duration::from_millis(100));
+        assert!(matches!(
+            announcer.poll_transmit(),
+            Some((Some(address), response))
+                if address == origin
+                    && matches!(response.payload, DoipPayload::VehicleAnnouncementMessage(_))
+        ));
+        assert!(announcer.poll_transmit().is_none());
+        assert_eq!(announcer.poll_timeout(), None);
+    }
+}
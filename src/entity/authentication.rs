@@ -0,0 +1,74 @@
+use core::fmt::Debug;
+
+use crate::{entity::SocketId, payload::RoutingActivationRequest};
+
+/// The outcome of an [`AuthenticationHandler`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Authentication {
+    /// The tester is authenticated, routing activation continues
+    Authenticated,
+
+    /// The tester is not authenticated, the entity answers with
+    /// `DeniedMissingAuthentication` and keeps the socket open
+    Missing,
+}
+
+/// The outcome of a [`ConfirmationHandler`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Confirmation {
+    /// Routing is confirmed and activated
+    Confirmed,
+
+    /// The confirmation is outstanding, the entity answers with
+    /// `ActivatedConfirmationRequired` and the tester repeats its request
+    Pending,
+
+    /// The confirmation was rejected, the entity answers with
+    /// `DeniedRejectedConfirmation` and closes the socket
+    Rejected,
+}
+
+/// Decides whether a tester requesting routing activation is authenticated.
+///
+/// Called by the [`DoipEntity`](crate::entity::DoipEntity) once the socket
+/// handling of a `RoutingActivationRequest` succeeded. The OEM specific bytes of
+/// the request are available through `request.oem_specific`.
+pub trait AuthenticationHandler: Debug + Send {
+    /// Authenticates the tester behind `request` received on `socket`.
+    fn authenticate(
+        &mut self,
+        socket: SocketId,
+        request: &RoutingActivationRequest,
+    ) -> Authentication;
+}
+
+/// Decides whether the routing activation of an authenticated tester is
+/// confirmed, e.g. by the driver or the vehicle state.
+///
+/// The tester repeats its `RoutingActivationRequest` while the confirmation is
+/// pending, so the handler is asked again for every repetition.
+pub trait ConfirmationHandler: Debug + Send {
+    /// Confirms the routing activation of `request` received on `socket`.
+    fn confirm(&mut self, socket: SocketId, request: &RoutingActivationRequest) -> Confirmation;
+}
+
+/// Handler authenticating and confirming every routing activation, used by
+/// default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AcceptAll;
+
+impl AuthenticationHandler for AcceptAll {
+    fn authenticate(
+        &mut self,
+        _socket: SocketId,
+        _request: &RoutingActivationRequest,
+    ) -> Authentication {
+        Authentication::Authenticated
+    }
+}
+
+impl ConfirmationHandler for AcceptAll {
+    fn confirm(&mut self, _socket: SocketId, _request: &RoutingActivationRequest) -> Confirmation {
+        Confirmation::Confirmed
+    }
+}
@@ -0,0 +1,955 @@
+// region:      --- Modules
+
+mod announcer;
+mod authentication;
+
+// -- Flatten
+
+pub use announcer::*;
+pub use authentication::*;
+
+// endregion:      --- Modules
+
+use std::collections::{BTreeMap, VecDeque};
+use std::time::Instant;
+
+use crate::{
+    builder::DoipMessageBuilder,
+    definitions::{DOIP_DIAG_COMMON_SOURCE_LEN, DOIP_ROUTING_ACTIVATION_RES_ISO_LEN},
+    header::ProtocolVersion,
+    message::DoipMessage,
+    payload::{
+        ActivationCode, ActivationType, AliveCheckRequest, DiagnosticMessage,
+        DiagnosticMessageNack, DiagnosticNackCode, DoipPayload, EntityStatusResponse, NodeType,
+        PowerInformationResponse, PowerMode, RoutingActivationRequest, RoutingActivationResponse,
+    },
+    timing::DoipTimings,
+};
+
+/// Configuration of a [`DoipEntity`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntityConfig {
+    /// Logical address of the entity
+    pub logical_address: [u8; DOIP_DIAG_COMMON_SOURCE_LEN],
+
+    /// Reported in the `EntityStatusResponse`
+    pub node_type: NodeType,
+
+    /// Maximum number of concurrently registered TCP sockets
+    pub max_concurrent_sockets: u8,
+
+    /// Maximum size of a single `DoIP` message accepted by the entity
+    pub max_data_size: u32,
+
+    /// Reported in the `PowerInformationResponse`
+    pub power_mode: PowerMode,
+
+    /// Activation types accepted in a `RoutingActivationRequest`
+    pub activation_types: Vec<ActivationType>,
+
+    /// Activation types denied with `DeniedRequestEncryptedTLSConnection` on
+    /// sockets which were not accepted with [`DoipEntity::accept_encrypted`]
+    pub encrypted_activation_types: Vec<ActivationType>,
+
+    /// Protocol version used in the header of each sent message
+    pub protocol_version: ProtocolVersion,
+
+    /// Timing parameters applied to socket inactivity and alive checks
+    pub timings: DoipTimings,
+}
+
+impl Default for EntityConfig {
+    /// Constructs an `EntityConfig` with:
+    /// - Logical address `0x1000`
+    /// - Node type set to `DoipNode`
+    /// - A single concurrent socket and a max data size of 4096 bytes
+    /// - Power mode set to `Ready`
+    /// - `Default` and `WwhObd` activation types, none requiring TLS
+    /// - Protocol version set to `Iso13400_2012`
+    /// - ISO 13400-2 default timings
+    fn default() -> Self {
+        Self {
+            logical_address: [0x10, 0x00],
+            node_type: NodeType::DoipNode,
+            max_concurrent_sockets: 1,
+            max_data_size: 4096,
+            power_mode: PowerMode::Ready,
+            activation_types: vec![ActivationType::Default, ActivationType::WwhObd],
+            encrypted_activation_types: Vec::new(),
+            protocol_version: ProtocolVersion::Iso13400_2012,
+            timings: DoipTimings::default(),
+        }
+    }
+}
+
+/// Handle of a TCP socket accepted by a [`DoipEntity`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SocketId(pub u32);
+
+/// Connection states of a TCP socket as defined by ISO 13400-2.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SocketState {
+    /// The socket is accepted, no routing activation was received yet
+    Initialized,
+
+    /// A source address is registered on the socket, authentication is outstanding
+    RegisteredPendingAuthentication,
+
+    /// A source address is registered on the socket, confirmation is outstanding
+    RegisteredPendingConfirmation,
+
+    /// A source address is registered on the socket and routing is active
+    RegisteredRoutingActive,
+}
+
+/// Notifications raised by a [`DoipEntity`] for the application.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EntityEvent {
+    /// Routing was activated on a socket
+    RoutingActivated {
+        /// The socket the tester is connected on
+        socket: SocketId,
+
+        /// Logical address of the tester
+        source_address: [u8; DOIP_DIAG_COMMON_SOURCE_LEN],
+
+        /// The activation type requested by the tester
+        activation_type: ActivationType,
+    },
+
+    /// A `DiagnosticMessage` was received on a routing active socket
+    DiagnosticMessage {
+        /// The socket the message was received on
+        socket: SocketId,
+
+        /// The received message
+        message: DiagnosticMessage,
+    },
+
+    /// The socket must be closed by the application
+    SocketClosed {
+        /// The socket to close
+        socket: SocketId,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Socket {
+    state: SocketState,
+    source_address: Option<[u8; DOIP_DIAG_COMMON_SOURCE_LEN]>,
+    inactivity_deadline: Instant,
+    alive_check_deadline: Option<Instant>,
+    encrypted: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct PendingActivation {
+    socket: SocketId,
+    request: RoutingActivationRequest,
+    awaiting: Vec<SocketId>,
+}
+
+/// Transport agnostic state machine of the server side of ISO 13400-2.
+///
+/// The entity tracks every accepted TCP socket, enforces the initial and general
+/// inactivity timers and performs routing activation, including the alive check
+/// procedure when all sockets are in use. UDP requests for the entity status
+/// and power information are answered through [`DoipEntity::handle_datagram`].
+///
+/// Authentication and confirmation of a routing activation are decided by an
+/// [`AuthenticationHandler`] and a [`ConfirmationHandler`], both accepting every
+/// tester unless replaced.
+///
+/// No IO is performed, messages to send are retrieved with
+/// [`DoipEntity::poll_transmit`] and events with [`DoipEntity::poll_event`].
+///
+/// ```rust
+/// use std::time::Instant;
+/// use doip_definitions::entity::{DoipEntity, EntityConfig, SocketState};
+///
+/// let mut entity = DoipEntity::new(EntityConfig::default());
+/// let socket = entity.accept(Instant::now());
+///
+/// assert_eq!(entity.socket_state(socket), Some(SocketState::Initialized));
+/// ```
+#[derive(Debug)]
+pub struct DoipEntity {
+    config: EntityConfig,
+    authentication: Box<dyn AuthenticationHandler>,
+    confirmation: Box<dyn ConfirmationHandler>,
+    next_socket: u32,
+    sockets: BTreeMap<SocketId, Socket>,
+    pending: Vec<PendingActivation>,
+    transmit: VecDeque<(SocketId, DoipMessage)>,
+    events: VecDeque<EntityEvent>,
+}
+
+impl DoipEntity {
+    /// Creates a new `DoipEntity` without any sockets.
+    #[must_use]
+    pub fn new(config: EntityConfig) -> Self {
+        Self {
+            config,
+            authentication: Box::new(AcceptAll),
+            confirmation: Box::new(AcceptAll),
+            next_socket: 0,
+            sockets: BTreeMap::new(),
+            pending: Vec::new(),
+            transmit: VecDeque::new(),
+            events: VecDeque::new(),
+        }
+    }
+
+    /// Sets the handler authenticating testers requesting routing activation.
+    #[must_use]
+    pub fn authentication_handler(
+        mut self,
+        authentication: impl AuthenticationHandler + 'static,
+    ) -> Self {
+        self.authentication = Box::new(authentication);
+        self
+    }
+
+    /// Sets the handler confirming the routing activation of authenticated
+    /// testers.
+    #[must_use]
+    pub fn confirmation_handler(
+        mut self,
+        confirmation: impl ConfirmationHandler + 'static,
+    ) -> Self {
+        self.confirmation = Box::new(confirmation);
+        self
+    }
+
+    /// Returns the configuration of the entity.
+    #[must_use]
+    pub fn config(&self) -> &EntityConfig {
+        &self.config
+    }
+
+    /// Returns the state of `socket`, or `None` when the socket is unknown.
+    #[must_use]
+    pub fn socket_state(&self, socket: SocketId) -> Option<SocketState> {
+        self.sockets.get(&socket).map(|socket| socket.state)
+    }
+
+    /// Returns the source address registered on `socket`.
+    #[must_use]
+    pub fn source_address(&self, socket: SocketId) -> Option<[u8; DOIP_DIAG_COMMON_SOURCE_LEN]> {
+        self.sockets
+            .get(&socket)
+            .and_then(|socket| socket.source_address)
+    }
+
+    /// Returns the number of currently open TCP sockets.
+    #[must_use]
+    pub fn open_sockets(&self) -> usize {
+        self.sockets.len()
+    }
+
+    /// Registers a newly accepted TCP socket and starts its
+    /// `T_TCP_Initial_Inactivity` timer.
+    pub fn accept(&mut self, now: Instant) -> SocketId {
+        self.register_socket(now, false)
+    }
+
+    /// Registers a newly accepted TLS socket, which may activate the routing
+    /// types listed in `encrypted_activation_types`.
+    pub fn accept_encrypted(&mut self, now: Instant) -> SocketId {
+        self.register_socket(now, true)
+    }
+
+    fn register_socket(&mut self, now: Instant, encrypted: bool) -> SocketId {
+        let socket = SocketId(self.next_socket);
+        self.next_socket = self.next_socket.wrapping_add(1);
+
+        self.sockets.insert(
+            socket,
+            Socket {
+                state: SocketState::Initialized,
+                source_address: None,
+                inactivity_deadline: now + self.config.timings.tcp_initial_inactivity,
+                alive_check_deadline: None,
+                encrypted,
+            },
+        );
+
+        socket
+    }
+
+    /// Signals that `socket` was closed by the peer or the application.
+    pub fn close(&mut self, now: Instant, socket: SocketId) {
+        if self.sockets.remove(&socket).is_none() {
+            return;
+        }
+
+        self.transmit.retain(|(id, _)| *id != socket);
+        self.pending.retain(|pending| pending.socket != socket);
+        self.resolve_alive_check(now, socket);
+    }
+
+    /// Processes a message received on `socket`.
+    ///
+    /// Messages for unknown sockets are ignored.
+    pub fn handle_message(&mut self, now: Instant, socket: SocketId, message: DoipMessage) {
+        let general_inactivity = self.config.timings.tcp_general_inactivity;
+
+        let Some(entry) = self.sockets.get_mut(&socket) else {
+            return;
+        };
+
+        if entry.state != SocketState::Initialized {
+            entry.inactivity_deadline = now + general_inactivity;
+        }
+
+        match message.payload {
+            DoipPayload::RoutingActivationRequest(request) => {
+                self.handle_routing_activation(now, socket, request, true);
+            }
+            DoipPayload::AliveCheckResponse(_) => {
+                if let Some(entry) = self.sockets.get_mut(&socket) {
+                    entry.alive_check_deadline = None;
+                }
+
+                self.resolve_alive_check(now, socket);
+            }
+            DoipPayload::DiagnosticMessage(diagnostic) => {
+                self.handle_diagnostic_message(now, socket, diagnostic);
+            }
+            DoipPayload::EntityStatusRequest(_) => {
+                let response = self.entity_status();
+                self.queue(socket, response);
+            }
+            DoipPayload::PowerInformationRequest(_) => {
+                let response = self.power_information();
+                self.queue(socket, response);
+            }
+            _ => {}
+        }
+    }
+
+    /// Answers a UDP request, returning the response to send to its origin.
+    ///
+    /// Supports the `EntityStatusRequest` and `PowerInformationRequest`.
+    #[must_use]
+    pub fn handle_datagram(&self, message: &DoipMessage) -> Option<DoipMessage> {
+        let payload = match message.payload {
+            DoipPayload::EntityStatusRequest(_) => self.entity_status(),
+            DoipPayload::PowerInformationRequest(_) => self.power_information(),
+            _ => return None,
+        };
+
+        Some(self.build(payload))
+    }
+
+    /// Processes every timer which expired at or before `now`.
+    pub fn handle_timeout(&mut self, now: Instant) {
+        let expired: Vec<SocketId> = self
+            .sockets
+            .iter()
+            .filter(|(_, socket)| {
+                socket.inactivity_deadline <= now
+                    || socket
+                        .alive_check_deadline
+                        .is_some_and(|deadline| deadline <= now)
+            })
+            .map(|(id, _)| *id)
+            .collect();
+
+        for socket in expired {
+            self.events.push_back(EntityEvent::SocketClosed { socket });
+            self.close(now, socket);
+        }
+    }
+
+    /// Returns the next instant at which [`DoipEntity::handle_timeout`] must be
+    /// called.
+    #[must_use]
+    pub fn poll_timeout(&self) -> Option<Instant> {
+        self.sockets
+            .values()
+            .flat_map(|socket| {
+                [
+                    Some(socket.inactivity_deadline),
+                    socket.alive_check_deadline,
+                ]
+            })
+            .flatten()
+            .min()
+    }
+
+    /// Returns the next message to be written to a socket.
+    pub fn poll_transmit(&mut self) -> Option<(SocketId, DoipMessage)> {
+        self.transmit.pop_front()
+    }
+
+    /// Returns the next event for the application.
+    pub fn poll_event(&mut self) -> Option<EntityEvent> {
+        self.events.pop_front()
+    }
+
+    /// Queues `payload` to be sent on `socket`.
+    pub fn send(&mut self, socket: SocketId, payload: DoipPayload) {
+        if self.sockets.contains_key(&socket) {
+            self.queue(socket, payload);
+        }
+    }
+
+    fn handle_routing_activation(
+        &mut self,
+        now: Instant,
+        socket: SocketId,
+        request: RoutingActivationRequest,
+        alive_check: bool,
+    ) {
+        let Some(entry) = self.sockets.get(&socket) else {
+            return;
+        };
+
+        if !self
+            .config
+            .activation_types
+            .contains(&request.activation_type)
+        {
+            self.deny(
+                now,
+                socket,
+                request,
+                ActivationCode::DeniedUnsupportedRoutingActivationType,
+            );
+            return;
+        }
+
+        if !entry.encrypted
+            && self
+                .config
+                .encrypted_activation_types
+                .contains(&request.activation_type)
+        {
+            self.deny(
+                now,
+                socket,
+                request,
+                ActivationCode::DeniedRequestEncryptedTLSConnection,
+            );
+            return;
+        }
+
+        if entry
+            .source_address
+            .is_some_and(|source| source != request.source_address)
+        {
+            self.deny(
+                now,
+                socket,
+                request,
+                ActivationCode::DeniedTCPSocketAlreadyConnected,
+            );
+            return;
+        }
+
+        if entry.source_address.is_none() {
+            let active_elsewhere: Vec<SocketId> = self
+                .sockets
+                .iter()
+                .filter(|(id, other)| {
+                    **id != socket && other.source_address == Some(request.source_address)
+                })
+                .map(|(id, _)| *id)
+                .collect();
+
+            if !active_elsewhere.is_empty() {
+                if alive_check {
+                    self.start_alive_check(now, socket, request, active_elsewhere);
+                } else {
+                    self.deny(
+                        now,
+                        socket,
+                        request,
+                        ActivationCode::DeniedSourceIsAlreadyActive,
+                    );
+                }
+                return;
+            }
+
+            let registered: Vec<SocketId> = self
+                .sockets
+                .iter()
+                .filter(|(_, other)| other.source_address.is_some())
+                .map(|(id, _)| *id)
+                .collect();
+
+            if registered.len() >= usize::from(self.config.max_concurrent_sockets) {
+                if alive_check {
+                    self.start_alive_check(now, socket, request, registered);
+                } else {
+                    self.deny(now, socket, request, ActivationCode::DeniedTCPSocketsFull);
+                }
+                return;
+            }
+        }
+
+        if !self.authorize(now, socket, request) {
+            return;
+        }
+
+        self.register(now, socket, request, SocketState::RegisteredRoutingActive);
+        self.respond(socket, request, ActivationCode::SuccessfullyActivated);
+        self.events.push_back(EntityEvent::RoutingActivated {
+            socket,
+            source_address: request.source_address,
+            activation_type: request.activation_type,
+        });
+    }
+
+    /// Asks the authentication and confirmation handlers, answering the request
+    /// unless routing is to be activated.
+    fn authorize(
+        &mut self,
+        now: Instant,
+        socket: SocketId,
+        request: RoutingActivationRequest,
+    ) -> bool {
+        if self.authentication.authenticate(socket, &request) == Authentication::Missing {
+            self.register(
+                now,
+                socket,
+                request,
+                SocketState::RegisteredPendingAuthentication,
+            );
+            self.respond(socket, request, ActivationCode::DeniedMissingAuthentication);
+            return false;
+        }
+
+        match self.confirmation.confirm(socket, &request) {
+            Confirmation::Confirmed => true,
+            Confirmation::Pending => {
+                self.register(
+                    now,
+                    socket,
+                    request,
+                    SocketState::RegisteredPendingConfirmation,
+                );
+                self.respond(
+                    socket,
+                    request,
+                    ActivationCode::ActivatedConfirmationRequired,
+                );
+                false
+            }
+            Confirmation::Rejected => {
+                self.deny(
+                    now,
+                    socket,
+                    request,
+                    ActivationCode::DeniedRejectedConfirmation,
+                );
+                false
+            }
+        }
+    }
+
+    fn register(
+        &mut self,
+        now: Instant,
+        socket: SocketId,
+        request: RoutingActivationRequest,
+        state: SocketState,
+    ) {
+        let general_inactivity = self.config.timings.tcp_general_inactivity;
+
+        if let Some(entry) = self.sockets.get_mut(&socket) {
+            entry.source_address = Some(request.source_address);
+            entry.state = state;
+            entry.inactivity_deadline = now + general_inactivity;
+        }
+    }
+
+    fn handle_diagnostic_message(
+        &mut self,
+        now: Instant,
+        socket: SocketId,
+        message: DiagnosticMessage,
+    ) {
+        let active = self.sockets.get(&socket).is_some_and(|entry| {
+            entry.state == SocketState::RegisteredRoutingActive
+                && entry.source_address == Some(message.source_address)
+        });
+
+        if active {
+            self.events
+                .push_back(EntityEvent::DiagnosticMessage { socket, message });
+            return;
+        }
+
+        self.queue(
+            socket,
+            DoipPayload::DiagnosticMessageNack(DiagnosticMessageNack {
+                source_address: message.target_address,
+                target_address: message.source_address,
+                nack_code: DiagnosticNackCode::InvalidSourceAddress,
+            }),
+        );
+        self.close_after_transmit(now, socket);
+    }
+
+    fn start_alive_check(
+        &mut self,
+        now: Instant,
+        socket: SocketId,
+        request: RoutingActivationRequest,
+        awaiting: Vec<SocketId>,
+    ) {
+        let alive_check = self.config.timings.tcp_alive_check;
+
+        for id in &awaiting {
+            let Some(entry) = self.sockets.get_mut(id) else {
+                continue;
+            };
+
+            if entry.alive_check_deadline.is_none() {
+                entry.alive_check_deadline = Some(now + alive_check);
+                self.queue(*id, DoipPayload::AliveCheckRequest(AliveCheckRequest {}));
+            }
+        }
+
+        self.pending.push(PendingActivation {
+            socket,
+            request,
+            awaiting,
+        });
+    }
+
+    /// Removes `socket` from every pending activation and re-evaluates the
+    /// activations which are no longer waiting on any alive check.
+    fn resolve_alive_check(&mut self, now: Instant, socket: SocketId) {
+        for pending in &mut self.pending {
+            pending.awaiting.retain(|id| *id != socket);
+        }
+
+        let (ready, waiting): (Vec<_>, Vec<_>) = self
+            .pending
+            .drain(..)
+            .partition(|pending| pending.awaiting.is_empty());
+        self.pending = waiting;
+
+        for pending in ready {
+            self.handle_routing_activation(now, pending.socket, pending.request, false);
+        }
+    }
+
+    fn deny(
+        &mut self,
+        now: Instant,
+        socket: SocketId,
+        request: RoutingActivationRequest,
+        activation_code: ActivationCode,
+    ) {
+        self.respond(socket, request, activation_code);
+        self.close_after_transmit(now, socket);
+    }
+
+    fn respond(
+        &mut self,
+        socket: SocketId,
+        request: RoutingActivationRequest,
+        activation_code: ActivationCode,
+    ) {
+        self.queue(
+            socket,
+            DoipPayload::RoutingActivationResponse(RoutingActivationResponse {
+                logical_address: request.source_address,
+                source_address: self.config.logical_address,
+                activation_code,
+                buffer: [0; DOIP_ROUTING_ACTIVATION_RES_ISO_LEN],
+                oem_specific: None,
+            }),
+        );
+    }
+
+    /// Closes `socket` while keeping the messages already queued for it, so the
+    /// application can flush them before closing the socket.
+    fn close_after_transmit(&mut self, now: Instant, socket: SocketId) {
+        if self.sockets.remove(&socket).is_some() {
+            self.pending.retain(|pending| pending.socket != socket);
+            self.events.push_back(EntityEvent::SocketClosed { socket });
+            self.resolve_alive_check(now, socket);
+        }
+    }
+
+    fn entity_status(&self) -> DoipPayload {
+        // Like `max_concurrent_sockets`, only sockets with a registered source
+        // address are counted, not those still awaiting a routing activation.
+        let registered = self
+            .sockets
+            .values()
+            .filter(|socket| socket.source_address.is_some())
+            .count();
+
+        DoipPayload::EntityStatusResponse(EntityStatusResponse {
+            node_type: self.config.node_type,
+            max_concurrent_sockets: [self.config.max_concurrent_sockets],
+            currently_open_sockets: [u8::try_from(registered).unwrap_or(u8::MAX)],
+            max_data_size: self.config.max_data_size.to_be_bytes(),
+        })
+    }
+
+    fn power_information(&self) -> DoipPayload {
+        DoipPayload::PowerInformationResponse(PowerInformationResponse {
+            power_mode: self.config.power_mode,
+        })
+    }
+
+    fn queue(&mut self, socket: SocketId, payload: DoipPayload) {
+        let message = self.build(payload);
+        self.transmit.push_back((socket, message));
+    }
+
+    fn build(&self, payload: DoipPayload) -> DoipMessage {
+        DoipMessageBuilder::new()
+            .protocol_version(self.config.protocol_version)
+            .payload(payload)
+            .build()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, Instant};
+
+    use crate::{
+        builder::DoipMessageBuilder,
+        entity::{
+            Authentication, AuthenticationHandler, Confirmation, ConfirmationHandler, DoipEntity,
+            EntityConfig, EntityEvent, SocketId, SocketState,
+        },
+        message::DoipMessage,
+        payload::{
+            ActivationCode, ActivationType, AliveCheckResponse, DoipPayload, EntityStatusRequest,
+            RoutingActivationRequest,
+        },
+    };
+
+    #[derive(Debug)]
+    struct OemKey;
+
+    impl AuthenticationHandler for OemKey {
+        fn authenticate(
+            &mut self,
+            _socket: SocketId,
+            request: &RoutingActivationRequest,
+        ) -> Authentication {
+            match request.oem_specific {
+                Some([0xCA, 0xFE, _, _]) => Authentication::Authenticated,
+                _ => Authentication::Missing,
+            }
+        }
+    }
+
+    #[derive(Debug)]
+    struct ConfirmOnRetry(u8);
+
+    impl ConfirmationHandler for ConfirmOnRetry {
+        fn confirm(
+            &mut self,
+            _socket: SocketId,
+            _request: &RoutingActivationRequest,
+        ) -> Confirmation {
+            self.0 += 1;
+
+            if self.0 > 1 {
+                Confirmation::Confirmed
+            } else {
+                Confirmation::Pending
+            }
+        }
+    }
+
+    fn activation_request(source_address: [u8; 2]) -> DoipMessage {
+        oem_activation_request(source_address, None)
+    }
+
+    fn oem_activation_request(
+        source_address: [u8; 2],
+        oem_specific: Option<[u8; 4]>,
+    ) -> DoipMessage {
+        DoipMessageBuilder::new()
+            .payload(DoipPayload::RoutingActivationRequest(
+                RoutingActivationRequest {
+                    source_address,
+                    activation_type: ActivationType::Default,
+                    buffer: [0; 4],
+                    oem_specific,
+                },
+            ))
+            .build()
+    }
+
+    fn activation_code(message: &DoipMessage) -> Option<ActivationCode> {
+        match message.payload {
+            DoipPayload::RoutingActivationResponse(response) => Some(response.activation_code),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_sockets_full_alive_check() {
+        let now = Instant::now();
+        let mut entity = DoipEntity::new(EntityConfig::default());
+
+        let first = entity.accept(now);
+        entity.handle_message(now, first, activation_request([0x0E, 0x00]));
+        let (_, response) = entity.poll_transmit().unwrap();
+        assert_eq!(
+            activation_code(&response),
+            Some(ActivationCode::SuccessfullyActivated)
+        );
+
+        // The first tester answers the alive check, the second is denied.
+        let second = entity.accept(now);
+        entity.handle_message(now, second, activation_request([0x0E, 0x01]));
+        let (socket, request) = entity.poll_transmit().unwrap();
+        assert_eq!(socket, first);
+        assert!(matches!(request.payload, DoipPayload::AliveCheckRequest(_)));
+
+        entity.handle_message(
+            now,
+            first,
+            DoipMessageBuilder::new()
+                .payload(DoipPayload::AliveCheckResponse(AliveCheckResponse {
+                    source_address: [0x0E, 0x00],
+                }))
+                .build(),
+        );
+        let (socket, response) = entity.poll_transmit().unwrap();
+        assert_eq!(socket, second);
+        assert_eq!(
+            activation_code(&response),
+            Some(ActivationCode::DeniedTCPSocketsFull)
+        );
+        assert_eq!(entity.socket_state(second), None);
+
+        // The first tester is silent, so its socket is replaced.
+        let third = entity.accept(now);
+        entity.handle_message(now, third, activation_request([0x0E, 0x02]));
+        entity.poll_transmit();
+        entity.handle_timeout(now + Duration::from_secs(1));
+
+        let (socket, response) = entity.poll_transmit().unwrap();
+        assert_eq!(socket, third);
+        assert_eq!(
+            activation_code(&response),
+            Some(ActivationCode::SuccessfullyActivated)
+        );
+        assert_eq!(entity.socket_state(first), None);
+        assert_eq!(
+            entity.socket_state(third),
+            Some(SocketState::RegisteredRoutingActive)
+        );
+    }
+
+    #[test]
+    fn test_entity_status() {
+        let now = Instant::now();
+        let mut entity = DoipEntity::new(EntityConfig::default());
+        let request = DoipMessageBuilder::new()
+            .payload(DoipPayload::EntityStatusRequest(EntityStatusRequest {}))
+            .build();
+        let open_sockets = |entity: &DoipEntity| match entity.handle_datagram(&request) {
+            Some(DoipMessage {
+                payload: DoipPayload::EntityStatusResponse(response),
+                ..
+            }) => response.currently_open_sockets[0],
+            _ => panic!("expected an EntityStatusResponse"),
+        };
+
+        let socket = entity.accept(now);
+        assert_eq!(open_sockets(&entity), 0);
+
+        entity.handle_message(now, socket, activation_request([0x0E, 0x00]));
+        assert_eq!(open_sockets(&entity), 1);
+    }
+
+    #[test]
+    fn test_initial_inactivity() {
+        let now = Instant::now();
+        let mut entity = DoipEntity::new(EntityConfig::default());
+
+        let socket = entity.accept(now);
+        assert_eq!(entity.poll_timeout(), Some(now + Duration::from_secs(2)));
+
+        entity.handle_timeout(now + Duration::from_secs(2));
+        assert!(entity.poll_event().is_some_and(
+            |event| matches!(event, EntityEvent::SocketClosed { socket: closed } if closed == socket)
+        ));
+        assert_eq!(entity.open_sockets(), 0);
+    }
+
+    #[test]
+    fn test_encrypted_activation_type() {
+        let now = Instant::now();
+        let mut entity = DoipEntity::new(EntityConfig {
+            encrypted_activation_types: vec![ActivationType::Default],
+            ..EntityConfig::default()
+        });
+
+        let plain = entity.accept(now);
+        entity.handle_message(now, plain, activation_request([0x0E, 0x00]));
+        let (_, response) = entity.poll_transmit().unwrap();
+        assert_eq!(
+            activation_code(&response),
+            Some(ActivationCode::DeniedRequestEncryptedTLSConnection)
+        );
+
+        let encrypted = entity.accept_encrypted(now);
+        entity.handle_message(now, encrypted, activation_request([0x0E, 0x00]));
+        let (_, response) = entity.poll_transmit().unwrap();
+        assert_eq!(
+            activation_code(&response),
+            Some(ActivationCode::SuccessfullyActivated)
+        );
+    }
+
+    #[test]
+    fn test_authentication_and_confirmation() {
+        let now = Instant::now();
+        let mut entity = DoipEntity::new(EntityConfig::default())
+            .authentication_handler(OemKey)
+            .confirmation_handler(ConfirmOnRetry(0));
+
+        let socket = entity.accept(now);
+        entity.handle_message(now, socket, activation_request([0x0E, 0x00]));
+        let (_, response) = entity.poll_transmit().unwrap();
+        assert_eq!(
+            activation_code(&response),
+            Some(ActivationCode::DeniedMissingAuthentication)
+        );
+        assert_eq!(
+            entity.socket_state(socket),
+            Some(SocketState::RegisteredPendingAuthentication)
+        );
+
+        let authenticated = oem_activation_request([0x0E, 0x00], Some([0xCA, 0xFE, 0, 0]));
+        entity.handle_message(now, socket, authenticated.clone());
+        let (_, response) = entity.poll_transmit().unwrap();
+        assert_eq!(
+            activation_code(&response),
+            Some(ActivationCode::ActivatedConfirmationRequired)
+        );
+        assert_eq!(
+            entity.socket_state(socket),
+            Some(SocketState::RegisteredPendingConfirmation)
+        );
+
+        entity.handle_message(now, socket, authenticated);
+        let (_, response) = entity.poll_transmit().unwrap();
+        assert_eq!(
+            activation_code(&response),
+            Some(ActivationCode::SuccessfullyActivated)
+        );
+        assert_eq!(
+            entity.socket_state(socket),
+            Some(SocketState::RegisteredRoutingActive)
+        );
+    }
+}
@@ -86,6 +86,18 @@ pub enum Error {
         value: u8,
     },
 
+    /// Invalid UDS `ServiceId`
+    InvalidServiceId {
+        /// Value
+        value: u8,
+    },
+
+    /// Invalid UDS `RoutineControlType`
+    InvalidRoutineControlType {
+        /// Value
+        value: u8,
+    },
+
     /// Invalid `PayloadType`
     InvalidPayloadType {
         /// Value
@@ -98,6 +110,126 @@ pub enum Error {
         size: usize,
     },
 
+    /// When a `DiagnosticMessage` is sent before routing has been activated
+    RoutingNotActive,
+
+    /// When the connection to the `DoIP` entity is not established
+    NotConnected,
+
+    /// When the `DoIP` entity denies routing activation
+    RoutingActivationDenied {
+        /// The denial code sent by the entity
+        activation_code: crate::payload::ActivationCode,
+    },
+
+    /// When the `DoIP` entity rejects a `DiagnosticMessage`
+    DiagnosticNack {
+        /// The negative acknowledgement code
+        nack_code: crate::payload::DiagnosticNackCode,
+    },
+
+    /// When the `DoIP` entity rejects a message with a `GenericNack`
+    GenericNack {
+        /// The negative acknowledgement code
+        nack_code: crate::payload::NackCode,
+    },
+
+    /// When a UDS server answers with a negative response
+    #[cfg(feature = "std")]
+    NegativeResponse {
+        /// Service identifier of the rejected request
+        service_id: u8,
+
+        /// Reason of the rejection
+        response_code: crate::uds::NegativeResponseCode,
+    },
+
+    /// When a UDS server answers with a positive response to another request
+    #[cfg(feature = "std")]
+    UnexpectedResponse {
+        /// Service identifier of the response
+        service_id: u8,
+    },
+
+    /// When a `KeyAlgorithm` calculates no key for a `SecurityAccess` seed
+    #[cfg(feature = "std")]
+    KeyUnavailable {
+        /// The `requestSeed` sub-function of the seed
+        level: u8,
+    },
+
+    /// When a data identifier is not registered with a `DataIdentifierRegistry`
+    #[cfg(feature = "std")]
+    UnknownDataIdentifier {
+        /// The data identifier
+        identifier: u16,
+    },
+
+    /// When a routine identifier is not registered with a `RoutineRegistry`
+    #[cfg(feature = "std")]
+    UnknownRoutineIdentifier {
+        /// The routine identifier
+        identifier: u16,
+    },
+
+    /// When a data record or value does not match the codec of its data or
+    /// routine identifier
+    #[cfg(feature = "std")]
+    InvalidDataRecord {
+        /// The data or routine identifier
+        identifier: u16,
+    },
+
+    /// When a `RequestDownload` response leaves no room for data in the
+    /// `TransferData` requests
+    #[cfg(feature = "std")]
+    InvalidBlockLength {
+        /// The largest `TransferData` request accepted by the server
+        max_number_of_block_length: u64,
+    },
+
+    /// When a `TransferData` response acknowledges another block than the one
+    /// sent
+    #[cfg(feature = "std")]
+    InvalidBlockSequenceCounter {
+        /// Counter of the sent block
+        expected: u8,
+
+        /// Counter of the acknowledged block
+        received: u8,
+    },
+
+    /// When a `ReadDTCInformation` response has a report type which cannot be
+    /// decoded
+    #[cfg(feature = "std")]
+    UnsupportedReportType {
+        /// The report type sub-function
+        report_type: u8,
+    },
+
+    /// When an expected response was not received in time
+    Timeout,
+
+    /// Derived implementation for standard library IO errors
+    #[cfg(feature = "std")]
+    #[from]
+    Io(std::io::Error),
+
+    /// Derived implementation for rustls errors
+    #[cfg(feature = "tls")]
+    #[from]
+    Tls(rustls::Error),
+
+    /// Derived implementation for JSON description errors
+    #[cfg(feature = "description")]
+    #[from]
+    Json(serde_json::Error),
+
+    /// Derived implementation for TOML description errors
+    #[cfg(feature = "description")]
+    #[from]
+    Toml(toml::de::Error),
+
     /// Derived implementation for standard library IO errors
     #[from]
     #[allow(clippy::enum_variant_names)]
@@ -0,0 +1,279 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    definitions::DOIP_DIAG_COMMON_TARGET_LEN,
+    entity::{DoipEntity, SocketId, SocketState},
+    payload::{
+        DiagnosticAckCode, DiagnosticMessage, DiagnosticMessageAck, DiagnosticMessageNack,
+        DiagnosticNackCode, DoipPayload,
+    },
+};
+
+/// Logical address of a `DoIP` entity or a node behind a gateway.
+pub type LogicalAddress = [u8; DOIP_DIAG_COMMON_TARGET_LEN];
+
+/// The outcome of routing a `DiagnosticMessage` through a [`RoutingTable`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum RoutingDecision<N> {
+    /// The message is forwarded to `nodes` and acknowledged to the tester with `ack`
+    Forward {
+        /// Downstream nodes which receive the message
+        nodes: Vec<N>,
+
+        /// Acknowledgement to send to the tester
+        ack: DiagnosticMessageAck,
+    },
+
+    /// The message is dropped and rejected to the tester with `nack`
+    Reject {
+        /// Negative acknowledgement to send to the tester
+        nack: DiagnosticMessageNack,
+    },
+}
+
+impl<N> RoutingDecision<N> {
+    /// Returns the payload to send back to the tester.
+    #[must_use]
+    pub fn response(&self) -> DoipPayload {
+        match self {
+            RoutingDecision::Forward { ack, .. } => DoipPayload::DiagnosticMessageAck(ack.clone()),
+            RoutingDecision::Reject { nack } => DoipPayload::DiagnosticMessageNack(*nack),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Route<N> {
+    Physical(N),
+    Functional(Vec<N>),
+}
+
+/// Maps target logical addresses to the downstream nodes of a gateway.
+///
+/// Physical addresses resolve to a single node, functional addresses to a group
+/// of nodes. [`RoutingTable::route`] validates an incoming `DiagnosticMessage`
+/// in the order given by ISO 13400-2 and returns the acknowledgement or negative
+/// acknowledgement to send to the tester. `N` is any identifier the gateway uses
+/// for its downstream nodes.
+///
+/// ```rust
+/// use doip_definitions::entity::SocketState;
+/// use doip_definitions::gateway::{RoutingDecision, RoutingTable};
+/// use doip_definitions::payload::DiagnosticMessage;
+///
+/// let mut table = RoutingTable::new();
+/// table.insert_physical([0x10, 0x01], "engine");
+/// table.insert_functional([0xE4, 0x00], vec!["engine", "gearbox"]);
+///
+/// let message = DiagnosticMessage {
+///     source_address: [0x0E, 0x00],
+///     target_address: [0xE4, 0x00],
+///     message: vec![0x3E, 0x80],
+/// };
+///
+/// let decision = table.route(
+///     SocketState::RegisteredRoutingActive,
+///     Some([0x0E, 0x00]),
+///     &message,
+/// );
+///
+/// assert!(matches!(decision, RoutingDecision::Forward { nodes, .. } if nodes.len() == 2));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoutingTable<N> {
+    routes: BTreeMap<LogicalAddress, Route<N>>,
+    unreachable: Vec<N>,
+    max_message_size: Option<usize>,
+}
+
+impl<N> Default for RoutingTable<N> {
+    fn default() -> Self {
+        Self {
+            routes: BTreeMap::new(),
+            unreachable: Vec::new(),
+            max_message_size: None,
+        }
+    }
+}
+
+impl<N: Clone + PartialEq> RoutingTable<N> {
+    /// Creates an empty `RoutingTable` without a message size limit.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the largest UDS message, in bytes, which is forwarded.
+    #[must_use]
+    pub fn max_message_size(mut self, max_message_size: usize) -> Self {
+        self.max_message_size = Some(max_message_size);
+        self
+    }
+
+    /// Routes the physical `address` to `node`, replacing any previous route.
+    pub fn insert_physical(&mut self, address: LogicalAddress, node: N) {
+        self.routes.insert(address, Route::Physical(node));
+    }
+
+    /// Routes the functional `address` to `nodes`, replacing any previous route.
+    pub fn insert_functional(&mut self, address: LogicalAddress, nodes: Vec<N>) {
+        self.routes.insert(address, Route::Functional(nodes));
+    }
+
+    /// Removes the route of `address`.
+    pub fn remove(&mut self, address: LogicalAddress) {
+        self.routes.remove(&address);
+    }
+
+    /// Marks `node` as reachable or unreachable, e.g. when its bus is asleep.
+    pub fn set_reachable(&mut self, node: &N, reachable: bool) {
+        self.unreachable.retain(|other| other != node);
+
+        if !reachable {
+            self.unreachable.push(node.clone());
+        }
+    }
+
+    /// Returns the nodes `address` resolves to.
+    #[must_use]
+    pub fn resolve(&self, address: LogicalAddress) -> Option<Vec<N>> {
+        match self.routes.get(&address)? {
+            Route::Physical(node) => Some(vec![node.clone()]),
+            Route::Functional(nodes) => Some(nodes.clone()),
+        }
+    }
+
+    /// Decides how to handle `message` received on a socket in `state` on which
+    /// `registered_source` is registered.
+    ///
+    /// Functional requests are forwarded to every reachable node of the group and
+    /// only rejected with `TargetUnreachable` when none is reachable.
+    #[must_use]
+    pub fn route(
+        &self,
+        state: SocketState,
+        registered_source: Option<LogicalAddress>,
+        message: &DiagnosticMessage,
+    ) -> RoutingDecision<N> {
+        let reject = |nack_code| RoutingDecision::Reject {
+            nack: DiagnosticMessageNack {
+                source_address: message.target_address,
+                target_address: message.source_address,
+                nack_code,
+            },
+        };
+
+        if state != SocketState::RegisteredRoutingActive
+            || registered_source != Some(message.source_address)
+        {
+            return reject(DiagnosticNackCode::InvalidSourceAddress);
+        }
+
+        let Some(route) = self.routes.get(&message.target_address) else {
+            return reject(DiagnosticNackCode::UnknownTargetAddress);
+        };
+
+        if self
+            .max_message_size
+            .is_some_and(|max| message.message.len() > max)
+        {
+            return reject(DiagnosticNackCode::DiagnosticMessageTooLarge);
+        }
+
+        let nodes: Vec<N> = match route {
+            Route::Physical(node) => vec![node.clone()],
+            Route::Functional(nodes) => nodes.clone(),
+        }
+        .into_iter()
+        .filter(|node| !self.unreachable.contains(node))
+        .collect();
+
+        if nodes.is_empty() {
+            return reject(DiagnosticNackCode::TargetUnreachable);
+        }
+
+        RoutingDecision::Forward {
+            nodes,
+            ack: DiagnosticMessageAck {
+                source_address: message.target_address,
+                target_address: message.source_address,
+                ack_code: DiagnosticAckCode::Acknowledged,
+                previous_message: message.message.clone(),
+            },
+        }
+    }
+
+    /// Decides how to handle `message` received on `socket` of `entity`.
+    ///
+    /// Messages on unknown sockets are rejected with `InvalidSourceAddress`.
+    #[must_use]
+    pub fn route_for(
+        &self,
+        entity: &DoipEntity,
+        socket: SocketId,
+        message: &DiagnosticMessage,
+    ) -> RoutingDecision<N> {
+        let state = entity
+            .socket_state(socket)
+            .unwrap_or(SocketState::Initialized);
+
+        self.route(state, entity.source_address(socket), message)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        entity::SocketState,
+        gateway::{RoutingDecision, RoutingTable},
+        payload::{DiagnosticMessage, DiagnosticNackCode},
+    };
+
+    fn nack_code<N>(decision: &RoutingDecision<N>) -> Option<DiagnosticNackCode> {
+        match decision {
+            RoutingDecision::Reject { nack } => Some(nack.nack_code),
+            RoutingDecision::Forward { .. } => None,
+        }
+    }
+
+    #[test]
+    fn test_route_rejections() {
+        let mut table = RoutingTable::new().max_message_size(4);
+        table.insert_physical([0x10, 0x01], 1);
+
+        let mut message = DiagnosticMessage {
+            source_address: [0x0E, 0x00],
+            target_address: [0x10, 0x01],
+            message: vec![0x22, 0xF1, 0x90],
+        };
+        let active = SocketState::RegisteredRoutingActive;
+        let source = Some([0x0E, 0x00]);
+
+        assert!(matches!(
+            table.route(active, source, &message),
+            RoutingDecision::Forward { .. }
+        ));
+        assert_eq!(
+            nack_code(&table.route(SocketState::Initialized, None, &message)),
+            Some(DiagnosticNackCode::InvalidSourceAddress)
+        );
+
+        table.set_reachable(&1, false);
+        assert_eq!(
+            nack_code(&table.route(active, source, &message)),
+            Some(DiagnosticNackCode::TargetUnreachable)
+        );
+
+        message.message.extend_from_slice(&[0x00, 0x00]);
+        assert_eq!(
+            nack_code(&table.route(active, source, &message)),
+            Some(DiagnosticNackCode::DiagnosticMessageTooLarge)
+        );
+
+        message.target_address = [0x10, 0x02];
+        assert_eq!(
+            nack_code(&table.route(active, source, &message)),
+            Some(DiagnosticNackCode::UnknownTargetAddress)
+        );
+    }
+}
@@ -0,0 +1,265 @@
+use std::time::{Duration, Instant};
+
+use crate::{
+    definitions::DOIP_DIAG_COMMON_TARGET_LEN,
+    payload::DiagnosticMessage,
+    timing::S3_CLIENT,
+    uds::{is_response_pending, ResetType, ServiceId, SessionType, POSITIVE_RESPONSE_OFFSET},
+};
+
+/// UDS `TesterPresent` request with the suppress positive response bit set.
+pub const TESTER_PRESENT_REQUEST: [u8; 2] = [0x3E, 0x80];
+
+/// Keeps the non-default diagnostic session of a target alive by scheduling
+/// `TesterPresent` requests.
+///
+/// The scheduler performs no IO itself. While it is active, a request
+/// [`TESTER_PRESENT_REQUEST`] is due every `interval` and retrieved with
+/// [`TesterPresentScheduler::poll_transmit`]. Requests sent by the application
+/// to the target address of the scheduler are reported with
+/// [`TesterPresentScheduler::handle_request_sent`] and
+/// [`TesterPresentScheduler::handle_request_completed`], no `TesterPresent` is
+/// sent while any of them is in flight and the interval restarts once the last
+/// one completes, as every request resets the `S3Server` timer of the target.
+/// Requests to other addresses do not keep the target alive and must not be
+/// reported.
+///
+/// The target may be a physical or a functional address. Positive responses of
+/// a physical target are watched for session changes: entering a non-default
+/// session starts the scheduler, returning to the default session or resetting
+/// the ECU stops it. Responses of the target to requests sent to another
+/// address, e.g. a functional one, are passed to
+/// [`TesterPresentScheduler::handle_response`].
+///
+/// ```rust
+/// use std::time::{Duration, Instant};
+/// use doip_definitions::keep_alive::{TesterPresentScheduler, TESTER_PRESENT_REQUEST};
+/// use doip_definitions::payload::DiagnosticMessage;
+///
+/// let now = Instant::now();
+/// let mut scheduler = TesterPresentScheduler::new([0x10, 0x01], Duration::from_secs(2));
+///
+/// scheduler.handle_request_sent(now);
+/// scheduler.handle_request_completed(
+///     now,
+///     Some(&DiagnosticMessage {
+///         source_address: [0x10, 0x01],
+///         target_address: [0x0E, 0x00],
+///         message: vec![0x50, 0x03, 0x00, 0x32, 0x01, 0xF4],
+///     }),
+/// );
+/// assert!(scheduler.is_active());
+///
+/// scheduler.handle_timeout(now + Duration::from_secs(2));
+/// assert_eq!(
+///     scheduler.poll_transmit(),
+///     Some(([0x10, 0x01], TESTER_PRESENT_REQUEST.to_vec()))
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TesterPresentScheduler {
+    target_address: [u8; DOIP_DIAG_COMMON_TARGET_LEN],
+    interval: Duration,
+    active: bool,
+    in_flight: usize,
+    deadline: Instant,
+    due: bool,
+}
+
+impl TesterPresentScheduler {
+    /// Creates a new, inactive `TesterPresentScheduler` for `target_address`.
+    #[must_use]
+    pub fn new(target_address: [u8; DOIP_DIAG_COMMON_TARGET_LEN], interval: Duration) -> Self {
+        Self {
+            target_address,
+            interval,
+            active: false,
+            in_flight: 0,
+            deadline: Instant::now(),
+            due: false,
+        }
+    }
+
+    /// Creates a new, inactive `TesterPresentScheduler` for `target_address`
+    /// sending every `S3Client`.
+    #[must_use]
+    pub fn with_default_interval(target_address: [u8; DOIP_DIAG_COMMON_TARGET_LEN]) -> Self {
+        Self::new(target_address, S3_CLIENT)
+    }
+
+    /// Returns the address the `TesterPresent` requests are sent to.
+    #[must_use]
+    pub fn target_address(&self) -> [u8; DOIP_DIAG_COMMON_TARGET_LEN] {
+        self.target_address
+    }
+
+    /// Returns true while `TesterPresent` requests are scheduled.
+    #[must_use]
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Starts sending `TesterPresent` requests, the first one after `interval`.
+    pub fn start(&mut self, now: Instant) {
+        self.active = true;
+        self.deadline = now + self.interval;
+    }
+
+    /// Stops sending `TesterPresent` requests and drops a due one.
+    pub fn stop(&mut self) {
+        self.active = false;
+        self.due = false;
+    }
+
+    /// Pauses the scheduler while the request sent at `now` is in flight,
+    /// dropping a due `TesterPresent` as the request keeps the session alive.
+    pub fn handle_request_sent(&mut self, _now: Instant) {
+        self.in_flight += 1;
+        self.due = false;
+    }
+
+    /// Resumes the scheduler once every request is completed, restarting the
+    /// interval at `now`.
+    ///
+    /// `response` is the final response of the request, or `None` when it was
+    /// rejected, timed out or cancelled. A `requestCorrectlyReceived-ResponsePending`
+    /// does not complete a request and is ignored.
+    pub fn handle_request_completed(&mut self, now: Instant, response: Option<&DiagnosticMessage>) {
+        if response.is_some_and(|response| is_response_pending(&response.message)) {
+            return;
+        }
+
+        self.in_flight = self.in_flight.saturating_sub(1);
+        self.deadline = now + self.interval;
+
+        if let Some(response) = response {
+            self.handle_response(now, response);
+        }
+    }
+
+    /// Starts or stops the scheduler on a session change reported by
+    /// `response`, ignoring the responses of other targets.
+    pub fn handle_response(&mut self, now: Instant, response: &DiagnosticMessage) {
+        if response.source_address != self.target_address {
+            return;
+        }
+
+        let response = response.message.as_slice();
+        let session_control =
+            u8::from(ServiceId::DiagnosticSessionControl) + POSITIVE_RESPONSE_OFFSET;
+        let ecu_reset = u8::from(ServiceId::EcuReset) + POSITIVE_RESPONSE_OFFSET;
+
+        match (response.first(), response.get(1)) {
+            (Some(&sid), Some(&session)) if sid == session_control => {
+                if SessionType::from(session) == SessionType::Default {
+                    self.stop();
+                } else if !self.active {
+                    self.start(now);
+                }
+            }
+            (Some(&sid), Some(&reset))
+                if sid == ecu_reset
+                    && !matches!(
+                        ResetType::from(reset),
+                        ResetType::EnableRapidPowerShutDown | ResetType::DisableRapidPowerShutDown
+                    ) =>
+            {
+                self.stop();
+            }
+            _ => {}
+        }
+    }
+
+    /// Processes the interval timer if it expired at or before `now`.
+    pub fn handle_timeout(&mut self, now: Instant) {
+        if self.active && self.in_flight == 0 && self.deadline <= now {
+            self.due = true;
+            self.deadline = now + self.interval;
+        }
+    }
+
+    /// Returns the next instant at which [`TesterPresentScheduler::handle_timeout`]
+    /// must be called, or `None` while the scheduler is inactive or paused.
+    #[must_use]
+    pub fn poll_timeout(&self) -> Option<Instant> {
+        (self.active && self.in_flight == 0).then_some(self.deadline)
+    }
+
+    /// Returns the target address and UDS message of a due `TesterPresent`.
+    pub fn poll_transmit(&mut self) -> Option<([u8; DOIP_DIAG_COMMON_TARGET_LEN], Vec<u8>)> {
+        if !std::mem::take(&mut self.due) {
+            return None;
+        }
+
+        Some((self.target_address, TESTER_PRESENT_REQUEST.to_vec()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, Instant};
+
+    use crate::{
+        keep_alive::{TesterPresentScheduler, TESTER_PRESENT_REQUEST},
+        payload::DiagnosticMessage,
+    };
+
+    fn response(message: &[u8]) -> DiagnosticMessage {
+        DiagnosticMessage {
+            source_address: [0x10, 0x01],
+            target_address: [0x0E, 0x00],
+            message: message.to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_pause_while_in_flight() {
+        let now = Instant::now();
+        let second = Duration::from_secs(1);
+        let mut scheduler = TesterPresentScheduler::new([0x10, 0x01], 2 * second);
+
+        assert_eq!(scheduler.poll_timeout(), None);
+        scheduler.start(now);
+        assert_eq!(scheduler.poll_timeout(), Some(now + 2 * second));
+
+        scheduler.handle_request_sent(now + second);
+        scheduler.handle_timeout(now + 3 * second);
+        assert_eq!(scheduler.poll_timeout(), None);
+        assert_eq!(scheduler.poll_transmit(), None);
+
+        scheduler.handle_request_completed(now + 3 * second, Some(&response(&[0x7F, 0x31, 0x78])));
+        assert_eq!(scheduler.poll_timeout(), None);
+
+        scheduler.handle_request_completed(now + 4 * second, None);
+        assert_eq!(scheduler.poll_timeout(), Some(now + 6 * second));
+
+        scheduler.handle_timeout(now + 6 * second);
+        assert_eq!(
+            scheduler.poll_transmit(),
+            Some(([0x10, 0x01], TESTER_PRESENT_REQUEST.to_vec()))
+        );
+        assert_eq!(scheduler.poll_transmit(), None);
+        assert_eq!(scheduler.poll_timeout(), Some(now + 8 * second));
+    }
+
+    #[test]
+    fn test_follow_session() {
+        let now = Instant::now();
+        let mut scheduler = TesterPresentScheduler::with_default_interval([0x10, 0x01]);
+
+        scheduler.handle_request_sent(now);
+        scheduler
+            .handle_request_completed(now, Some(&response(&[0x50, 0x02, 0x00, 0x32, 0x01, 0xF4])));
+        assert!(scheduler.is_active());
+
+        scheduler.handle_request_sent(now);
+        scheduler.handle_request_completed(now, Some(&response(&[0x51, 0x01])));
+        assert!(!scheduler.is_active());
+
+        scheduler.start(now);
+        scheduler.handle_request_sent(now);
+        scheduler
+            .handle_request_completed(now, Some(&response(&[0x50, 0x01, 0x00, 0x32, 0x01, 0xF4])));
+        assert!(!scheduler.is_active());
+    }
+}
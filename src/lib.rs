@@ -91,6 +91,14 @@ pub mod payload {
 /// and so their definitions were lifted so to support this crate.
 pub mod definitions;
 
+/// Timing parameters defined by ISO 13400-2.
+///
+/// Each parameter is available as a `core::time::Duration` constant named after
+/// the standard, e.g. `A_DoIP_Ctrl` becomes [`timing::A_DOIP_CTRL`]. Applications
+/// which need to deviate from the standard values can use [`timing::DoipTimings`]
+/// which defaults to the same values.
+pub mod timing;
+
 /// Contains the implementations for the overarching `DoIP Message` structure.
 pub mod message {
     pub use crate::doip_message::DoipMessage;
@@ -148,6 +156,117 @@ pub mod message {
 #[cfg(feature = "builder")]
 pub mod builder;
 
+/// Transport agnostic state machine for the tester side of a `DoIP` connection.
+///
+/// [`tester::TesterConnection`] consumes received `DoipMessage`s and timer ticks and
+/// produces the messages to send and events for the application. It covers routing
+/// activation, alive checks and the acknowledgement of diagnostic messages so the
+/// same protocol core can be reused on top of any socket implementation.
+#[cfg(feature = "builder")]
+pub mod tester;
+
+/// Transport agnostic state machine for the server side of a `DoIP` entity.
+///
+/// [`entity::DoipEntity`] tracks the TCP sockets of connected testers through the
+/// socket states defined by ISO 13400-2, enforces the inactivity timers and handles
+/// routing activation including the alive check procedure. Simulators and gateways
+/// drive it with received messages and timer ticks and write the produced messages
+/// to their sockets.
+///
+/// [`entity::VehicleAnnouncer`] schedules the vehicle announcements sent on startup
+/// and answers vehicle identification requests for the entity.
+#[cfg(feature = "builder")]
+pub mod entity;
+
+/// Transport agnostic vehicle discovery.
+///
+/// [`discovery::VehicleDiscovery`] produces the vehicle identification request and
+/// collects the `VehicleAnnouncementMessage` responses until the discovery timer
+/// expires, reporting a deduplicated list of vehicles and their entities.
+#[cfg(feature = "builder")]
+pub mod discovery;
+
+/// Diagnostic message routing for `DoIP` gateways.
+///
+/// [`gateway::RoutingTable`] maps physical and functional target addresses to the
+/// downstream nodes of a gateway and decides whether an incoming `DiagnosticMessage`
+/// is forwarded and acknowledged or rejected with a `DiagnosticMessageNack`.
+#[cfg(feature = "builder")]
+pub mod gateway;
+
+/// Request and response correlation for diagnostic messages.
+///
+/// [`correlation::DiagnosticCorrelator`] pairs each sent `DiagnosticMessage` with its
+/// `DiagnosticMessageAck` or `DiagnosticMessageNack` and the following response from
+/// the target, applying the acknowledgement and response timeouts.
+#[cfg(feature = "std")]
+pub mod correlation;
+
+/// Keep-alive of non-default diagnostic sessions.
+///
+/// [`keep_alive::TesterPresentScheduler`] schedules suppressed `TesterPresent`
+/// requests to a physical or functional target while a non-default session is
+/// active, pausing while other requests are in flight so the ECU does not fall
+/// back to the default session during long procedures.
+#[cfg(feature = "std")]
+pub mod keep_alive;
+
+/// Diagnostic session and server timing management.
+///
+/// [`session::SessionManager`] tracks the active session of each target and the
+/// `P2Server_max` and `P2*Server_max` it reported, and enters the session again
+/// after an `ECUReset` or a lost connection.
+#[cfg(feature = "std")]
+pub mod session;
+
+/// In-memory `DoIP` entity for tests.
+///
+/// [`mock::MockEntity`] is configured with the identification of an ECU and
+/// scripted UDS responses and answers `DoipMessage`s without any network, so code
+/// built on [`builder::DoipMessageBuilder`] can be tested without hardware.
+#[cfg(feature = "builder")]
+pub mod mock;
+
+/// Blocking `DoIP` transports on top of `std::net`.
+///
+/// [`transport::DoipTcpClient`] connects to an entity, activates routing and sends
+/// diagnostic messages in a single call each, without an async runtime.
+/// [`transport::discover`] broadcasts a vehicle identification request over UDP and
+/// collects the announcements of every responding entity, or multicasts it to
+/// `FF02::1` on IPv6.
+#[cfg(feature = "builder")]
+pub mod transport;
+
+/// Typed UDS (ISO 14229-1) requests and responses.
+///
+/// [`uds::UdsRequest`] and [`uds::UdsResponse`] convert from and into the
+/// `message` of a `DiagnosticMessage` for the core diagnostic services, replacing
+/// hand written indexing into the raw UDS bytes. [`uds::check_response`] turns
+/// negative responses into an [`error::Error::NegativeResponse`] with a typed
+/// [`uds::NegativeResponseCode`].
+/// [`uds::DataIdentifierRegistry`] decodes and encodes the data records of
+/// `ReadDataByIdentifier` and `WriteDataByIdentifier` with typed codecs,
+/// [`uds::RoutineRegistry`] those of `RoutineControl` and [`uds::DtcRegistry`]
+/// names DTCs. The `description` feature loads all three from a JSON or TOML
+/// file with `uds::UdsDescription`. [`uds::SecurityAccess`] runs the
+/// `requestSeed` / `sendKey` exchange with a user supplied
+/// [`uds::KeyAlgorithm`] and [`uds::ProgrammingSequence`] downloads data into
+/// the memory of a server in blocks of `TransferData`. [`uds::DtcReport`]
+/// decodes `ReadDTCInformation` responses into [`uds::Dtc`]s shown in SAE J2012
+/// form and their [`uds::DtcStatus`] bits.
+#[cfg(feature = "std")]
+pub mod uds;
+
+/// Async `DoIP` transports on top of tokio, enabled by the `tokio` feature.
+///
+/// [`asynchronous::DoipClient`] shares one routing activated socket between any
+/// number of tasks, sending requests to different targets concurrently.
+/// [`asynchronous::DoipServer`] serves testers on TCP and UDP and passes their UDS
+/// requests to a [`asynchronous::DiagnosticHandler`]. The `tls` feature adds
+/// rustls connections on `DOIP_TLS_PORT` to both.
+#[cfg(feature = "tokio")]
+pub mod asynchronous;
+
 // endregion:      --- Modules
 
 // Python bindings (only available when python-bindings is enabled)
@@ -0,0 +1,367 @@
+use std::collections::BTreeMap;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::time::Instant;
+
+use crate::{
+    builder::DoipMessageBuilder,
+    definitions::{
+        DOIP_COMMON_EID_LEN, DOIP_COMMON_VIN_LEN, DOIP_DIAG_COMMON_SOURCE_LEN,
+        DOIP_VEHICLE_ANNOUNCEMENT_GID_LEN,
+    },
+    entity::{DoipEntity, EntityConfig, EntityEvent, SocketId, VehicleAnnouncer},
+    message::DoipMessage,
+    payload::{
+        ActionCode, DiagnosticAckCode, DiagnosticMessage, DiagnosticMessageAck,
+        DiagnosticMessageNack, DiagnosticNackCode, DoipPayload, NodeType, PowerMode,
+        VehicleAnnouncementMessage,
+    },
+};
+
+/// UDS negative response service identifier.
+const UDS_NEGATIVE_RESPONSE: u8 = 0x7F;
+
+/// UDS negative response code `serviceNotSupported`.
+const UDS_SERVICE_NOT_SUPPORTED: u8 = 0x11;
+
+/// An in-memory `DoIP` entity answering messages the way an ECU would, for use in
+/// tests.
+///
+/// The mock runs a [`DoipEntity`] with a single tester socket and a
+/// [`VehicleAnnouncer`]. Vehicle identification, entity status and power
+/// information requests are answered as datagrams, routing activation and alive
+/// checks as on a TCP socket. Every `DiagnosticMessage` addressed to the mock is
+/// acknowledged and answered with the scripted responses of its UDS request, or
+/// with the negative response `serviceNotSupported` when none was scripted.
+///
+/// ```rust
+/// use doip_definitions::builder::DoipMessageBuilder;
+/// use doip_definitions::mock::MockEntity;
+/// use doip_definitions::payload::{
+///     ActivationType, DiagnosticMessage, DoipPayload, RoutingActivationRequest,
+/// };
+///
+/// let mut ecu = MockEntity::new()
+///     .logical_address([0x10, 0x01])
+///     .response(&[0x22, 0xF1, 0x90], b"\x62\xF1\x90WVWZZZ1JZXW000001");
+///
+/// ecu.handle_message(
+///     DoipMessageBuilder::new()
+///         .payload(DoipPayload::RoutingActivationRequest(RoutingActivationRequest {
+///             source_address: [0x0E, 0x00],
+///             activation_type: ActivationType::Default,
+///             buffer: [0; 4],
+///             oem_specific: None,
+///         }))
+///         .build(),
+/// );
+///
+/// let replies = ecu.handle_message(
+///     DoipMessageBuilder::new()
+///         .payload(DoipPayload::DiagnosticMessage(DiagnosticMessage {
+///             source_address: [0x0E, 0x00],
+///             target_address: [0x10, 0x01],
+///             message: vec![0x22, 0xF1, 0x90],
+///         }))
+///         .build(),
+/// );
+///
+/// assert!(matches!(replies[0].payload, DoipPayload::DiagnosticMessageAck(_)));
+/// assert!(matches!(
+///     &replies[1].payload,
+///     DoipPayload::DiagnosticMessage(response) if response.message[0] == 0x62
+/// ));
+/// ```
+#[derive(Debug)]
+pub struct MockEntity {
+    config: EntityConfig,
+    announcement: VehicleAnnouncementMessage,
+    entity: DoipEntity,
+    announcer: VehicleAnnouncer,
+    socket: SocketId,
+    responses: BTreeMap<Vec<u8>, Vec<Vec<u8>>>,
+    requests: Vec<DiagnosticMessage>,
+}
+
+impl Default for MockEntity {
+    /// Constructs a `MockEntity` with:
+    /// - VIN `WVWZZZ1JZXW000001`
+    /// - EID and GID `00:00:00:00:00:01`
+    /// - Logical address `0x1000`
+    /// - Node type set to `DoipNode` and power mode set to `Ready`
+    /// - No scripted responses
+    fn default() -> Self {
+        let config = EntityConfig::default();
+        let announcement = VehicleAnnouncementMessage {
+            vin: *b"WVWZZZ1JZXW000001",
+            logical_address: config.logical_address,
+            eid: [0x00, 0x00, 0x00, 0x00, 0x00, 0x01],
+            gid: [0x00, 0x00, 0x00, 0x00, 0x00, 0x01],
+            further_action: ActionCode::NoFurtherActionRequired,
+            vin_gid_sync: None,
+        };
+
+        let mut entity = DoipEntity::new(config.clone());
+        let socket = entity.accept(Instant::now());
+
+        Self {
+            announcer: VehicleAnnouncer::new(announcement, config.timings),
+            config,
+            announcement,
+            entity,
+            socket,
+            responses: BTreeMap::new(),
+            requests: Vec::new(),
+        }
+    }
+}
+
+impl MockEntity {
+    /// Creates a new `MockEntity` with default identification.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the Vehicle Identification Number.
+    #[must_use]
+    pub fn vin(mut self, vin: [u8; DOIP_COMMON_VIN_LEN]) -> Self {
+        self.announcement.vin = vin;
+        self.restart()
+    }
+
+    /// Sets the Entity Identification.
+    #[must_use]
+    pub fn eid(mut self, eid: [u8; DOIP_COMMON_EID_LEN]) -> Self {
+        self.announcement.eid = eid;
+        self.restart()
+    }
+
+    /// Sets the Group Identification.
+    #[must_use]
+    pub fn gid(mut self, gid: [u8; DOIP_VEHICLE_ANNOUNCEMENT_GID_LEN]) -> Self {
+        self.announcement.gid = gid;
+        self.restart()
+    }
+
+    /// Sets the logical address diagnostic messages must be addressed to.
+    #[must_use]
+    pub fn logical_address(mut self, logical_address: [u8; DOIP_DIAG_COMMON_SOURCE_LEN]) -> Self {
+        self.config.logical_address = logical_address;
+        self.announcement.logical_address = logical_address;
+        self.restart()
+    }
+
+    /// Sets the node type reported in the `EntityStatusResponse`.
+    #[must_use]
+    pub fn node_type(mut self, node_type: NodeType) -> Self {
+        self.config.node_type = node_type;
+        self.restart()
+    }
+
+    /// Sets the power mode reported in the `PowerInformationResponse`.
+    #[must_use]
+    pub fn power_mode(mut self, power_mode: PowerMode) -> Self {
+        self.config.power_mode = power_mode;
+        self.restart()
+    }
+
+    /// Scripts `response` as the answer to the UDS `request`.
+    ///
+    /// Scripting several responses for the same request sends them in order,
+    /// e.g. a `requestCorrectlyReceived-ResponsePending` followed by the final
+    /// response.
+    #[must_use]
+    pub fn response(mut self, request: &[u8], response: &[u8]) -> Self {
+        self.responses
+            .entry(request.to_vec())
+            .or_default()
+            .push(response.to_vec());
+        self
+    }
+
+    /// Returns the `VehicleAnnouncementMessage` sent by the mock.
+    #[must_use]
+    pub fn announcement(&self) -> DoipMessage {
+        self.build(DoipPayload::VehicleAnnouncementMessage(self.announcement))
+    }
+
+    /// Returns every `DiagnosticMessage` the mock accepted, in order.
+    #[must_use]
+    pub fn requests(&self) -> &[DiagnosticMessage] {
+        &self.requests
+    }
+
+    /// Answers `message`, returning every message the entity sends in response.
+    ///
+    /// Vehicle identification requests are answered without waiting
+    /// `A_DoIP_Announce_Wait`. Once the entity closed the tester socket, e.g.
+    /// after a denied routing activation, the mock behaves as if the tester
+    /// connected again.
+    pub fn handle_message(&mut self, message: DoipMessage) -> Vec<DoipMessage> {
+        let now = Instant::now();
+        let origin = SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0));
+
+        if self.announcer.handle_message(now, 0, origin, &message) {
+            self.announcer
+                .handle_timeout(now + self.config.timings.announce_wait(0));
+            return self
+                .announcer
+                .poll_transmit()
+                .map(|(_, response)| response)
+                .into_iter()
+                .collect();
+        }
+
+        if let Some(response) = self.entity.handle_datagram(&message) {
+            return vec![response];
+        }
+
+        if self.entity.socket_state(self.socket).is_none() {
+            self.socket = self.entity.accept(now);
+        }
+
+        self.entity.handle_message(now, self.socket, message);
+
+        while let Some(event) = self.entity.poll_event() {
+            if let EntityEvent::DiagnosticMessage { socket, message } = event {
+                self.handle_diagnostic_message(socket, message);
+            }
+        }
+
+        let mut replies = Vec::new();
+        while let Some((_, reply)) = self.entity.poll_transmit() {
+            replies.push(reply);
+        }
+
+        replies
+    }
+
+    fn handle_diagnostic_message(&mut self, socket: SocketId, message: DiagnosticMessage) {
+        if message.target_address != self.config.logical_address {
+            self.entity.send(
+                socket,
+                DoipPayload::DiagnosticMessageNack(DiagnosticMessageNack {
+                    source_address: message.target_address,
+                    target_address: message.source_address,
+                    nack_code: DiagnosticNackCode::UnknownTargetAddress,
+                }),
+            );
+            return;
+        }
+
+        self.entity.send(
+            socket,
+            DoipPayload::DiagnosticMessageAck(DiagnosticMessageAck {
+                source_address: message.target_address,
+                target_address: message.source_address,
+                ack_code: DiagnosticAckCode::Acknowledged,
+                previous_message: message.message.clone(),
+            }),
+        );
+
+        let responses = if let Some(responses) = self.responses.get(&message.message) {
+            responses.clone()
+        } else {
+            let service = message.message.first().copied().unwrap_or_default();
+            vec![vec![
+                UDS_NEGATIVE_RESPONSE,
+                service,
+                UDS_SERVICE_NOT_SUPPORTED,
+            ]]
+        };
+
+        for response in responses {
+            self.entity.send(
+                socket,
+                DoipPayload::DiagnosticMessage(DiagnosticMessage {
+                    source_address: message.target_address,
+                    target_address: message.source_address,
+                    message: response,
+                }),
+            );
+        }
+
+        self.requests.push(message);
+    }
+
+    /// Recreates the entity and announcer after the identification changed.
+    fn restart(mut self) -> Self {
+        self.entity = DoipEntity::new(self.config.clone());
+        self.socket = self.entity.accept(Instant::now());
+        self.announcer = VehicleAnnouncer::new(self.announcement, self.config.timings);
+        self
+    }
+
+    fn build(&self, payload: DoipPayload) -> DoipMessage {
+        DoipMessageBuilder::new()
+            .protocol_version(self.config.protocol_version)
+            .payload(payload)
+            .build()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        builder::DoipMessageBuilder,
+        mock::MockEntity,
+        payload::{
+            ActivationType, DiagnosticMessage, DoipPayload, EntityStatusRequest, NodeType,
+            RoutingActivationRequest,
+        },
+    };
+
+    #[test]
+    fn test_mock_entity() {
+        let mut ecu = MockEntity::new()
+            .node_type(NodeType::DoipGateway)
+            .response(&[0x10, 0x03], &[0x7F, 0x10, 0x78])
+            .response(&[0x10, 0x03], &[0x50, 0x03, 0x00, 0x32, 0x01, 0xF4]);
+
+        let status = ecu.handle_message(
+            DoipMessageBuilder::new()
+                .payload(DoipPayload::EntityStatusRequest(EntityStatusRequest {}))
+                .build(),
+        );
+        assert!(matches!(
+            status[0].payload,
+            DoipPayload::EntityStatusResponse(response) if response.node_type == NodeType::DoipGateway
+        ));
+
+        let diagnostic = DoipMessageBuilder::new()
+            .payload(DoipPayload::DiagnosticMessage(DiagnosticMessage {
+                source_address: [0x0E, 0x00],
+                target_address: [0x10, 0x00],
+                message: vec![0x10, 0x03],
+            }))
+            .build();
+
+        // Diagnostic messages are rejected before routing activation.
+        let replies = ecu.handle_message(diagnostic.clone());
+        assert!(matches!(
+            replies[0].payload,
+            DoipPayload::DiagnosticMessageNack(_)
+        ));
+
+        ecu.handle_message(
+            DoipMessageBuilder::new()
+                .payload(DoipPayload::RoutingActivationRequest(
+                    RoutingActivationRequest {
+                        source_address: [0x0E, 0x00],
+                        activation_type: ActivationType::Default,
+                        buffer: [0; 4],
+                        oem_specific: None,
+                    },
+                ))
+                .build(),
+        );
+
+        let replies = ecu.handle_message(diagnostic);
+        assert_eq!(replies.len(), 3);
+        assert!(matches!(
+            &replies[1].payload,
+            DoipPayload::DiagnosticMessage(response) if response.message == [0x7F, 0x10, 0x78]
+        ));
+        assert_eq!(ecu.requests().len(), 1);
+    }
+}
@@ -0,0 +1,426 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use crate::{
+    definitions::DOIP_DIAG_COMMON_TARGET_LEN,
+    timing::ServerTimings,
+    uds::{
+        is_response_pending, NegativeResponseCode, ResetType, ServiceId, SessionType, UdsRequest,
+        UdsResponse,
+    },
+};
+
+/// Default time a server needs to restart after an `ECUReset` before its
+/// session is entered again.
+pub const RESET_DELAY: Duration = Duration::from_secs(1);
+
+/// A change of the session of a target, raised by a [`SessionManager`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionEvent {
+    /// The target entered a session and reported its timings
+    Entered {
+        /// The target
+        target_address: [u8; DOIP_DIAG_COMMON_TARGET_LEN],
+
+        /// The active session
+        session_type: SessionType,
+
+        /// The timings of the active session
+        timings: ServerTimings,
+    },
+
+    /// The target fell back to the default session after a reset or a lost
+    /// connection
+    Lost {
+        /// The target
+        target_address: [u8; DOIP_DIAG_COMMON_TARGET_LEN],
+    },
+
+    /// The target rejected a session change
+    Rejected {
+        /// The target
+        target_address: [u8; DOIP_DIAG_COMMON_TARGET_LEN],
+
+        /// Reason of the rejection
+        response_code: NegativeResponseCode,
+    },
+}
+
+/// The session of one target.
+#[derive(Debug, Clone, Copy)]
+struct TargetSession {
+    requested: SessionType,
+    active: SessionType,
+    timings: ServerTimings,
+    restore_at: Option<Instant>,
+}
+
+impl Default for TargetSession {
+    fn default() -> Self {
+        Self {
+            requested: SessionType::Default,
+            active: SessionType::Default,
+            timings: ServerTimings::default(),
+            restore_at: None,
+        }
+    }
+}
+
+/// Tracks the diagnostic session and server timings of each target and enters
+/// the session again after the target lost it.
+///
+/// The manager performs no IO itself. The final responses of physical targets
+/// are passed to [`SessionManager::handle_response`]. A positive
+/// `DiagnosticSessionControl` response sets the active session and its
+/// [`ServerTimings`] and raises [`SessionEvent::Entered`] with the timings to
+/// apply to later requests. A positive `ECUReset` response returns the target
+/// to the default session and raises [`SessionEvent::Lost`], the previous
+/// session is requested again once the reset delay expired. A lost connection
+/// is reported with [`SessionManager::handle_disconnect`] and every session is
+/// requested again on [`SessionManager::handle_connect`]. The requests are
+/// retrieved with [`SessionManager::poll_transmit`].
+///
+/// ```rust
+/// use std::time::{Duration, Instant};
+/// use doip_definitions::session::{SessionManager, RESET_DELAY};
+/// use doip_definitions::uds::SessionType;
+///
+/// let now = Instant::now();
+/// let mut sessions = SessionManager::new();
+///
+/// sessions.enter([0x10, 0x01], SessionType::Programming);
+/// assert_eq!(sessions.poll_transmit(), Some(([0x10, 0x01], vec![0x10, 0x02])));
+///
+/// sessions.handle_response(now, [0x10, 0x01], &[0x50, 0x02, 0x00, 0x19, 0x01, 0xF4]);
+/// assert_eq!(sessions.session([0x10, 0x01]), SessionType::Programming);
+/// assert_eq!(sessions.timings([0x10, 0x01]).p2_server_max, Duration::from_millis(25));
+///
+/// sessions.handle_response(now, [0x10, 0x01], &[0x51, 0x01]);
+/// assert_eq!(sessions.session([0x10, 0x01]), SessionType::Default);
+///
+/// sessions.handle_timeout(now + RESET_DELAY);
+/// assert_eq!(sessions.poll_transmit(), Some(([0x10, 0x01], vec![0x10, 0x02])));
+/// ```
+#[derive(Debug, Clone)]
+pub struct SessionManager {
+    reset_delay: Duration,
+    targets: BTreeMap<[u8; DOIP_DIAG_COMMON_TARGET_LEN], TargetSession>,
+    transmit: VecDeque<([u8; DOIP_DIAG_COMMON_TARGET_LEN], Vec<u8>)>,
+    events: VecDeque<SessionEvent>,
+}
+
+impl Default for SessionManager {
+    fn default() -> Self {
+        Self {
+            reset_delay: RESET_DELAY,
+            targets: BTreeMap::new(),
+            transmit: VecDeque::new(),
+            events: VecDeque::new(),
+        }
+    }
+}
+
+impl SessionManager {
+    /// Creates a new `SessionManager` waiting [`RESET_DELAY`] after a reset.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the time waited after a reset before the session is entered again.
+    #[must_use]
+    pub fn reset_delay(mut self, reset_delay: Duration) -> Self {
+        self.reset_delay = reset_delay;
+        self
+    }
+
+    /// Returns the active session of `target_address`.
+    #[must_use]
+    pub fn session(&self, target_address: [u8; DOIP_DIAG_COMMON_TARGET_LEN]) -> SessionType {
+        self.targets
+            .get(&target_address)
+            .map_or(SessionType::Default, |target| target.active)
+    }
+
+    /// Returns the timings of the active session of `target_address`.
+    #[must_use]
+    pub fn timings(&self, target_address: [u8; DOIP_DIAG_COMMON_TARGET_LEN]) -> ServerTimings {
+        self.targets
+            .get(&target_address)
+            .map_or_else(ServerTimings::default, |target| target.timings)
+    }
+
+    /// Requests `session_type` from `target_address`.
+    pub fn enter(
+        &mut self,
+        target_address: [u8; DOIP_DIAG_COMMON_TARGET_LEN],
+        session_type: SessionType,
+    ) {
+        let target = self.targets.entry(target_address).or_default();
+        target.requested = session_type;
+        target.restore_at = None;
+
+        self.request(target_address, session_type);
+    }
+
+    fn request(
+        &mut self,
+        target_address: [u8; DOIP_DIAG_COMMON_TARGET_LEN],
+        session_type: SessionType,
+    ) {
+        self.transmit.push_back((
+            target_address,
+            UdsRequest::DiagnosticSessionControl {
+                session_type,
+                suppress_positive_response: false,
+            }
+            .into(),
+        ));
+    }
+
+    /// Processes the final UDS `response` of `target_address` received at
+    /// `now`, ignoring every service other than `DiagnosticSessionControl` and
+    /// `ECUReset`.
+    pub fn handle_response(
+        &mut self,
+        now: Instant,
+        target_address: [u8; DOIP_DIAG_COMMON_TARGET_LEN],
+        response: &[u8],
+    ) {
+        if is_response_pending(response) {
+            return;
+        }
+
+        match UdsResponse::try_from(response) {
+            Ok(UdsResponse::DiagnosticSessionControl {
+                session_type,
+                p2_server_max,
+                p2_star_server_max,
+            }) => {
+                let timings = ServerTimings::from_response(p2_server_max, p2_star_server_max);
+
+                self.targets.insert(
+                    target_address,
+                    TargetSession {
+                        requested: session_type,
+                        active: session_type,
+                        timings,
+                        restore_at: None,
+                    },
+                );
+
+                self.events.push_back(SessionEvent::Entered {
+                    target_address,
+                    session_type,
+                    timings,
+                });
+            }
+            Ok(UdsResponse::EcuReset { reset_type, .. })
+                if !matches!(
+                    reset_type,
+                    ResetType::EnableRapidPowerShutDown | ResetType::DisableRapidPowerShutDown
+                ) =>
+            {
+                let restore_at = now + self.reset_delay;
+                self.fall_back(target_address);
+
+                if let Some(target) = self
+                    .targets
+                    .get_mut(&target_address)
+                    .filter(|target| target.requested != SessionType::Default)
+                {
+                    target.restore_at = Some(restore_at);
+                }
+            }
+            Ok(UdsResponse::Negative {
+                service_id,
+                response_code,
+            }) if service_id == u8::from(ServiceId::DiagnosticSessionControl) => {
+                self.events.push_back(SessionEvent::Rejected {
+                    target_address,
+                    response_code,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns `target_address` to the default session and its timings.
+    fn fall_back(&mut self, target_address: [u8; DOIP_DIAG_COMMON_TARGET_LEN]) {
+        let Some(target) = self.targets.get_mut(&target_address) else {
+            return;
+        };
+
+        if target.active != SessionType::Default || target.timings != ServerTimings::default() {
+            self.events.push_back(SessionEvent::Lost { target_address });
+        }
+
+        target.active = SessionType::Default;
+        target.timings = ServerTimings::default();
+        target.restore_at = None;
+    }
+
+    /// Returns every target to the default session after the connection was
+    /// lost.
+    pub fn handle_disconnect(&mut self) {
+        let targets: Vec<_> = self.targets.keys().copied().collect();
+
+        for target_address in targets {
+            self.fall_back(target_address);
+        }
+    }
+
+    /// Requests the previous session of every target once the connection was
+    /// established again.
+    pub fn handle_connect(&mut self, _now: Instant) {
+        let restore: Vec<_> = self
+            .targets
+            .iter()
+            .filter(|(_, target)| target.requested != SessionType::Default)
+            .map(|(target_address, target)| (*target_address, target.requested))
+            .collect();
+
+        for (target_address, session_type) in restore {
+            self.request(target_address, session_type);
+        }
+    }
+
+    /// Requests the previous session of every target whose reset delay expired
+    /// at or before `now`.
+    pub fn handle_timeout(&mut self, now: Instant) {
+        let restore: Vec<_> = self
+            .targets
+            .iter_mut()
+            .filter(|(_, target)| target.restore_at.is_some_and(|deadline| deadline <= now))
+            .map(|(target_address, target)| {
+                target.restore_at = None;
+                (*target_address, target.requested)
+            })
+            .collect();
+
+        for (target_address, session_type) in restore {
+            self.request(target_address, session_type);
+        }
+    }
+
+    /// Returns the next instant at which [`SessionManager::handle_timeout`]
+    /// must be called.
+    #[must_use]
+    pub fn poll_timeout(&self) -> Option<Instant> {
+        self.targets
+            .values()
+            .filter_map(|target| target.restore_at)
+            .min()
+    }
+
+    /// Returns the target address and UDS message of the next
+    /// `DiagnosticSessionControl` request.
+    pub fn poll_transmit(&mut self) -> Option<([u8; DOIP_DIAG_COMMON_TARGET_LEN], Vec<u8>)> {
+        self.transmit.pop_front()
+    }
+
+    /// Returns the next session change for the application.
+    pub fn poll_event(&mut self) -> Option<SessionEvent> {
+        self.events.pop_front()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, Instant};
+
+    use crate::{
+        session::{SessionEvent, SessionManager},
+        timing::ServerTimings,
+        uds::{NegativeResponseCode, SessionType},
+    };
+
+    const TARGET: [u8; 2] = [0x10, 0x01];
+
+    #[test]
+    fn test_reenter_after_reset() {
+        let now = Instant::now();
+        let delay = Duration::from_millis(500);
+        let mut sessions = SessionManager::new().reset_delay(delay);
+
+        sessions.enter(TARGET, SessionType::ExtendedDiagnostic);
+        assert_eq!(sessions.poll_transmit(), Some((TARGET, vec![0x10, 0x03])));
+
+        sessions.handle_response(now, TARGET, &[0x7F, 0x10, 0x78]);
+        assert_eq!(sessions.poll_event(), None);
+
+        sessions.handle_response(now, TARGET, &[0x50, 0x03, 0x00, 0x64, 0x00, 0xC8]);
+        let timings = ServerTimings {
+            p2_server_max: Duration::from_millis(100),
+            p2_star_server_max: Duration::from_secs(2),
+        };
+        assert_eq!(
+            sessions.poll_event(),
+            Some(SessionEvent::Entered {
+                target_address: TARGET,
+                session_type: SessionType::ExtendedDiagnostic,
+                timings,
+            })
+        );
+        assert_eq!(sessions.timings(TARGET), timings);
+
+        sessions.handle_response(now, TARGET, &[0x51, 0x04, 0x0A]);
+        assert_eq!(sessions.poll_event(), None);
+        assert_eq!(sessions.poll_timeout(), None);
+
+        sessions.handle_response(now, TARGET, &[0x51, 0x01]);
+        assert_eq!(
+            sessions.poll_event(),
+            Some(SessionEvent::Lost {
+                target_address: TARGET
+            })
+        );
+        assert_eq!(sessions.session(TARGET), SessionType::Default);
+        assert_eq!(sessions.timings(TARGET), ServerTimings::default());
+        assert_eq!(sessions.poll_timeout(), Some(now + delay));
+
+        sessions.handle_timeout(now);
+        assert_eq!(sessions.poll_transmit(), None);
+        sessions.handle_timeout(now + delay);
+        assert_eq!(sessions.poll_transmit(), Some((TARGET, vec![0x10, 0x03])));
+        assert_eq!(sessions.poll_timeout(), None);
+
+        sessions.handle_response(now + delay, TARGET, &[0x7F, 0x10, 0x22]);
+        assert_eq!(
+            sessions.poll_event(),
+            Some(SessionEvent::Rejected {
+                target_address: TARGET,
+                response_code: NegativeResponseCode::ConditionsNotCorrect,
+            })
+        );
+    }
+
+    #[test]
+    fn test_reenter_after_reconnect() {
+        let now = Instant::now();
+        let mut sessions = SessionManager::new();
+
+        sessions.handle_response(now, TARGET, &[0x50, 0x02, 0x00, 0x32, 0x01, 0xF4]);
+        sessions.handle_response(now, [0x10, 0x02], &[0x50, 0x01, 0x00, 0x32, 0x01, 0xF4]);
+        while sessions.poll_event().is_some() {}
+
+        sessions.handle_disconnect();
+        assert_eq!(
+            sessions.poll_event(),
+            Some(SessionEvent::Lost {
+                target_address: TARGET
+            })
+        );
+        assert_eq!(sessions.poll_event(), None);
+
+        sessions.handle_connect(now);
+        assert_eq!(sessions.poll_transmit(), Some((TARGET, vec![0x10, 0x02])));
+        assert_eq!(sessions.poll_transmit(), None);
+
+        sessions.enter(TARGET, SessionType::Default);
+        sessions.handle_response(now, TARGET, &[0x50, 0x01, 0x00, 0x32, 0x01, 0xF4]);
+        sessions.handle_connect(now);
+        assert_eq!(sessions.poll_transmit(), Some((TARGET, vec![0x10, 0x01])));
+        assert_eq!(sessions.poll_transmit(), None);
+    }
+}
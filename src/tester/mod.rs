@@ -0,0 +1,630 @@
+use std::collections::VecDeque;
+use std::time::Instant;
+
+use crate::{
+    builder::DoipMessageBuilder,
+    definitions::{
+        DOIP_DIAG_COMMON_SOURCE_LEN, DOIP_DIAG_COMMON_TARGET_LEN,
+        DOIP_ROUTING_ACTIVATION_REQ_ISO_LEN, DOIP_ROUTING_ACTIVATION_REQ_OEM_LEN,
+    },
+    error::{Error, Result},
+    header::ProtocolVersion,
+    message::DoipMessage,
+    payload::{
+        ActivationCode, ActivationType, AliveCheckResponse, DiagnosticMessage, DiagnosticNackCode,
+        DoipPayload, NackCode, RoutingActivationRequest,
+    },
+    timing::DoipTimings,
+};
+
+/// Configuration of a [`TesterConnection`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TesterConfig {
+    /// Logical address of the tester, used as the source of every request
+    pub source_address: [u8; DOIP_DIAG_COMMON_SOURCE_LEN],
+
+    /// Activation type sent in the `RoutingActivationRequest`
+    pub activation_type: ActivationType,
+
+    /// OEM specific bytes sent in the `RoutingActivationRequest`, e.g. for
+    /// authentication
+    pub oem_specific: Option<[u8; DOIP_ROUTING_ACTIVATION_REQ_OEM_LEN]>,
+
+    /// Number of times the `RoutingActivationRequest` is repeated, every
+    /// `A_DoIP_Ctrl`, while the entity awaits a confirmation
+    pub confirmation_retries: u8,
+
+    /// Protocol version used in the header of each sent message
+    pub protocol_version: ProtocolVersion,
+
+    /// Timing parameters applied to routing activation and diagnostic messages
+    pub timings: DoipTimings,
+}
+
+impl Default for TesterConfig {
+    /// Constructs a `TesterConfig` with:
+    /// - Source address `0x0E00`, the first external test equipment address
+    /// - Activation type set to `Default` without OEM specific bytes
+    /// - 5 repetitions of the request while a confirmation is pending
+    /// - Protocol version set to `Iso13400_2012`
+    /// - ISO 13400-2 default timings
+    fn default() -> Self {
+        Self {
+            source_address: [0x0E, 0x00],
+            activation_type: ActivationType::Default,
+            oem_specific: None,
+            confirmation_retries: 5,
+            protocol_version: ProtocolVersion::Iso13400_2012,
+            timings: DoipTimings::default(),
+        }
+    }
+}
+
+/// The lifecycle of a [`TesterConnection`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TesterState {
+    /// No TCP socket has been established
+    Disconnected,
+
+    /// The socket is established, the `RoutingActivationRequest` has been sent
+    /// and the response is outstanding
+    ActivationPending,
+
+    /// The entity answered with `ActivatedConfirmationRequired` and awaits a
+    /// confirmation before routing is activated
+    ConfirmationPending,
+
+    /// The entity answered with `DeniedMissingAuthentication`, the socket stays
+    /// open until routing activation is requested again with authentication data
+    AuthenticationRequired,
+
+    /// Routing is active, diagnostic messages may be sent
+    RoutingActive,
+}
+
+/// Notifications raised by a [`TesterConnection`] for the application.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TesterEvent {
+    /// Routing was successfully activated by the entity
+    RoutingActivated {
+        /// Logical address of the `DoIP` entity
+        entity_address: [u8; DOIP_DIAG_COMMON_SOURCE_LEN],
+    },
+
+    /// The entity requires a confirmation before activating routing
+    ConfirmationRequired {
+        /// Logical address of the `DoIP` entity
+        entity_address: [u8; DOIP_DIAG_COMMON_SOURCE_LEN],
+    },
+
+    /// The entity requires authentication before activating routing, see
+    /// [`TesterConnection::request_routing_activation`]
+    AuthenticationRequired {
+        /// Logical address of the `DoIP` entity
+        entity_address: [u8; DOIP_DIAG_COMMON_SOURCE_LEN],
+    },
+
+    /// Routing activation was denied by the entity
+    RoutingActivationDenied {
+        /// The denial code sent by the entity
+        activation_code: ActivationCode,
+    },
+
+    /// No `RoutingActivationResponse` was received within `A_DoIP_Ctrl`
+    RoutingActivationTimeout,
+
+    /// A sent `DiagnosticMessage` was positively acknowledged
+    DiagnosticAck {
+        /// Target address of the acknowledged message
+        target_address: [u8; DOIP_DIAG_COMMON_TARGET_LEN],
+
+        /// Echo of the acknowledged message, may be empty
+        previous_message: Vec<u8>,
+    },
+
+    /// A sent `DiagnosticMessage` was negatively acknowledged
+    DiagnosticNack {
+        /// Target address of the rejected message
+        target_address: [u8; DOIP_DIAG_COMMON_TARGET_LEN],
+
+        /// The negative acknowledgement code
+        nack_code: DiagnosticNackCode,
+    },
+
+    /// A sent `DiagnosticMessage` was not acknowledged within
+    /// `A_DoIP_Diagnostic_Message`
+    DiagnosticAckTimeout {
+        /// Target address of the unacknowledged message
+        target_address: [u8; DOIP_DIAG_COMMON_TARGET_LEN],
+    },
+
+    /// A `DiagnosticMessage` was received from the entity
+    DiagnosticMessage(DiagnosticMessage),
+
+    /// The entity rejected a message with a `GenericNack`
+    GenericNack {
+        /// The negative acknowledgement code
+        nack_code: NackCode,
+    },
+
+    /// A message was received which the connection does not process
+    Unhandled(DoipMessage),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct PendingDiagnostic {
+    target_address: [u8; DOIP_DIAG_COMMON_TARGET_LEN],
+    deadline: Instant,
+}
+
+/// Transport agnostic state machine of a tester's TCP connection to a `DoIP` entity.
+///
+/// The connection performs no IO itself. Received messages are passed in with
+/// [`TesterConnection::handle_message`] and expired timers with
+/// [`TesterConnection::handle_timeout`]. Messages which need to be written to the
+/// socket are retrieved with [`TesterConnection::poll_transmit`] and events for the
+/// application with [`TesterConnection::poll_event`].
+///
+/// ```rust
+/// use std::time::Instant;
+/// use doip_definitions::payload::DoipPayload;
+/// use doip_definitions::tester::{TesterConfig, TesterConnection, TesterState};
+///
+/// let mut connection = TesterConnection::new(TesterConfig::default());
+/// connection.connect(Instant::now());
+///
+/// let request = connection.poll_transmit().unwrap();
+/// assert!(matches!(request.payload, DoipPayload::RoutingActivationRequest(_)));
+/// assert_eq!(connection.state(), TesterState::ActivationPending);
+/// ```
+#[derive(Debug)]
+pub struct TesterConnection {
+    config: TesterConfig,
+    state: TesterState,
+    entity_address: Option<[u8; DOIP_DIAG_COMMON_SOURCE_LEN]>,
+    activation_deadline: Option<Instant>,
+    confirmation_retries: u8,
+    pending: VecDeque<PendingDiagnostic>,
+    transmit: VecDeque<DoipMessage>,
+    events: VecDeque<TesterEvent>,
+}
+
+impl TesterConnection {
+    /// Creates a new `TesterConnection` in the `Disconnected` state.
+    #[must_use]
+    pub fn new(config: TesterConfig) -> Self {
+        Self {
+            config,
+            state: TesterState::Disconnected,
+            entity_address: None,
+            activation_deadline: None,
+            confirmation_retries: 0,
+            pending: VecDeque::new(),
+            transmit: VecDeque::new(),
+            events: VecDeque::new(),
+        }
+    }
+
+    /// Returns the configuration of the connection.
+    #[must_use]
+    pub fn config(&self) -> &TesterConfig {
+        &self.config
+    }
+
+    /// Returns the current state of the connection.
+    #[must_use]
+    pub fn state(&self) -> TesterState {
+        self.state
+    }
+
+    /// Returns the logical address of the entity once it has responded to the
+    /// routing activation.
+    #[must_use]
+    pub fn entity_address(&self) -> Option<[u8; DOIP_DIAG_COMMON_SOURCE_LEN]> {
+        self.entity_address
+    }
+
+    /// Signals that the TCP socket has been established and queues the
+    /// `RoutingActivationRequest`.
+    pub fn connect(&mut self, now: Instant) {
+        self.reset();
+        self.send_routing_activation(now);
+        self.state = TesterState::ActivationPending;
+    }
+
+    /// Requests routing activation again on the established socket, replacing
+    /// the OEM specific bytes of the configuration with `oem_specific`.
+    ///
+    /// Used to send authentication data after `DeniedMissingAuthentication`, or
+    /// to repeat the request while a confirmation is pending.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::NotConnected`] when the connection is `Disconnected`.
+    pub fn request_routing_activation(
+        &mut self,
+        now: Instant,
+        oem_specific: Option<[u8; DOIP_ROUTING_ACTIVATION_REQ_OEM_LEN]>,
+    ) -> Result<()> {
+        if self.state == TesterState::Disconnected {
+            return Err(Error::NotConnected);
+        }
+
+        self.config.oem_specific = oem_specific;
+        self.send_routing_activation(now);
+
+        if self.state != TesterState::ConfirmationPending {
+            self.state = TesterState::ActivationPending;
+        }
+
+        Ok(())
+    }
+
+    /// Signals that the TCP socket was closed, outstanding messages and timers
+    /// are discarded.
+    pub fn disconnect(&mut self) {
+        self.reset();
+    }
+
+    /// Queues a `DiagnosticMessage` to `target_address` and starts the
+    /// `A_DoIP_Diagnostic_Message` timer for its acknowledgement.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::RoutingNotActive`] when routing has not been activated.
+    pub fn send_diagnostic(
+        &mut self,
+        now: Instant,
+        target_address: [u8; DOIP_DIAG_COMMON_TARGET_LEN],
+        message: &[u8],
+    ) -> Result<()> {
+        if self.state != TesterState::RoutingActive {
+            return Err(Error::RoutingNotActive);
+        }
+
+        self.queue(DoipPayload::DiagnosticMessage(DiagnosticMessage {
+            source_address: self.config.source_address,
+            target_address,
+            message: message.to_vec(),
+        }));
+
+        self.pending.push_back(PendingDiagnostic {
+            target_address,
+            deadline: now + self.config.timings.diagnostic_message,
+        });
+
+        Ok(())
+    }
+
+    /// Processes a message received from the entity.
+    pub fn handle_message(&mut self, now: Instant, message: DoipMessage) {
+        match message.payload {
+            DoipPayload::RoutingActivationResponse(response) => {
+                if !matches!(
+                    self.state,
+                    TesterState::ActivationPending
+                        | TesterState::ConfirmationPending
+                        | TesterState::AuthenticationRequired
+                ) {
+                    self.events.push_back(TesterEvent::Unhandled(DoipMessage {
+                        header: message.header,
+                        payload: DoipPayload::RoutingActivationResponse(response),
+                    }));
+                    return;
+                }
+
+                self.entity_address = Some(response.source_address);
+                self.handle_activation_code(now, response.source_address, response.activation_code);
+            }
+            DoipPayload::AliveCheckRequest(_) => {
+                self.queue(DoipPayload::AliveCheckResponse(AliveCheckResponse {
+                    source_address: self.config.source_address,
+                }));
+            }
+            DoipPayload::DiagnosticMessageAck(ack) => {
+                self.take_pending(ack.source_address);
+                self.events.push_back(TesterEvent::DiagnosticAck {
+                    target_address: ack.source_address,
+                    previous_message: ack.previous_message,
+                });
+            }
+            DoipPayload::DiagnosticMessageNack(nack) => {
+                self.take_pending(nack.source_address);
+                self.events.push_back(TesterEvent::DiagnosticNack {
+                    target_address: nack.source_address,
+                    nack_code: nack.nack_code,
+                });
+            }
+            DoipPayload::DiagnosticMessage(diagnostic) => {
+                self.events
+                    .push_back(TesterEvent::DiagnosticMessage(diagnostic));
+            }
+            DoipPayload::GenericNack(nack) => {
+                self.events.push_back(TesterEvent::GenericNack {
+                    nack_code: nack.nack_code,
+                });
+            }
+            payload => self.events.push_back(TesterEvent::Unhandled(DoipMessage {
+                header: message.header,
+                payload,
+            })),
+        }
+    }
+
+    /// Processes every timer which expired at or before `now`.
+    pub fn handle_timeout(&mut self, now: Instant) {
+        if self
+            .activation_deadline
+            .is_some_and(|deadline| deadline <= now)
+        {
+            if self.state == TesterState::ConfirmationPending && self.confirmation_retries > 0 {
+                self.confirmation_retries -= 1;
+                self.send_routing_activation(now);
+            } else {
+                self.activation_deadline = None;
+                self.state = TesterState::Disconnected;
+                self.events.push_back(TesterEvent::RoutingActivationTimeout);
+            }
+        }
+
+        while let Some(pending) = self.pending.front() {
+            if pending.deadline > now {
+                break;
+            }
+
+            let target_address = pending.target_address;
+            self.pending.pop_front();
+            self.events
+                .push_back(TesterEvent::DiagnosticAckTimeout { target_address });
+        }
+    }
+
+    /// Returns the next instant at which [`TesterConnection::handle_timeout`]
+    /// must be called.
+    #[must_use]
+    pub fn poll_timeout(&self) -> Option<Instant> {
+        let pending = self.pending.iter().map(|pending| pending.deadline).min();
+
+        match (self.activation_deadline, pending) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+
+    /// Returns the next message to be written to the socket.
+    pub fn poll_transmit(&mut self) -> Option<DoipMessage> {
+        self.transmit.pop_front()
+    }
+
+    /// Returns the next event for the application.
+    pub fn poll_event(&mut self) -> Option<TesterEvent> {
+        self.events.pop_front()
+    }
+
+    fn handle_activation_code(
+        &mut self,
+        now: Instant,
+        entity_address: [u8; DOIP_DIAG_COMMON_SOURCE_LEN],
+        activation_code: ActivationCode,
+    ) {
+        match activation_code {
+            ActivationCode::SuccessfullyActivated => {
+                self.activation_deadline = None;
+                self.state = TesterState::RoutingActive;
+                self.events
+                    .push_back(TesterEvent::RoutingActivated { entity_address });
+            }
+            ActivationCode::ActivatedConfirmationRequired => {
+                self.activation_deadline = Some(now + self.config.timings.ctrl);
+
+                if self.state != TesterState::ConfirmationPending {
+                    self.confirmation_retries = self.config.confirmation_retries;
+                    self.state = TesterState::ConfirmationPending;
+                    self.events
+                        .push_back(TesterEvent::ConfirmationRequired { entity_address });
+                }
+            }
+            ActivationCode::DeniedMissingAuthentication => {
+                self.activation_deadline = None;
+                self.state = TesterState::AuthenticationRequired;
+                self.events
+                    .push_back(TesterEvent::AuthenticationRequired { entity_address });
+            }
+            activation_code => {
+                self.activation_deadline = None;
+                self.state = TesterState::Disconnected;
+                self.events
+                    .push_back(TesterEvent::RoutingActivationDenied { activation_code });
+            }
+        }
+    }
+
+    fn send_routing_activation(&mut self, now: Instant) {
+        self.queue(DoipPayload::RoutingActivationRequest(
+            RoutingActivationRequest {
+                source_address: self.config.source_address,
+                activation_type: self.config.activation_type,
+                buffer: [0; DOIP_ROUTING_ACTIVATION_REQ_ISO_LEN],
+                oem_specific: self.config.oem_specific,
+            },
+        ));
+
+        self.activation_deadline = Some(now + self.config.timings.ctrl);
+    }
+
+    fn take_pending(&mut self, target_address: [u8; DOIP_DIAG_COMMON_TARGET_LEN]) {
+        if let Some(index) = self
+            .pending
+            .iter()
+            .position(|pending| pending.target_address == target_address)
+        {
+            self.pending.remove(index);
+        }
+    }
+
+    fn queue(&mut self, payload: DoipPayload) {
+        self.transmit.push_back(
+            DoipMessageBuilder::new()
+                .protocol_version(self.config.protocol_version)
+                .payload(payload)
+                .build(),
+        );
+    }
+
+    fn reset(&mut self) {
+        self.state = TesterState::Disconnected;
+        self.entity_address = None;
+        self.activation_deadline = None;
+        self.pending.clear();
+        self.transmit.clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, Instant};
+
+    use crate::{
+        builder::DoipMessageBuilder,
+        header::ProtocolVersion,
+        payload::{
+            ActivationCode, AliveCheckRequest, DiagnosticAckCode, DiagnosticMessageAck,
+            DoipPayload, RoutingActivationResponse,
+        },
+        tester::{TesterConfig, TesterConnection, TesterEvent, TesterState},
+    };
+
+    fn activation_response(activation_code: ActivationCode) -> crate::message::DoipMessage {
+        DoipMessageBuilder::new()
+            .protocol_version(ProtocolVersion::Iso13400_2012)
+            .payload(DoipPayload::RoutingActivationResponse(
+                RoutingActivationResponse {
+                    logical_address: [0x0E, 0x00],
+                    source_address: [0x10, 0x00],
+                    activation_code,
+                    buffer: [0; 4],
+                    oem_specific: None,
+                },
+            ))
+            .build()
+    }
+
+    #[test]
+    fn test_routing_activation_and_diagnostic_ack() {
+        let now = Instant::now();
+        let mut connection = TesterConnection::new(TesterConfig::default());
+
+        connection.connect(now);
+        assert!(connection.poll_transmit().is_some());
+        assert!(connection
+            .send_diagnostic(now, [0x10, 0x00], &[0x3E, 0x00])
+            .is_err());
+
+        connection.handle_message(
+            now,
+            activation_response(ActivationCode::SuccessfullyActivated),
+        );
+        assert_eq!(connection.state(), TesterState::RoutingActive);
+        assert_eq!(
+            connection.poll_event(),
+            Some(TesterEvent::RoutingActivated {
+                entity_address: [0x10, 0x00]
+            })
+        );
+
+        connection
+            .send_diagnostic(now, [0x10, 0x00], &[0x3E, 0x00])
+            .unwrap();
+        assert!(connection.poll_transmit().is_some());
+
+        connection.handle_message(
+            now,
+            DoipMessageBuilder::new()
+                .payload(DoipPayload::DiagnosticMessageAck(DiagnosticMessageAck {
+                    source_address: [0x10, 0x00],
+                    target_address: [0x0E, 0x00],
+                    ack_code: DiagnosticAckCode::Acknowledged,
+                    previous_message: vec![],
+                }))
+                .build(),
+        );
+
+        assert!(matches!(
+            connection.poll_event(),
+            Some(TesterEvent::DiagnosticAck { .. })
+        ));
+        assert_eq!(connection.poll_timeout(), None);
+    }
+
+    #[test]
+    fn test_alive_check_and_timeouts() {
+        let now = Instant::now();
+        let mut connection = TesterConnection::new(TesterConfig::default());
+
+        connection.connect(now);
+        connection.poll_transmit();
+
+        connection.handle_message(
+            now,
+            DoipMessageBuilder::new()
+                .payload(DoipPayload::AliveCheckRequest(AliveCheckRequest {}))
+                .build(),
+        );
+        assert!(matches!(
+            connection.poll_transmit().map(|message| message.payload),
+            Some(DoipPayload::AliveCheckResponse(_))
+        ));
+
+        connection.handle_timeout(now + Duration::from_secs(3));
+        assert_eq!(
+            connection.poll_event(),
+            Some(TesterEvent::RoutingActivationTimeout)
+        );
+        assert_eq!(connection.state(), TesterState::Disconnected);
+    }
+
+    #[test]
+    fn test_authentication_and_confirmation_retries() {
+        let now = Instant::now();
+        let mut connection = TesterConnection::new(TesterConfig {
+            confirmation_retries: 1,
+            ..TesterConfig::default()
+        });
+
+        assert!(connection.request_routing_activation(now, None).is_err());
+
+        connection.connect(now);
+        connection.poll_transmit();
+        connection.handle_message(
+            now,
+            activation_response(ActivationCode::DeniedMissingAuthentication),
+        );
+        assert_eq!(connection.state(), TesterState::AuthenticationRequired);
+        assert_eq!(connection.poll_timeout(), None);
+
+        connection
+            .request_routing_activation(now, Some([0xCA, 0xFE, 0x00, 0x00]))
+            .unwrap();
+        let request = connection.poll_transmit().unwrap();
+        assert!(matches!(
+            request.payload,
+            DoipPayload::RoutingActivationRequest(request)
+                if request.oem_specific == Some([0xCA, 0xFE, 0x00, 0x00])
+        ));
+
+        connection.handle_message(
+            now,
+            activation_response(ActivationCode::ActivatedConfirmationRequired),
+        );
+        assert_eq!(connection.state(), TesterState::ConfirmationPending);
+
+        // The request is repeated once, then the activation times out.
+        connection.handle_timeout(now + Duration::from_secs(2));
+        assert!(connection.poll_transmit().is_some());
+        assert_eq!(connection.state(), TesterState::ConfirmationPending);
+
+        connection.handle_timeout(now + Duration::from_secs(4));
+        assert!(connection.poll_transmit().is_none());
+        assert_eq!(connection.state(), TesterState::Disconnected);
+    }
+}
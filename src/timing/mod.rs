@@ -0,0 +1,206 @@
+use core::time::Duration;
+
+// Control //
+/// `A_DoIP_Ctrl`: Maximum time a `DoIP` entity waits for a response to a UDP
+/// control request (vehicle identification, entity status, power information).
+pub const A_DOIP_CTRL: Duration = Duration::from_secs(2);
+
+// Vehicle announcement //
+/// `A_DoIP_Announce_Wait`: Lower bound of the random delay before the first
+/// vehicle announcement or identification response is sent.
+pub const A_DOIP_ANNOUNCE_WAIT_MIN: Duration = Duration::from_millis(0);
+/// `A_DoIP_Announce_Wait`: Upper bound of the random delay before the first
+/// vehicle announcement or identification response is sent.
+pub const A_DOIP_ANNOUNCE_WAIT_MAX: Duration = Duration::from_millis(500);
+/// `A_DoIP_Announce_Interval`: Time between two consecutive vehicle
+/// announcement messages.
+pub const A_DOIP_ANNOUNCE_INTERVAL: Duration = Duration::from_millis(500);
+/// `A_DoIP_Announce_Num`: Number of vehicle announcement messages sent after
+/// startup or a change of IP address.
+pub const A_DOIP_ANNOUNCE_NUM: u8 = 3;
+
+// Diagnostic message //
+/// `A_DoIP_Diagnostic_Message`: Maximum time a tester waits for the
+/// `DiagnosticMessageAck` or `DiagnosticMessageNack` of a `DiagnosticMessage`.
+pub const A_DOIP_DIAGNOSTIC_MESSAGE: Duration = Duration::from_secs(2);
+
+// TCP //
+/// `T_TCP_General_Inactivity`: Time after which an idle, routing activated TCP
+/// socket is closed by the `DoIP` entity.
+#[allow(clippy::duration_suboptimal_units)] // `Duration::from_mins` needs Rust 1.91
+pub const T_TCP_GENERAL_INACTIVITY: Duration = Duration::from_secs(300);
+/// `T_TCP_Initial_Inactivity`: Time after which a TCP socket without a routing
+/// activation request is closed by the `DoIP` entity.
+pub const T_TCP_INITIAL_INACTIVITY: Duration = Duration::from_secs(2);
+/// `T_TCP_Alive_Check`: Time a `DoIP` entity waits for an `AliveCheckResponse`.
+pub const T_TCP_ALIVE_CHECK: Duration = Duration::from_millis(500);
+
+// Processing //
+/// `A_Processing_Time`: Maximum time between receiving a request and sending
+/// the corresponding response.
+pub const A_PROCESSING_TIME: Duration = Duration::from_secs(2);
+
+// UDS //
+/// `P2Server_max`: Default time a server takes to start its response to a
+/// request, as defined in ISO 14229-2.
+pub const P2_SERVER_MAX: Duration = Duration::from_millis(50);
+/// `P2*Server_max`: Default time a tester keeps waiting for the final response
+/// after the server answered with `requestCorrectlyReceived-ResponsePending`,
+/// as defined in ISO 14229-2.
+pub const P2_STAR_SERVER_MAX: Duration = Duration::from_secs(5);
+/// `S3Client`: Time between the `TesterPresent` requests a tester sends to keep
+/// a non-default session alive, as defined in ISO 14229-2.
+pub const S3_CLIENT: Duration = Duration::from_secs(2);
+/// `ΔP2`: Margin a tester adds to `P2Server_max` and `P2*Server_max` for the
+/// network delay. ISO 14229-2 leaves the value to the system, 50 ms suit a
+/// direct Ethernet connection.
+pub const DELTA_P2: Duration = Duration::from_millis(50);
+
+// Discovery //
+/// `A_Vehicle_Discovery_Timer`: Time a tester collects vehicle announcement
+/// messages after sending a vehicle identification request.
+pub const A_VEHICLE_DISCOVERY_TIMER: Duration = Duration::from_secs(5);
+
+/// The `P2Server_max` and `P2*Server_max` of the active session of a server,
+/// as reported by its `DiagnosticSessionControl` response.
+///
+/// ```rust
+/// use core::time::Duration;
+/// use doip_definitions::timing::ServerTimings;
+///
+/// let timings = ServerTimings::from_response(0x0032, 0x01F4);
+///
+/// assert_eq!(timings.p2_server_max, Duration::from_millis(50));
+/// assert_eq!(timings.p2_star_server_max, Duration::from_secs(5));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ServerTimings {
+    /// `P2Server_max`
+    pub p2_server_max: Duration,
+
+    /// `P2*Server_max`
+    pub p2_star_server_max: Duration,
+}
+
+impl Default for ServerTimings {
+    /// Constructs `ServerTimings` with the default values defined in ISO 14229-2.
+    fn default() -> Self {
+        Self {
+            p2_server_max: P2_SERVER_MAX,
+            p2_star_server_max: P2_STAR_SERVER_MAX,
+        }
+    }
+}
+
+impl ServerTimings {
+    /// Converts the timings of a `DiagnosticSessionControl` response,
+    /// `p2_server_max` in 1 ms and `p2_star_server_max` in 10 ms resolution.
+    #[must_use]
+    pub fn from_response(p2_server_max: u16, p2_star_server_max: u16) -> Self {
+        Self {
+            p2_server_max: Duration::from_millis(u64::from(p2_server_max)),
+            p2_star_server_max: Duration::from_millis(u64::from(p2_star_server_max) * 10),
+        }
+    }
+}
+
+/// Configurable set of ISO 13400-2 timing parameters.
+///
+/// Defaults to the values given by the standard, individual fields can be
+/// overridden where an OEM or test setup requires different values.
+///
+/// ```rust
+/// use core::time::Duration;
+/// use doip_definitions::timing::DoipTimings;
+///
+/// let timings = DoipTimings {
+///     tcp_general_inactivity: Duration::from_secs(60),
+///     ..DoipTimings::default()
+/// };
+///
+/// assert_eq!(timings.tcp_alive_check, Duration::from_millis(500));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DoipTimings {
+    /// `A_DoIP_Ctrl`
+    pub ctrl: Duration,
+
+    /// Lower bound of `A_DoIP_Announce_Wait`
+    pub announce_wait_min: Duration,
+
+    /// Upper bound of `A_DoIP_Announce_Wait`
+    pub announce_wait_max: Duration,
+
+    /// `A_DoIP_Announce_Interval`
+    pub announce_interval: Duration,
+
+    /// `A_DoIP_Announce_Num`
+    pub announce_num: u8,
+
+    /// `A_DoIP_Diagnostic_Message`
+    pub diagnostic_message: Duration,
+
+    /// `T_TCP_General_Inactivity`
+    pub tcp_general_inactivity: Duration,
+
+    /// `T_TCP_Initial_Inactivity`
+    pub tcp_initial_inactivity: Duration,
+
+    /// `T_TCP_Alive_Check`
+    pub tcp_alive_check: Duration,
+
+    /// `A_Processing_Time`
+    pub processing_time: Duration,
+
+    /// `A_Vehicle_Discovery_Timer`
+    pub vehicle_discovery_timer: Duration,
+}
+
+impl Default for DoipTimings {
+    /// Constructs `DoipTimings` with the values defined in ISO 13400-2.
+    fn default() -> Self {
+        Self {
+            ctrl: A_DOIP_CTRL,
+            announce_wait_min: A_DOIP_ANNOUNCE_WAIT_MIN,
+            announce_wait_max: A_DOIP_ANNOUNCE_WAIT_MAX,
+            announce_interval: A_DOIP_ANNOUNCE_INTERVAL,
+            announce_num: A_DOIP_ANNOUNCE_NUM,
+            diagnostic_message: A_DOIP_DIAGNOSTIC_MESSAGE,
+            tcp_general_inactivity: T_TCP_GENERAL_INACTIVITY,
+            tcp_initial_inactivity: T_TCP_INITIAL_INACTIVITY,
+            tcp_alive_check: T_TCP_ALIVE_CHECK,
+            processing_time: A_PROCESSING_TIME,
+            vehicle_discovery_timer: A_VEHICLE_DISCOVERY_TIMER,
+        }
+    }
+}
+
+impl DoipTimings {
+    /// Picks a value of `A_DoIP_Announce_Wait` from the configured range.
+    ///
+    /// This crate does not ship a random number generator, `random` is supplied
+    /// by the caller and mapped uniformly onto
+    /// `announce_wait_min..=announce_wait_max` with millisecond resolution.
+    ///
+    /// # Example
+    /// ```
+    /// use doip_definitions::timing::DoipTimings;
+    ///
+    /// let timings = DoipTimings::default();
+    /// let wait = timings.announce_wait(0x1234_5678);
+    ///
+    /// assert!(wait <= timings.announce_wait_max);
+    /// ```
+    #[must_use]
+    pub fn announce_wait(&self, random: u32) -> Duration {
+        let min = self.announce_wait_min;
+        let span = self.announce_wait_max.saturating_sub(min);
+        let span_ms = u64::try_from(span.as_millis()).unwrap_or(u64::MAX);
+
+        if span_ms == 0 {
+            return min;
+        }
+
+        min + Duration::from_millis(u64::from(random) % span_ms.saturating_add(1))
+    }
+}
@@ -0,0 +1,48 @@
+// region:      --- Modules
+
+mod tcp;
+mod udp;
+
+// -- Flatten
+
+pub use tcp::*;
+pub use udp::*;
+
+// endregion:      --- Modules
+
+use crate::{
+    definitions::{DOIP_HEADER_LEN, DOIP_PORT},
+    error::Result,
+    header::DoipHeader,
+    message::DoipMessage,
+};
+
+/// `DOIP_PORT` as a port number for `std::net` addresses.
+#[allow(clippy::cast_possible_truncation)]
+pub(crate) const DOIP_NET_PORT: u16 = DOIP_PORT as u16;
+
+/// `DOIP_TLS_PORT` as a port number for `std::net` addresses.
+#[allow(clippy::cast_possible_truncation)]
+#[cfg(feature = "tls")]
+pub(crate) const DOIP_TLS_NET_PORT: u16 = crate::definitions::DOIP_TLS_PORT as u16;
+
+/// Removes the first complete message from the bytes read from a stream socket.
+///
+/// Returns `None` while the buffer holds only part of a message.
+pub(crate) fn take_message(buffer: &mut Vec<u8>) -> Result<Option<DoipMessage>> {
+    let Some(header) = buffer.get(..DOIP_HEADER_LEN) else {
+        return Ok(None);
+    };
+
+    let header = DoipHeader::try_from(<[u8; DOIP_HEADER_LEN]>::try_from(header)?)?;
+    let len = DOIP_HEADER_LEN + header.payload_length as usize;
+
+    if buffer.len() < len {
+        return Ok(None);
+    }
+
+    let message = DoipMessage::try_from(&buffer[..len]);
+    buffer.drain(..len);
+
+    message.map(Some)
+}
@@ -0,0 +1,334 @@
+use std::io::{self, Read, Write};
+use std::net::{IpAddr, SocketAddr, TcpStream};
+use std::time::{Duration, Instant};
+
+use crate::{
+    definitions::{DOIP_DIAG_COMMON_SOURCE_LEN, DOIP_DIAG_COMMON_TARGET_LEN},
+    error::{Error, Result},
+    message::DoipMessage,
+    payload::ActivationCode,
+    tester::{TesterConfig, TesterConnection, TesterEvent},
+    timing::{A_PROCESSING_TIME, P2_STAR_SERVER_MAX},
+    transport::{take_message, DOIP_NET_PORT},
+    uds::is_response_pending,
+};
+
+/// Size of the chunks read from the socket.
+const READ_CHUNK_LEN: usize = 4096;
+
+/// A blocking `DoIP` client on top of `std::net::TcpStream`.
+///
+/// The client connects to an entity, activates routing and sends diagnostic
+/// messages, waiting for their acknowledgement and the response of the target.
+/// The protocol is handled by a [`TesterConnection`], so alive check requests of
+/// the entity are answered whenever the client reads from the socket.
+///
+/// ```rust,no_run
+/// use doip_definitions::transport::DoipTcpClient;
+///
+/// let mut client = DoipTcpClient::connect("192.168.0.10".parse().unwrap())?;
+/// let response = client.send_diagnostic([0x10, 0x00], &[0x22, 0xF1, 0x90])?;
+/// # Ok::<(), doip_definitions::error::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct DoipTcpClient {
+    stream: TcpStream,
+    connection: TesterConnection,
+    response_timeout: Duration,
+    response_pending_timeout: Duration,
+    buffer: Vec<u8>,
+}
+
+impl DoipTcpClient {
+    /// Connects to the entity at `ip` on `DOIP_PORT` and activates routing using
+    /// the default [`TesterConfig`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`] when the socket cannot be established,
+    /// [`Error::RoutingActivationDenied`] when the entity denies routing and
+    /// [`Error::Timeout`] when it does not respond.
+    pub fn connect(ip: IpAddr) -> Result<Self> {
+        Self::connect_with(SocketAddr::new(ip, DOIP_NET_PORT), TesterConfig::default())
+    }
+
+    /// Connects to the entity at `address` and activates routing using `config`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`] when the socket cannot be established,
+    /// [`Error::RoutingActivationDenied`] when the entity denies routing and
+    /// [`Error::Timeout`] when it does not respond.
+    pub fn connect_with(address: SocketAddr, config: TesterConfig) -> Result<Self> {
+        let stream = TcpStream::connect_timeout(&address, config.timings.ctrl)?;
+        stream.set_nodelay(true)?;
+
+        let mut client = Self {
+            stream,
+            connection: TesterConnection::new(config),
+            response_timeout: A_PROCESSING_TIME,
+            response_pending_timeout: P2_STAR_SERVER_MAX,
+            buffer: Vec::new(),
+        };
+
+        client.activate_routing()?;
+
+        Ok(client)
+    }
+
+    /// Sets the time the client waits for the response of the target once a
+    /// diagnostic message has been acknowledged.
+    #[must_use]
+    pub fn response_timeout(mut self, response_timeout: Duration) -> Self {
+        self.response_timeout = response_timeout;
+        self
+    }
+
+    /// Sets the time the client keeps waiting for the final response after
+    /// each `requestCorrectlyReceived-ResponsePending` of the target.
+    #[must_use]
+    pub fn response_pending_timeout(mut self, response_pending_timeout: Duration) -> Self {
+        self.response_pending_timeout = response_pending_timeout;
+        self
+    }
+
+    /// Returns the logical address of the connected entity.
+    #[must_use]
+    pub fn entity_address(&self) -> Option<[u8; DOIP_DIAG_COMMON_SOURCE_LEN]> {
+        self.connection.entity_address()
+    }
+
+    /// Returns the address of the connected entity.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`] when the socket is no longer connected.
+    pub fn peer_addr(&self) -> Result<SocketAddr> {
+        Ok(self.stream.peer_addr()?)
+    }
+
+    /// Sends the UDS request `data` to `target` and returns the response of the
+    /// target.
+    ///
+    /// Only responses sent by `target` are returned, responses of other targets
+    /// are discarded. Responses with the negative response code
+    /// `requestCorrectlyReceived-ResponsePending` are not returned either, each
+    /// of them extends the wait for the final response by the response pending
+    /// timeout.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::DiagnosticNack`] when the entity rejects the message,
+    /// [`Error::GenericNack`] when it rejects the `DoIP` header,
+    /// [`Error::Timeout`] when no acknowledgement or response is received in time
+    /// and [`Error::Io`] when the socket fails.
+    pub fn send_diagnostic(
+        &mut self,
+        target: [u8; DOIP_DIAG_COMMON_TARGET_LEN],
+        data: &[u8],
+    ) -> Result<Vec<u8>> {
+        let now = Instant::now();
+        self.connection.send_diagnostic(now, target, data)?;
+
+        let mut deadline =
+            now + self.connection.config().timings.diagnostic_message + self.response_timeout;
+
+        loop {
+            match self.next_event(Some(deadline))? {
+                TesterEvent::DiagnosticAck { target_address, .. } if target_address == target => {
+                    deadline = Instant::now() + self.response_timeout;
+                }
+                TesterEvent::DiagnosticNack {
+                    target_address,
+                    nack_code,
+                } if target_address == target => {
+                    return Err(Error::DiagnosticNack { nack_code });
+                }
+                TesterEvent::DiagnosticAckTimeout { target_address }
+                    if target_address == target =>
+                {
+                    return Err(Error::Timeout);
+                }
+                TesterEvent::DiagnosticMessage(message) if message.source_address == target => {
+                    if is_response_pending(&message.message) {
+                        deadline = Instant::now() + self.response_pending_timeout;
+                        continue;
+                    }
+
+                    return Ok(message.message);
+                }
+                TesterEvent::GenericNack { nack_code } => {
+                    return Err(Error::GenericNack { nack_code });
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn activate_routing(&mut self) -> Result<()> {
+        self.connection.connect(Instant::now());
+
+        loop {
+            match self.next_event(None)? {
+                TesterEvent::RoutingActivated { .. } => return Ok(()),
+                TesterEvent::RoutingActivationDenied { activation_code } => {
+                    return Err(Error::RoutingActivationDenied { activation_code });
+                }
+                TesterEvent::AuthenticationRequired { .. } => {
+                    return Err(Error::RoutingActivationDenied {
+                        activation_code: ActivationCode::DeniedMissingAuthentication,
+                    });
+                }
+                TesterEvent::RoutingActivationTimeout => return Err(Error::Timeout),
+                TesterEvent::GenericNack { nack_code } => {
+                    return Err(Error::GenericNack { nack_code });
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Drives the connection until it raises an event or `deadline` expires.
+    fn next_event(&mut self, deadline: Option<Instant>) -> Result<TesterEvent> {
+        loop {
+            if let Some(event) = self.connection.poll_event() {
+                return Ok(event);
+            }
+
+            self.flush()?;
+
+            let now = Instant::now();
+
+            if deadline.is_some_and(|deadline| deadline <= now) {
+                return Err(Error::Timeout);
+            }
+
+            let timeout = match (deadline, self.connection.poll_timeout()) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, b) => a.or(b),
+            };
+
+            if timeout.is_some_and(|timeout| timeout <= now) {
+                self.connection.handle_timeout(now);
+                continue;
+            }
+
+            self.stream
+                .set_read_timeout(timeout.map(|timeout| timeout - now))?;
+
+            if let Some(message) = self.read_message()? {
+                self.connection.handle_message(Instant::now(), message);
+            }
+        }
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        while let Some(message) = self.connection.poll_transmit() {
+            let bytes: Vec<u8> = message.try_into()?;
+            self.stream.write_all(&bytes)?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads from the socket once, returning a message when one is complete.
+    fn read_message(&mut self) -> Result<Option<DoipMessage>> {
+        if let Some(message) = take_message(&mut self.buffer)? {
+            return Ok(Some(message));
+        }
+
+        let mut chunk = [0u8; READ_CHUNK_LEN];
+
+        match self.stream.read(&mut chunk) {
+            Ok(0) => Err(io::Error::from(io::ErrorKind::UnexpectedEof).into()),
+            Ok(len) => {
+                self.buffer.extend_from_slice(&chunk[..len]);
+                take_message(&mut self.buffer)
+            }
+            Err(err)
+                if matches!(
+                    err.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) =>
+            {
+                Ok(None)
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+    use std::time::Duration;
+
+    use crate::{
+        definitions::DOIP_HEADER_LEN, error::Error, message::DoipMessage, mock::MockEntity,
+        tester::TesterConfig, transport::DoipTcpClient,
+    };
+
+    /// Serves `ecu` to a single client until the client disconnects.
+    fn serve(mut ecu: MockEntity) -> std::net::SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut header = [0u8; DOIP_HEADER_LEN];
+
+            while stream.read_exact(&mut header).is_ok() {
+                let len = u32::from_be_bytes([header[4], header[5], header[6], header[7]]);
+                let mut bytes = header.to_vec();
+                bytes.resize(DOIP_HEADER_LEN + len as usize, 0);
+                stream.read_exact(&mut bytes[DOIP_HEADER_LEN..]).unwrap();
+
+                let message = DoipMessage::try_from(bytes.as_slice()).unwrap();
+                for reply in ecu.handle_message(message) {
+                    let reply: Vec<u8> = reply.try_into().unwrap();
+                    stream.write_all(&reply).unwrap();
+                }
+            }
+        });
+
+        address
+    }
+
+    #[test]
+    fn test_send_diagnostic() {
+        let address = serve(MockEntity::new().response(&[0x3E, 0x00], &[0x7E, 0x00]));
+        let mut client = DoipTcpClient::connect_with(address, TesterConfig::default()).unwrap();
+
+        assert_eq!(client.entity_address(), Some([0x10, 0x00]));
+        assert_eq!(
+            client.send_diagnostic([0x10, 0x00], &[0x3E, 0x00]).unwrap(),
+            vec![0x7E, 0x00]
+        );
+        assert!(matches!(
+            client.send_diagnostic([0x10, 0x01], &[0x3E, 0x00]),
+            Err(Error::DiagnosticNack { .. })
+        ));
+    }
+
+    #[test]
+    fn test_response_pending() {
+        let address = serve(
+            MockEntity::new()
+                .response(&[0x31, 0x01, 0xFF, 0x00], &[0x7F, 0x31, 0x78])
+                .response(&[0x31, 0x01, 0xFF, 0x00], &[0x7F, 0x31, 0x78])
+                .response(&[0x31, 0x01, 0xFF, 0x00], &[0x71, 0x01, 0xFF, 0x00]),
+        );
+        let mut client = DoipTcpClient::connect_with(address, TesterConfig::default())
+            .unwrap()
+            .response_pending_timeout(Duration::from_secs(1));
+
+        assert_eq!(
+            client
+                .send_diagnostic([0x10, 0x00], &[0x31, 0x01, 0xFF, 0x00])
+                .unwrap(),
+            vec![0x71, 0x01, 0xFF, 0x00]
+        );
+    }
+}
@@ -0,0 +1,238 @@
+use std::io;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV6, UdpSocket};
+use std::time::{Duration, Instant};
+
+use crate::{
+    discovery::{DiscoveredVehicle, DiscoveryFilter, VehicleDiscovery},
+    error::Result,
+    header::ProtocolVersion,
+    message::DoipMessage,
+    timing::A_VEHICLE_DISCOVERY_TIMER,
+    transport::DOIP_NET_PORT,
+};
+
+/// Largest UDP datagram accepted during discovery.
+const DATAGRAM_LEN: usize = 1500;
+
+/// Link-local all-nodes multicast address, used instead of the broadcast address
+/// for vehicle identification requests and announcements on IPv6.
+pub const DOIP_IPV6_MULTICAST: Ipv6Addr = Ipv6Addr::new(0xFF02, 0, 0, 0, 0, 0, 0, 1);
+
+/// Configuration of [`discover`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiscoveryConfig {
+    /// Selects the vehicle identification request which is sent
+    pub filter: DiscoveryFilter,
+
+    /// Address the request is sent to, a broadcast, subnet broadcast, multicast
+    /// or unicast address
+    pub destination: SocketAddr,
+
+    /// Local address of the UDP socket, unsolicited announcements are only
+    /// received when bound to `DOIP_PORT`
+    pub bind: SocketAddr,
+
+    /// Time announcements are collected after the request is sent
+    pub timeout: Duration,
+
+    /// Protocol version used in the header of the request
+    pub protocol_version: ProtocolVersion,
+}
+
+impl Default for DiscoveryConfig {
+    /// Constructs a `DiscoveryConfig` with:
+    /// - Filter set to `All`
+    /// - Destination `255.255.255.255:13400`
+    /// - Bound to `0.0.0.0:13400`
+    /// - A timeout of `A_Vehicle_Discovery_Timer`
+    /// - Protocol version set to `DefaultValue`
+    fn default() -> Self {
+        Self {
+            filter: DiscoveryFilter::All,
+            destination: SocketAddr::from((Ipv4Addr::BROADCAST, DOIP_NET_PORT)),
+            bind: SocketAddr::from((Ipv4Addr::UNSPECIFIED, DOIP_NET_PORT)),
+            timeout: A_VEHICLE_DISCOVERY_TIMER,
+            protocol_version: ProtocolVersion::DefaultValue,
+        }
+    }
+}
+
+impl DiscoveryConfig {
+    /// Creates a `DiscoveryConfig` sending the request to `FF02::1` on the
+    /// interface identified by `scope_id`, bound to `[::]:13400`.
+    #[must_use]
+    pub fn ipv6(scope_id: u32) -> Self {
+        Self {
+            destination: SocketAddrV6::new(DOIP_IPV6_MULTICAST, DOIP_NET_PORT, 0, scope_id).into(),
+            bind: SocketAddrV6::new(Ipv6Addr::UNSPECIFIED, DOIP_NET_PORT, 0, 0).into(),
+            ..Self::default()
+        }
+    }
+}
+
+/// Discovers the vehicles reachable through `config.destination`.
+///
+/// Sends one vehicle identification request and collects every
+/// `VehicleAnnouncementMessage` received until `config.timeout` expires,
+/// including unsolicited announcements of entities which just started. Blocks
+/// for the whole timeout.
+///
+/// ```rust,no_run
+/// use doip_definitions::transport::{discover, DiscoveryConfig};
+///
+/// for vehicle in discover(&DiscoveryConfig::default())? {
+///     for entity in vehicle.entities {
+///         println!("{:?} at {}", entity.logical_address, entity.address);
+///     }
+/// }
+/// # Ok::<(), doip_definitions::error::Error>(())
+/// ```
+///
+/// # Errors
+///
+/// Returns [`Error::Io`](crate::error::Error::Io) when the socket cannot be
+/// bound or the request cannot be sent.
+pub fn discover(config: &DiscoveryConfig) -> Result<Vec<DiscoveredVehicle>> {
+    let socket = UdpSocket::bind(config.bind)?;
+
+    // IPv6 has no broadcast, the multicast destination carries the scope id
+    // selecting the interface instead.
+    if config.destination.is_ipv4() {
+        socket.set_broadcast(true)?;
+    }
+
+    let mut discovery = VehicleDiscovery::new(config.filter)
+        .timeout(config.timeout)
+        .protocol_version(config.protocol_version);
+    discovery.start(Instant::now());
+
+    while let Some(request) = discovery.poll_transmit() {
+        let bytes: Vec<u8> = request.try_into()?;
+        socket.send_to(&bytes, config.destination)?;
+    }
+
+    collect(&socket, &mut discovery)?;
+
+    Ok(discovery.into_vehicles())
+}
+
+/// Feeds the datagrams received on `socket` into `discovery` until it finishes.
+fn collect(socket: &UdpSocket, discovery: &mut VehicleDiscovery) -> Result<()> {
+    let mut datagram = [0u8; DATAGRAM_LEN];
+
+    loop {
+        let now = Instant::now();
+        discovery.handle_timeout(now);
+
+        let Some(deadline) = discovery.poll_timeout() else {
+            return Ok(());
+        };
+
+        socket.set_read_timeout(Some(deadline - now))?;
+
+        match socket.recv_from(&mut datagram) {
+            Ok((len, origin)) => {
+                if let Ok(message) = DoipMessage::try_from(&datagram[..len]) {
+                    discovery.handle_message(Instant::now(), origin, &message);
+                }
+            }
+            Err(err)
+                if matches!(
+                    err.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) => {}
+            Err(err) => return Err(err.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::net::{SocketAddr, SocketAddrV6, UdpSocket};
+    use std::thread;
+    use std::time::Duration;
+
+    use crate::{
+        discovery::DiscoveredVehicle,
+        message::DoipMessage,
+        mock::MockEntity,
+        transport::{discover, DiscoveryConfig, DOIP_IPV6_MULTICAST},
+    };
+
+    /// Answers the first request received on `entity` with one entity and sends
+    /// the unsolicited announcement of a second one.
+    fn discover_from(entity: UdpSocket, bind: &str) {
+        let destination = entity.local_addr().unwrap();
+        let vehicles = discover_through(entity, destination, bind);
+
+        assert_eq!(vehicles[0].entities[0].address, destination);
+    }
+
+    /// Runs [`discover_from`] with the request sent to `destination`, returning
+    /// the discovered vehicles.
+    fn discover_through(
+        entity: UdpSocket,
+        destination: SocketAddr,
+        bind: &str,
+    ) -> Vec<DiscoveredVehicle> {
+        thread::spawn(move || {
+            let mut answering = MockEntity::new();
+            let starting = MockEntity::new().eid([0, 0, 0, 0, 0, 2]);
+
+            let mut datagram = [0u8; 64];
+            let (len, origin) = entity.recv_from(&mut datagram).unwrap();
+            let request = DoipMessage::try_from(&datagram[..len]).unwrap();
+
+            for response in answering.handle_message(request) {
+                let response: Vec<u8> = response.try_into().unwrap();
+                entity.send_to(&response, origin).unwrap();
+            }
+
+            let unsolicited: Vec<u8> = starting.announcement().try_into().unwrap();
+            entity.send_to(&unsolicited, origin).unwrap();
+        });
+
+        let vehicles = discover(&DiscoveryConfig {
+            destination,
+            bind: bind.parse().unwrap(),
+            timeout: Duration::from_millis(200),
+            ..DiscoveryConfig::default()
+        })
+        .unwrap();
+
+        assert_eq!(vehicles.len(), 1);
+        assert_eq!(vehicles[0].entities.len(), 2);
+
+        vehicles
+    }
+
+    #[test]
+    fn test_discover_localhost() {
+        discover_from(UdpSocket::bind("127.0.0.1:0").unwrap(), "127.0.0.1:0");
+    }
+
+    #[test]
+    fn test_discover_localhost_ipv6() {
+        discover_from(UdpSocket::bind("[::1]:0").unwrap(), "[::1]:0");
+    }
+
+    #[test]
+    #[ignore = "needs an IPv6 multicast capable interface, whose index is set in DOIP_TEST_SCOPE_ID"]
+    fn test_discover_ipv6_multicast() {
+        let scope_id: u32 = std::env::var("DOIP_TEST_SCOPE_ID")
+            .expect("DOIP_TEST_SCOPE_ID is not set")
+            .parse()
+            .unwrap();
+
+        let entity = UdpSocket::bind("[::]:0").unwrap();
+        entity
+            .join_multicast_v6(&DOIP_IPV6_MULTICAST, scope_id)
+            .unwrap();
+        let port = entity.local_addr().unwrap().port();
+
+        let destination = SocketAddrV6::new(DOIP_IPV6_MULTICAST, port, 0, scope_id).into();
+        let vehicles = discover_through(entity, destination, "[::]:0");
+
+        assert_eq!(vehicles[0].entities[0].address.port(), port);
+    }
+}
@@ -0,0 +1,590 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    error::{Error, Result},
+    uds::{check_response, Fields, UdsRequest, UdsResponse},
+};
+
+/// A named group of bits of a [`DataCodec::Bitfield`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BitField {
+    /// Name of the field
+    pub name: String,
+
+    /// Position of the least significant bit of the field, counted from the
+    /// least significant bit of the data record
+    pub position: u32,
+
+    /// Number of bits of the field
+    pub width: u32,
+}
+
+impl BitField {
+    /// Creates a field of `width` bits starting at bit `position`.
+    #[must_use]
+    pub fn new(name: impl Into<String>, position: u32, width: u32) -> Self {
+        Self {
+            name: name.into(),
+            position,
+            width,
+        }
+    }
+
+    fn mask(&self) -> u64 {
+        64u32
+            .checked_sub(self.width)
+            .and_then(|shift| u64::MAX.checked_shr(shift))
+            .unwrap_or(0)
+    }
+}
+
+/// Encoding of the data record of a data identifier.
+///
+/// Codecs without a length consume the rest of a `ReadDataByIdentifier`
+/// response, so they can only be decoded as the last record.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DataCodec {
+    /// ASCII text, trailing NUL and space padding is trimmed when decoding and
+    /// space padding up to `length` is added when encoding
+    Ascii {
+        /// Fixed length in bytes
+        length: Option<usize>,
+    },
+
+    /// Big-endian unsigned integer of 1 to 8 bytes, decoded into the physical
+    /// value `raw * scale + offset`
+    Unsigned {
+        /// Length in bytes
+        length: usize,
+
+        /// Factor applied to the raw value
+        scale: f64,
+
+        /// Offset added to the scaled value
+        offset: f64,
+    },
+
+    /// Big-endian bitfield of 1 to 8 bytes split into named fields
+    Bitfield {
+        /// Length in bytes
+        length: usize,
+
+        /// The named fields, bits outside of any field are ignored
+        fields: Vec<BitField>,
+    },
+
+    /// Uninterpreted bytes
+    Raw {
+        /// Fixed length in bytes
+        length: Option<usize>,
+    },
+}
+
+impl DataCodec {
+    /// Returns the length of the data record, or `None` when it is variable.
+    #[must_use]
+    pub fn length(&self) -> Option<usize> {
+        match self {
+            DataCodec::Ascii { length } | DataCodec::Raw { length } => *length,
+            DataCodec::Unsigned { length, .. } | DataCodec::Bitfield { length, .. } => {
+                Some(*length)
+            }
+        }
+    }
+
+    pub(super) fn decode(&self, data: &[u8]) -> Option<DataValue> {
+        if self.length().is_some_and(|length| length != data.len()) {
+            return None;
+        }
+
+        match self {
+            DataCodec::Ascii { .. } => {
+                let text = core::str::from_utf8(data)
+                    .ok()
+                    .filter(|text| text.is_ascii())?;
+
+                Some(DataValue::Text(
+                    text.trim_end_matches(['\0', ' ']).to_string(),
+                ))
+            }
+            DataCodec::Unsigned { scale, offset, .. } => {
+                #[allow(clippy::cast_precision_loss)]
+                let raw = from_be_bytes(data)? as f64;
+
+                Some(DataValue::Number(raw * scale + offset))
+            }
+            DataCodec::Bitfield { fields, .. } => {
+                let raw = from_be_bytes(data)?;
+
+                Some(DataValue::Bits(
+                    fields
+                        .iter()
+                        .map(|field| {
+                            let value = raw.checked_shr(field.position).unwrap_or(0) & field.mask();
+                            (field.name.clone(), value)
+                        })
+                        .collect(),
+                ))
+            }
+            DataCodec::Raw { .. } => Some(DataValue::Bytes(data.to_vec())),
+        }
+    }
+
+    pub(super) fn encode(&self, value: &DataValue) -> Option<Vec<u8>> {
+        let bytes = match (self, value) {
+            (DataCodec::Ascii { length }, DataValue::Text(text)) if text.is_ascii() => {
+                let mut bytes = text.as_bytes().to_vec();
+                if let Some(length) = length {
+                    if bytes.len() > *length {
+                        return None;
+                    }
+                    bytes.resize(*length, b' ');
+                }
+                bytes
+            }
+            (
+                DataCodec::Unsigned {
+                    length,
+                    scale,
+                    offset,
+                },
+                DataValue::Number(physical),
+            ) => {
+                let raw = ((physical - offset) / scale).round();
+
+                #[allow(clippy::cast_precision_loss)]
+                let max = max_value(*length)? as f64;
+
+                if !(0.0..=max).contains(&raw) {
+                    return None;
+                }
+
+                #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                to_be_bytes(raw as u64, *length)?
+            }
+            (DataCodec::Bitfield { length, fields }, DataValue::Bits(values)) => {
+                let mut raw = 0u64;
+
+                for (name, value) in values {
+                    let field = fields.iter().find(|field| field.name == *name)?;
+
+                    if *value & !field.mask() != 0 {
+                        return None;
+                    }
+                    raw |= value.checked_shl(field.position)?;
+                }
+
+                to_be_bytes(raw, *length)?
+            }
+            (DataCodec::Raw { length }, DataValue::Bytes(bytes)) => {
+                if length.is_some_and(|length| length != bytes.len()) {
+                    return None;
+                }
+                bytes.clone()
+            }
+            _ => return None,
+        };
+
+        Some(bytes)
+    }
+}
+
+/// Returns the largest value of a big-endian integer of `length` bytes.
+fn max_value(length: usize) -> Option<u64> {
+    match length {
+        1..=7 => Some((1 << (8 * length)) - 1),
+        8 => Some(u64::MAX),
+        _ => None,
+    }
+}
+
+fn from_be_bytes(data: &[u8]) -> Option<u64> {
+    max_value(data.len())?;
+
+    Some(
+        data.iter()
+            .fold(0, |value, byte| (value << 8) | u64::from(*byte)),
+    )
+}
+
+fn to_be_bytes(value: u64, length: usize) -> Option<Vec<u8>> {
+    if value > max_value(length)? {
+        return None;
+    }
+
+    Some(value.to_be_bytes()[8 - length..].to_vec())
+}
+
+/// A decoded data record.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DataValue {
+    /// Text of a [`DataCodec::Ascii`]
+    Text(String),
+
+    /// Physical value of a [`DataCodec::Unsigned`]
+    Number(f64),
+
+    /// Named field values of a [`DataCodec::Bitfield`], in field order
+    Bits(Vec<(String, u64)>),
+
+    /// Bytes of a [`DataCodec::Raw`]
+    Bytes(Vec<u8>),
+}
+
+/// Name and codec of a data identifier.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DataDefinition {
+    /// Name of the data identifier
+    pub name: String,
+
+    /// Encoding of the data record
+    pub codec: DataCodec,
+}
+
+/// A data record of a `ReadDataByIdentifier` response, decoded by a
+/// [`DataIdentifierRegistry`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct DataRecord {
+    /// The data identifier
+    pub identifier: u16,
+
+    /// Name of the data identifier
+    pub name: String,
+
+    /// The decoded data record
+    pub value: DataValue,
+}
+
+/// Maps data identifiers to their name and [`DataCodec`].
+///
+/// Decodes the records of `ReadDataByIdentifier` responses into named values
+/// and encodes the data records of `WriteDataByIdentifier` requests. The
+/// identification data identifiers of ISO 14229-1 are registered by default.
+///
+/// ```rust
+/// use doip_definitions::uds::{DataCodec, DataIdentifierRegistry, DataValue};
+///
+/// let mut registry = DataIdentifierRegistry::new();
+/// registry.insert(
+///     0x0100,
+///     "Coolant Temperature",
+///     DataCodec::Unsigned { length: 1, scale: 1.0, offset: -40.0 },
+/// );
+///
+/// let records = registry.decode_response(&[
+///     0x62, 0xF1, 0x90, b'W', b'V', b'W', b'Z', b'Z', b'Z', b'1', b'J', b'Z', b'X', b'W',
+///     b'0', b'0', b'0', b'0', b'0', b'1', 0x01, 0x00, 0x7D,
+/// ])?;
+///
+/// assert_eq!(records[0].name, "VIN Data Identifier");
+/// assert_eq!(records[0].value, DataValue::Text("WVWZZZ1JZXW000001".into()));
+/// assert_eq!(records[1].value, DataValue::Number(85.0));
+/// # Ok::<(), doip_definitions::error::Error>(())
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct DataIdentifierRegistry {
+    definitions: BTreeMap<u16, DataDefinition>,
+}
+
+impl Default for DataIdentifierRegistry {
+    /// Constructs a `DataIdentifierRegistry` with the identification data
+    /// identifiers of ISO 14229-1:
+    /// - `0xF180` to `0xF182` software identifications as raw bytes
+    /// - `0xF186` active diagnostic session as an unsigned integer
+    /// - `0xF187` to `0xF18A` and `0xF18C` part numbers, supplier and serial
+    ///   number as ASCII text
+    /// - `0xF18B` ECU manufacturing date as raw BCD bytes
+    /// - `0xF190` VIN as 17 characters of ASCII text
+    /// - `0xF191` to `0xF197` hardware and software numbers and the system name
+    ///   as ASCII text
+    fn default() -> Self {
+        let ascii = DataCodec::Ascii { length: None };
+        let raw = DataCodec::Raw { length: None };
+
+        let mut registry = Self::empty();
+        for (identifier, name, codec) in [
+            (0xF180, "Boot Software Identification", raw.clone()),
+            (0xF181, "Application Software Identification", raw.clone()),
+            (0xF182, "Application Data Identification", raw),
+            (
+                0xF186,
+                "Active Diagnostic Session",
+                DataCodec::Unsigned {
+                    length: 1,
+                    scale: 1.0,
+                    offset: 0.0,
+                },
+            ),
+            (
+                0xF187,
+                "Vehicle Manufacturer Spare Part Number",
+                ascii.clone(),
+            ),
+            (
+                0xF188,
+                "Vehicle Manufacturer ECU Software Number",
+                ascii.clone(),
+            ),
+            (
+                0xF189,
+                "Vehicle Manufacturer ECU Software Version Number",
+                ascii.clone(),
+            ),
+            (0xF18A, "System Supplier Identifier", ascii.clone()),
+            (
+                0xF18B,
+                "ECU Manufacturing Date",
+                DataCodec::Raw { length: Some(3) },
+            ),
+            (0xF18C, "ECU Serial Number", ascii.clone()),
+            (
+                0xF190,
+                "VIN Data Identifier",
+                DataCodec::Ascii { length: Some(17) },
+            ),
+            (
+                0xF191,
+                "Vehicle Manufacturer ECU Hardware Number",
+                ascii.clone(),
+            ),
+            (0xF192, "System Supplier ECU Hardware Number", ascii.clone()),
+            (
+                0xF193,
+                "System Supplier ECU Hardware Version Number",
+                ascii.clone(),
+            ),
+            (0xF194, "System Supplier ECU Software Number", ascii.clone()),
+            (
+                0xF195,
+                "System Supplier ECU Software Version Number",
+                ascii.clone(),
+            ),
+            (0xF197, "System Name Or Engine Type", ascii),
+        ] {
+            registry.insert(identifier, name, codec);
+        }
+
+        registry
+    }
+}
+
+impl DataIdentifierRegistry {
+    /// Creates a `DataIdentifierRegistry` with the ISO 14229-1 identification
+    /// data identifiers.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a `DataIdentifierRegistry` without any data identifier.
+    #[must_use]
+    pub fn empty() -> Self {
+        Self {
+            definitions: BTreeMap::new(),
+        }
+    }
+
+    /// Registers `identifier` with `name` and `codec`, replacing any previous
+    /// definition.
+    pub fn insert(&mut self, identifier: u16, name: impl Into<String>, codec: DataCodec) {
+        self.definitions.insert(
+            identifier,
+            DataDefinition {
+                name: name.into(),
+                codec,
+            },
+        );
+    }
+
+    /// Removes the definition of `identifier`.
+    pub fn remove(&mut self, identifier: u16) {
+        self.definitions.remove(&identifier);
+    }
+
+    /// Returns the definition of `identifier`.
+    #[must_use]
+    pub fn get(&self, identifier: u16) -> Option<&DataDefinition> {
+        self.definitions.get(&identifier)
+    }
+
+    fn definition(&self, identifier: u16) -> Result<&DataDefinition> {
+        self.get(identifier)
+            .ok_or(Error::UnknownDataIdentifier { identifier })
+    }
+
+    /// Decodes the data record of `identifier`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::UnknownDataIdentifier`] when `identifier` is not
+    /// registered and [`Error::InvalidDataRecord`] when `data` does not match
+    /// its codec.
+    pub fn decode(&self, identifier: u16, data: &[u8]) -> Result<DataValue> {
+        self.definition(identifier)?
+            .codec
+            .decode(data)
+            .ok_or(Error::InvalidDataRecord { identifier })
+    }
+
+    /// Encodes `value` into the data record of `identifier`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::UnknownDataIdentifier`] when `identifier` is not
+    /// registered and [`Error::InvalidDataRecord`] when `value` does not match
+    /// its codec or is out of range.
+    pub fn encode(&self, identifier: u16, value: &DataValue) -> Result<Vec<u8>> {
+        self.definition(identifier)?
+            .codec
+            .encode(value)
+            .ok_or(Error::InvalidDataRecord { identifier })
+    }
+
+    /// Builds the `WriteDataByIdentifier` request writing `value` to
+    /// `identifier`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`DataIdentifierRegistry::encode`].
+    pub fn write_request(&self, identifier: u16, value: &DataValue) -> Result<UdsRequest> {
+        Ok(UdsRequest::WriteDataByIdentifier {
+            identifier,
+            data: self.encode(identifier, value)?,
+        })
+    }
+
+    /// Splits and decodes the `records` of a `ReadDataByIdentifier` response,
+    /// each a data identifier followed by its data record.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::UnknownDataIdentifier`] when an identifier is not
+    /// registered, [`Error::InvalidDataRecord`] when a data record does not
+    /// match its codec and [`Error::OutOfBounds`] when `records` is truncated.
+    pub fn decode_records(&self, records: &[u8]) -> Result<Vec<DataRecord>> {
+        let fields = Fields::new(records, "DataRecord");
+        let mut decoded = Vec::new();
+        let mut index = 0;
+
+        while index < records.len() {
+            let identifier = fields.u16(index, "Data Identifier")?;
+            let definition = self.definition(identifier)?;
+            index += 2;
+
+            let end = definition
+                .codec
+                .length()
+                .map_or(records.len(), |length| index + length);
+            let data = fields.get(index..end, "Data Record")?;
+            index = end;
+
+            decoded.push(DataRecord {
+                identifier,
+                name: definition.name.clone(),
+                value: self.decode(identifier, data)?,
+            });
+        }
+
+        Ok(decoded)
+    }
+
+    /// Decodes the UDS message of a `ReadDataByIdentifier` response.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::NegativeResponse`] for a negative response,
+    /// [`Error::InvalidServiceId`] for the response of another service and the
+    /// errors of [`DataIdentifierRegistry::decode_records`].
+    pub fn decode_response(&self, response: &[u8]) -> Result<Vec<DataRecord>> {
+        match UdsResponse::try_from(check_response(response)?)? {
+            UdsResponse::ReadDataByIdentifier { records } => self.decode_records(&records),
+            _ => Err(Error::InvalidServiceId { value: response[0] }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        error::Error,
+        uds::{BitField, DataCodec, DataIdentifierRegistry, DataValue},
+    };
+
+    #[test]
+    fn test_data_identifiers() {
+        let mut registry = DataIdentifierRegistry::new();
+        registry.insert(
+            0x0101,
+            "Battery Voltage",
+            DataCodec::Unsigned {
+                length: 2,
+                scale: 0.01,
+                offset: 0.0,
+            },
+        );
+        registry.insert(
+            0x0102,
+            "Door Status",
+            DataCodec::Bitfield {
+                length: 1,
+                fields: vec![BitField::new("Driver", 0, 1), BitField::new("Lock", 4, 2)],
+            },
+        );
+
+        let records = registry
+            .decode_response(&[
+                0x62, 0x01, 0x01, 0x05, 0x0A, 0x01, 0x02, 0x21, 0xF1, 0x8C, b'S', b'N', b'1', 0x00,
+            ])
+            .unwrap();
+
+        assert_eq!(records.len(), 3);
+        assert!(
+            matches!(records[0].value, DataValue::Number(volts) if (volts - 12.9).abs() < 1e-9)
+        );
+        assert_eq!(
+            records[1].value,
+            DataValue::Bits(vec![("Driver".into(), 1), ("Lock".into(), 2)])
+        );
+        assert_eq!(records[2].name, "ECU Serial Number");
+        assert_eq!(records[2].value, DataValue::Text("SN1".into()));
+
+        assert_eq!(
+            Vec::<u8>::from(
+                registry
+                    .write_request(0x0101, &DataValue::Number(13.8))
+                    .unwrap()
+            ),
+            vec![0x2E, 0x01, 0x01, 0x05, 0x64]
+        );
+        assert_eq!(
+            registry
+                .encode(0x0102, &DataValue::Bits(vec![("Lock".into(), 3)]))
+                .unwrap(),
+            vec![0x30]
+        );
+        assert_eq!(
+            registry
+                .encode(0xF190, &DataValue::Text("WVWZZZ1JZXW00001".into()))
+                .unwrap()
+                .len(),
+            17
+        );
+
+        assert!(matches!(
+            registry.encode(0x0101, &DataValue::Number(-1.0)),
+            Err(Error::InvalidDataRecord { identifier: 0x0101 })
+        ));
+        assert!(matches!(
+            registry.encode(0x0102, &DataValue::Bits(vec![("Lock".into(), 4)])),
+            Err(Error::InvalidDataRecord { identifier: 0x0102 })
+        ));
+        assert!(matches!(
+            registry.decode_response(&[0x62, 0x12, 0x34, 0x00]),
+            Err(Error::UnknownDataIdentifier { identifier: 0x1234 })
+        ));
+        assert!(matches!(
+            registry.decode_response(&[0x62, 0x01, 0x01, 0x05]),
+            Err(Error::OutOfBounds { .. })
+        ));
+    }
+}
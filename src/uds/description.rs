@@ -0,0 +1,393 @@
+use std::path::Path;
+
+use serde::{de, Deserialize, Deserializer};
+
+use crate::{
+    error::Result,
+    uds::{
+        BitField, DataCodec, DataIdentifierRegistry, DtcRegistry, RoutineDefinition,
+        RoutineRegistry,
+    },
+};
+
+/// UDS codec registries loaded from a JSON or TOML description, enabled by the
+/// `description` feature.
+///
+/// The description is a lightweight stand-in for ODX listing data identifiers,
+/// routines and DTCs. Identifiers and DTCs are integers or hexadecimal strings,
+/// codecs are tagged with their `type`, one of `ascii`, `unsigned`, `bitfield`
+/// and `raw`, with the fields of the matching [`DataCodec`] variant. `scale`
+/// defaults to 1, `offset` to 0 and the `width` of a bit field to 1 bit.
+///
+/// ```rust
+/// use doip_definitions::uds::{DataValue, UdsDescription};
+///
+/// let description = UdsDescription::from_toml(
+///     r#"
+///     [[did]]
+///     id = 0x0100
+///     name = "Coolant Temperature"
+///     codec = { type = "unsigned", length = 1, offset = -40.0 }
+///
+///     [[routine]]
+///     id = "0xFF00"
+///     name = "Erase Memory"
+///     status_record = { type = "raw", length = 1 }
+///
+///     [[dtc]]
+///     code = 0x012300
+///     name = "Throttle Position Sensor Circuit"
+///     "#,
+/// )?;
+///
+/// assert_eq!(
+///     description.data_identifiers.decode(0x0100, &[0x7D])?,
+///     DataValue::Number(85.0)
+/// );
+/// assert_eq!(description.routines.get(0xFF00).unwrap().name, "Erase Memory");
+/// assert_eq!(
+///     description.dtcs.name(0x012300),
+///     Some("Throttle Position Sensor Circuit")
+/// );
+/// # Ok::<(), doip_definitions::error::Error>(())
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct UdsDescription {
+    /// The ISO 14229-1 identification data identifiers and those of the
+    /// description, which replace standard ones with the same identifier
+    pub data_identifiers: DataIdentifierRegistry,
+
+    /// The routines of the description
+    pub routines: RoutineRegistry,
+
+    /// The DTCs of the description
+    pub dtcs: DtcRegistry,
+}
+
+impl UdsDescription {
+    /// Parses a JSON description.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Json`](crate::error::Error::Json) when `json` is not a
+    /// valid description.
+    pub fn from_json(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str::<File>(json)?.into())
+    }
+
+    /// Parses a TOML description.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Toml`](crate::error::Error::Toml) when `toml` is not a
+    /// valid description.
+    pub fn from_toml(toml: &str) -> Result<Self> {
+        Ok(toml::from_str::<File>(toml)?.into())
+    }
+
+    /// Reads the description at `path`, parsed as JSON when the file name ends
+    /// with `.json` and as TOML otherwise.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`](crate::error::Error::Io) when the file cannot be
+    /// read and the errors of [`UdsDescription::from_json`] and
+    /// [`UdsDescription::from_toml`].
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)?;
+
+        if path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("json"))
+        {
+            Self::from_json(&text)
+        } else {
+            Self::from_toml(&text)
+        }
+    }
+}
+
+/// The layout of a description file.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct File {
+    #[serde(default, rename = "did")]
+    data_identifiers: Vec<DataIdentifierEntry>,
+
+    #[serde(default, rename = "routine")]
+    routines: Vec<RoutineEntry>,
+
+    #[serde(default, rename = "dtc")]
+    dtcs: Vec<DtcEntry>,
+}
+
+impl From<File> for UdsDescription {
+    fn from(value: File) -> Self {
+        let mut description = UdsDescription::default();
+
+        for entry in value.data_identifiers {
+            description
+                .data_identifiers
+                .insert(entry.id, entry.name, entry.codec.into());
+        }
+
+        for entry in value.routines {
+            let mut definition = RoutineDefinition::new(entry.name);
+            if let Some(codec) = entry.option_record {
+                definition.option_record = codec.into();
+            }
+            if let Some(codec) = entry.status_record {
+                definition.status_record = codec.into();
+            }
+
+            description.routines.insert(entry.id, definition);
+        }
+
+        for entry in value.dtcs {
+            description.dtcs.insert(entry.code, entry.name);
+        }
+
+        description
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DataIdentifierEntry {
+    #[serde(deserialize_with = "identifier")]
+    id: u16,
+    name: String,
+    codec: Codec,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RoutineEntry {
+    #[serde(deserialize_with = "identifier")]
+    id: u16,
+    name: String,
+    option_record: Option<Codec>,
+    status_record: Option<Codec>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DtcEntry {
+    #[serde(deserialize_with = "dtc")]
+    code: u32,
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Codec {
+    Ascii {
+        length: Option<usize>,
+    },
+    Unsigned {
+        length: usize,
+        #[serde(default = "unit_scale")]
+        scale: f64,
+        #[serde(default)]
+        offset: f64,
+    },
+    Bitfield {
+        length: usize,
+        fields: Vec<Field>,
+    },
+    Raw {
+        length: Option<usize>,
+    },
+}
+
+impl From<Codec> for DataCodec {
+    fn from(value: Codec) -> Self {
+        match value {
+            Codec::Ascii { length } => DataCodec::Ascii { length },
+            Codec::Unsigned {
+                length,
+                scale,
+                offset,
+            } => DataCodec::Unsigned {
+                length,
+                scale,
+                offset,
+            },
+            Codec::Bitfield { length, fields } => DataCodec::Bitfield {
+                length,
+                fields: fields
+                    .into_iter()
+                    .map(|field| BitField::new(field.name, field.position, field.width))
+                    .collect(),
+            },
+            Codec::Raw { length } => DataCodec::Raw { length },
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Field {
+    name: String,
+    position: u32,
+    #[serde(default = "unit_width")]
+    width: u32,
+}
+
+fn unit_scale() -> f64 {
+    1.0
+}
+
+fn unit_width() -> u32 {
+    1
+}
+
+/// An integer, or a string holding a decimal or `0x` prefixed hexadecimal
+/// integer, as JSON has no hexadecimal literals.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Integer {
+    Number(u64),
+    Text(String),
+}
+
+impl Integer {
+    fn value<E: de::Error>(self) -> core::result::Result<u64, E> {
+        match self {
+            Integer::Number(value) => Ok(value),
+            Integer::Text(text) => {
+                let parsed = match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+                    Some(hex) => u64::from_str_radix(hex, 16),
+                    None => text.parse(),
+                };
+
+                parsed.map_err(|_| E::custom(format!("invalid integer `{text}`")))
+            }
+        }
+    }
+}
+
+fn identifier<'de, D: Deserializer<'de>>(deserializer: D) -> core::result::Result<u16, D::Error> {
+    let value = Integer::deserialize(deserializer)?.value()?;
+
+    u16::try_from(value)
+        .map_err(|_| de::Error::custom(format!("identifier {value:#X} exceeds 2 bytes")))
+}
+
+fn dtc<'de, D: Deserializer<'de>>(deserializer: D) -> core::result::Result<u32, D::Error> {
+    let value = Integer::deserialize(deserializer)?.value()?;
+
+    u32::try_from(value)
+        .ok()
+        .filter(|value| *value <= 0x00FF_FFFF)
+        .ok_or_else(|| de::Error::custom(format!("DTC {value:#X} exceeds 3 bytes")))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        error::Error,
+        uds::{DataValue, RoutineControlType, UdsDescription},
+    };
+
+    #[test]
+    fn test_description() {
+        let json = UdsDescription::from_json(
+            r#"{
+                "did": [
+                    {
+                        "id": "0xF190",
+                        "name": "Vehicle Identification Number",
+                        "codec": { "type": "ascii", "length": 17 }
+                    },
+                    {
+                        "id": 258,
+                        "name": "Door Status",
+                        "codec": {
+                            "type": "bitfield",
+                            "length": 1,
+                            "fields": [
+                                { "name": "Driver", "position": 0 },
+                                { "name": "Lock", "position": 4, "width": 2 }
+                            ]
+                        }
+                    }
+                ],
+                "routine": [
+                    {
+                        "id": "0xFF00",
+                        "name": "Erase Memory",
+                        "option_record": { "type": "raw", "length": 4 }
+                    }
+                ],
+                "dtc": [{ "code": "0x012300", "name": "Throttle Position Sensor Circuit" }]
+            }"#,
+        )
+        .unwrap();
+
+        let toml = UdsDescription::from_toml(
+            r#"
+            [[did]]
+            id = 0xF190
+            name = "Vehicle Identification Number"
+            codec = { type = "ascii", length = 17 }
+
+            [[did]]
+            id = 258
+            name = "Door Status"
+            codec = { type = "bitfield", length = 1, fields = [
+                { name = "Driver", position = 0 },
+                { name = "Lock", position = 4, width = 2 },
+            ] }
+
+            [[routine]]
+            id = "0xFF00"
+            name = "Erase Memory"
+            option_record = { type = "raw", length = 4 }
+
+            [[dtc]]
+            code = 0x012300
+            name = "Throttle Position Sensor Circuit"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(json, toml);
+        assert_eq!(
+            json.data_identifiers.get(0xF190).unwrap().name,
+            "Vehicle Identification Number"
+        );
+        assert_eq!(
+            json.data_identifiers.get(0xF18C).unwrap().name,
+            "ECU Serial Number"
+        );
+        assert_eq!(
+            json.data_identifiers.decode(0x0102, &[0x21]).unwrap(),
+            DataValue::Bits(vec![("Driver".into(), 1), ("Lock".into(), 2)])
+        );
+        assert_eq!(
+            Vec::<u8>::from(
+                json.routines
+                    .request(
+                        0xFF00,
+                        RoutineControlType::StartRoutine,
+                        Some(&DataValue::Bytes(vec![0x00, 0x01, 0x00, 0x00]))
+                    )
+                    .unwrap()
+            ),
+            vec![0x31, 0x01, 0xFF, 0x00, 0x00, 0x01, 0x00, 0x00]
+        );
+
+        assert!(matches!(
+            UdsDescription::from_toml(
+                "[[did]]\nid = 0x10000\nname = \"Too Large\"\ncodec = { type = \"raw\" }"
+            ),
+            Err(Error::Toml(_))
+        ));
+        assert!(matches!(
+            UdsDescription::from_json(r#"{ "did": [{ "id": "F190" }] }"#),
+            Err(Error::Json(_))
+        ));
+    }
+}
@@ -0,0 +1,588 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    error::{Error, Result},
+    uds::{check_response, Fields, UdsRequest, UdsResponse},
+};
+
+/// Maps 3 byte DTCs to their name.
+///
+/// ```rust
+/// use doip_definitions::uds::DtcRegistry;
+///
+/// let mut registry = DtcRegistry::new();
+/// registry.insert(0x01_23_00, "Throttle Position Sensor Circuit");
+///
+/// assert_eq!(registry.name(0x01_23_00), Some("Throttle Position Sensor Circuit"));
+/// assert_eq!(registry.name(0x01_24_00), None);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DtcRegistry {
+    names: BTreeMap<u32, String>,
+}
+
+impl DtcRegistry {
+    /// Creates an empty `DtcRegistry`.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the 3 byte `dtc` with `name`, replacing any previous name.
+    pub fn insert(&mut self, dtc: u32, name: impl Into<String>) {
+        self.names.insert(dtc & 0x00FF_FFFF, name.into());
+    }
+
+    /// Removes the name of `dtc`.
+    pub fn remove(&mut self, dtc: u32) {
+        self.names.remove(&(dtc & 0x00FF_FFFF));
+    }
+
+    /// Returns the name of `dtc`.
+    #[must_use]
+    pub fn name(&self, dtc: u32) -> Option<&str> {
+        self.names.get(&(dtc & 0x00FF_FFFF)).map(String::as_str)
+    }
+}
+
+/// A 3 byte DTC, the 2 byte SAE J2012 DTC followed by the failure type byte.
+///
+/// Displayed in SAE J2012 form followed by the failure type byte.
+///
+/// ```rust
+/// use doip_definitions::uds::Dtc;
+///
+/// let dtc = Dtc(0xC1_00_87);
+/// assert_eq!(dtc.code(), "U0100");
+/// assert_eq!(dtc.failure_type(), 0x87);
+/// assert_eq!(dtc.to_string(), "U0100-87");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Dtc(pub u32);
+
+impl Dtc {
+    /// Returns the 2 byte DTC in SAE J2012 form, e.g. `P0123`.
+    #[must_use]
+    pub fn code(&self) -> String {
+        let system = match (self.0 >> 22) & 0x03 {
+            0 => 'P',
+            1 => 'C',
+            2 => 'B',
+            _ => 'U',
+        };
+
+        format!("{system}{:04X}", (self.0 >> 8) & 0x3FFF)
+    }
+
+    /// Returns the failure type byte.
+    #[must_use]
+    pub fn failure_type(&self) -> u8 {
+        self.0.to_be_bytes()[3]
+    }
+}
+
+impl From<u32> for Dtc {
+    fn from(value: u32) -> Self {
+        Dtc(value & 0x00FF_FFFF)
+    }
+}
+
+impl From<Dtc> for u32 {
+    fn from(value: Dtc) -> Self {
+        value.0
+    }
+}
+
+impl core::fmt::Display for Dtc {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(fmt, "{}-{:02X}", self.code(), self.failure_type())
+    }
+}
+
+/// The status byte of a DTC.
+///
+/// Displayed as the names of the set bits separated by ` | `.
+///
+/// ```rust
+/// use doip_definitions::uds::DtcStatus;
+///
+/// let status = DtcStatus(0x09);
+/// assert!(status.test_failed());
+/// assert!(status.confirmed_dtc());
+/// assert_eq!(status.to_string(), "testFailed | confirmedDTC");
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct DtcStatus(pub u8);
+
+impl DtcStatus {
+    /// The most recent test failed
+    pub const TEST_FAILED: u8 = 0x01;
+
+    /// A test failed during the current operation cycle
+    pub const TEST_FAILED_THIS_OPERATION_CYCLE: u8 = 0x02;
+
+    /// A test failed during the current or last completed operation cycle
+    pub const PENDING_DTC: u8 = 0x04;
+
+    /// The failure was detected often enough to be stored
+    pub const CONFIRMED_DTC: u8 = 0x08;
+
+    /// No test completed since DTCs were last cleared
+    pub const TEST_NOT_COMPLETED_SINCE_LAST_CLEAR: u8 = 0x10;
+
+    /// A test failed since DTCs were last cleared
+    pub const TEST_FAILED_SINCE_LAST_CLEAR: u8 = 0x20;
+
+    /// No test completed during the current operation cycle
+    pub const TEST_NOT_COMPLETED_THIS_OPERATION_CYCLE: u8 = 0x40;
+
+    /// The server requests a warning indicator
+    pub const WARNING_INDICATOR_REQUESTED: u8 = 0x80;
+
+    const NAMES: [(u8, &'static str); 8] = [
+        (Self::TEST_FAILED, "testFailed"),
+        (
+            Self::TEST_FAILED_THIS_OPERATION_CYCLE,
+            "testFailedThisOperationCycle",
+        ),
+        (Self::PENDING_DTC, "pendingDTC"),
+        (Self::CONFIRMED_DTC, "confirmedDTC"),
+        (
+            Self::TEST_NOT_COMPLETED_SINCE_LAST_CLEAR,
+            "testNotCompletedSinceLastClear",
+        ),
+        (
+            Self::TEST_FAILED_SINCE_LAST_CLEAR,
+            "testFailedSinceLastClear",
+        ),
+        (
+            Self::TEST_NOT_COMPLETED_THIS_OPERATION_CYCLE,
+            "testNotCompletedThisOperationCycle",
+        ),
+        (
+            Self::WARNING_INDICATOR_REQUESTED,
+            "warningIndicatorRequested",
+        ),
+    ];
+
+    /// Returns whether all bits of `mask` are set.
+    #[must_use]
+    pub fn contains(&self, mask: u8) -> bool {
+        self.0 & mask == mask
+    }
+
+    /// Returns whether the most recent test failed.
+    #[must_use]
+    pub fn test_failed(&self) -> bool {
+        self.contains(Self::TEST_FAILED)
+    }
+
+    /// Returns whether a test failed during the current operation cycle.
+    #[must_use]
+    pub fn test_failed_this_operation_cycle(&self) -> bool {
+        self.contains(Self::TEST_FAILED_THIS_OPERATION_CYCLE)
+    }
+
+    /// Returns whether a test failed during the current or last completed
+    /// operation cycle.
+    #[must_use]
+    pub fn pending_dtc(&self) -> bool {
+        self.contains(Self::PENDING_DTC)
+    }
+
+    /// Returns whether the failure was detected often enough to be stored.
+    #[must_use]
+    pub fn confirmed_dtc(&self) -> bool {
+        self.contains(Self::CONFIRMED_DTC)
+    }
+
+    /// Returns whether no test completed since DTCs were last cleared.
+    #[must_use]
+    pub fn test_not_completed_since_last_clear(&self) -> bool {
+        self.contains(Self::TEST_NOT_COMPLETED_SINCE_LAST_CLEAR)
+    }
+
+    /// Returns whether a test failed since DTCs were last cleared.
+    #[must_use]
+    pub fn test_failed_since_last_clear(&self) -> bool {
+        self.contains(Self::TEST_FAILED_SINCE_LAST_CLEAR)
+    }
+
+    /// Returns whether no test completed during the current operation cycle.
+    #[must_use]
+    pub fn test_not_completed_this_operation_cycle(&self) -> bool {
+        self.contains(Self::TEST_NOT_COMPLETED_THIS_OPERATION_CYCLE)
+    }
+
+    /// Returns whether the server requests a warning indicator.
+    #[must_use]
+    pub fn warning_indicator_requested(&self) -> bool {
+        self.contains(Self::WARNING_INDICATOR_REQUESTED)
+    }
+
+    /// Returns the ISO 14229-1 names of the set bits, lowest bit first.
+    #[must_use]
+    pub fn names(&self) -> Vec<&'static str> {
+        Self::NAMES
+            .iter()
+            .filter(|(bit, _)| self.contains(*bit))
+            .map(|(_, name)| *name)
+            .collect()
+    }
+}
+
+impl core::fmt::Display for DtcStatus {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(fmt, "{}", self.names().join(" | "))
+    }
+}
+
+/// A DTC and its status.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DtcRecord {
+    /// The DTC
+    pub dtc: Dtc,
+
+    /// The status of the DTC
+    pub status: DtcStatus,
+}
+
+/// Report type sub-function of `ReadDTCInformation` reporting the number of
+/// DTCs matching a status mask.
+pub const REPORT_NUMBER_OF_DTC_BY_STATUS_MASK: u8 = 0x01;
+
+/// Report type sub-function of `ReadDTCInformation` reporting the DTCs matching
+/// a status mask.
+pub const REPORT_DTC_BY_STATUS_MASK: u8 = 0x02;
+
+/// Report type sub-function of `ReadDTCInformation` reporting the snapshot
+/// records of a DTC.
+pub const REPORT_DTC_SNAPSHOT_RECORD_BY_DTC_NUMBER: u8 = 0x04;
+
+/// Report type sub-function of `ReadDTCInformation` reporting the extended data
+/// records of a DTC.
+pub const REPORT_DTC_EXT_DATA_RECORD_BY_DTC_NUMBER: u8 = 0x06;
+
+/// Report type sub-function of `ReadDTCInformation` reporting all supported
+/// DTCs.
+pub const REPORT_SUPPORTED_DTC: u8 = 0x0A;
+
+/// A `ReadDTCInformation` request of one of the supported report types.
+///
+/// Converts into the [`UdsRequest`] sent to the server.
+///
+/// ```rust
+/// use doip_definitions::uds::{DtcReportRequest, DtcStatus, UdsRequest};
+///
+/// let request: UdsRequest = DtcReportRequest::DtcByStatusMask {
+///     status_mask: DtcStatus(DtcStatus::CONFIRMED_DTC),
+/// }
+/// .into();
+///
+/// assert_eq!(Vec::<u8>::from(request), vec![0x19, 0x02, 0x08]);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DtcReportRequest {
+    /// Requests the number of DTCs with any status bit of `status_mask` set
+    NumberOfDtcByStatusMask {
+        /// The status bits to match
+        status_mask: DtcStatus,
+    },
+
+    /// Requests the DTCs with any status bit of `status_mask` set
+    DtcByStatusMask {
+        /// The status bits to match
+        status_mask: DtcStatus,
+    },
+
+    /// Requests a snapshot record of `dtc`, `0xFF` requesting all records
+    SnapshotRecordByDtcNumber {
+        /// The DTC
+        dtc: Dtc,
+
+        /// The snapshot record number
+        record_number: u8,
+    },
+
+    /// Requests an extended data record of `dtc`, `0xFF` requesting all
+    /// records
+    ExtendedDataRecordByDtcNumber {
+        /// The DTC
+        dtc: Dtc,
+
+        /// The extended data record number
+        record_number: u8,
+    },
+
+    /// Requests all DTCs supported by the server
+    SupportedDtc,
+}
+
+impl From<DtcReportRequest> for UdsRequest {
+    fn from(value: DtcReportRequest) -> Self {
+        let dtc_record = |dtc: Dtc, record_number: u8| {
+            let mut record = dtc.0.to_be_bytes()[1..].to_vec();
+            record.push(record_number);
+            record
+        };
+
+        let (report_type, record) = match value {
+            DtcReportRequest::NumberOfDtcByStatusMask { status_mask } => {
+                (REPORT_NUMBER_OF_DTC_BY_STATUS_MASK, vec![status_mask.0])
+            }
+            DtcReportRequest::DtcByStatusMask { status_mask } => {
+                (REPORT_DTC_BY_STATUS_MASK, vec![status_mask.0])
+            }
+            DtcReportRequest::SnapshotRecordByDtcNumber { dtc, record_number } => (
+                REPORT_DTC_SNAPSHOT_RECORD_BY_DTC_NUMBER,
+                dtc_record(dtc, record_number),
+            ),
+            DtcReportRequest::ExtendedDataRecordByDtcNumber { dtc, record_number } => (
+                REPORT_DTC_EXT_DATA_RECORD_BY_DTC_NUMBER,
+                dtc_record(dtc, record_number),
+            ),
+            DtcReportRequest::SupportedDtc => (REPORT_SUPPORTED_DTC, Vec::new()),
+        };
+
+        UdsRequest::ReadDtcInformation {
+            report_type,
+            record,
+        }
+    }
+}
+
+/// A `ReadDTCInformation` response of one of the supported report types.
+///
+/// Decodes the UDS message of a `DiagnosticMessage`. Snapshot and extended data
+/// records are kept as raw bytes as their layout is specific to the server.
+///
+/// ```rust
+/// use doip_definitions::uds::{Dtc, DtcReport};
+///
+/// let report = DtcReport::try_from([0x59, 0x02, 0xFF, 0x01, 0x23, 0x00, 0x09].as_slice())?;
+///
+/// let DtcReport::DtcByStatusMask { records, .. } = report else {
+///     unreachable!();
+/// };
+/// assert_eq!(records[0].dtc, Dtc(0x01_23_00));
+/// assert_eq!(
+///     format!("{} {}", records[0].dtc, records[0].status),
+///     "P0123-00 testFailed | confirmedDTC"
+/// );
+/// # Ok::<(), doip_definitions::error::Error>(())
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DtcReport {
+    /// The number of DTCs matching the status mask
+    NumberOfDtcByStatusMask {
+        /// The status bits supported by the server
+        status_availability_mask: DtcStatus,
+
+        /// The format of the DTCs, e.g. `0x01` for ISO 14229-1
+        format_identifier: u8,
+
+        /// The number of matching DTCs
+        count: u16,
+    },
+
+    /// The DTCs matching the status mask
+    DtcByStatusMask {
+        /// The status bits supported by the server
+        status_availability_mask: DtcStatus,
+
+        /// The matching DTCs
+        records: Vec<DtcRecord>,
+    },
+
+    /// The snapshot records of a DTC
+    SnapshotRecordByDtcNumber {
+        /// The DTC and its status
+        record: DtcRecord,
+
+        /// Each snapshot record number followed by the number of data
+        /// identifiers and their data records
+        snapshot_records: Vec<u8>,
+    },
+
+    /// The extended data records of a DTC
+    ExtendedDataRecordByDtcNumber {
+        /// The DTC and its status
+        record: DtcRecord,
+
+        /// Each extended data record number followed by its data
+        extended_data_records: Vec<u8>,
+    },
+
+    /// All DTCs supported by the server
+    SupportedDtc {
+        /// The status bits supported by the server
+        status_availability_mask: DtcStatus,
+
+        /// The supported DTCs
+        records: Vec<DtcRecord>,
+    },
+}
+
+impl TryFrom<&[u8]> for DtcReport {
+    type Error = Error;
+
+    fn try_from(value: &[u8]) -> Result<Self> {
+        let UdsResponse::ReadDtcInformation {
+            report_type,
+            record,
+        } = UdsResponse::try_from(check_response(value)?)?
+        else {
+            return Err(Error::InvalidServiceId { value: value[0] });
+        };
+
+        let fields = Fields::new(&record, "DtcReport");
+
+        match report_type {
+            REPORT_NUMBER_OF_DTC_BY_STATUS_MASK => Ok(DtcReport::NumberOfDtcByStatusMask {
+                status_availability_mask: DtcStatus(fields.u8(0, "DTC Status Availability Mask")?),
+                format_identifier: fields.u8(1, "DTC Format Identifier")?,
+                count: fields.u16(2, "DTC Count")?,
+            }),
+            REPORT_DTC_BY_STATUS_MASK => Ok(DtcReport::DtcByStatusMask {
+                status_availability_mask: DtcStatus(fields.u8(0, "DTC Status Availability Mask")?),
+                records: dtc_records(&fields, 1)?,
+            }),
+            REPORT_DTC_SNAPSHOT_RECORD_BY_DTC_NUMBER => Ok(DtcReport::SnapshotRecordByDtcNumber {
+                record: dtc_record(&fields, 0)?,
+                snapshot_records: fields.rest(4),
+            }),
+            REPORT_DTC_EXT_DATA_RECORD_BY_DTC_NUMBER => {
+                Ok(DtcReport::ExtendedDataRecordByDtcNumber {
+                    record: dtc_record(&fields, 0)?,
+                    extended_data_records: fields.rest(4),
+                })
+            }
+            REPORT_SUPPORTED_DTC => Ok(DtcReport::SupportedDtc {
+                status_availability_mask: DtcStatus(fields.u8(0, "DTC Status Availability Mask")?),
+                records: dtc_records(&fields, 1)?,
+            }),
+            report_type => Err(Error::UnsupportedReportType { report_type }),
+        }
+    }
+}
+
+fn dtc_record(fields: &Fields, index: usize) -> Result<DtcRecord> {
+    let dtc = fields.get(index..index + 3, "DTC")?;
+
+    Ok(DtcRecord {
+        dtc: Dtc(u32::from_be_bytes([0, dtc[0], dtc[1], dtc[2]])),
+        status: DtcStatus(fields.u8(index + 3, "Status Of DTC")?),
+    })
+}
+
+fn dtc_records(fields: &Fields, index: usize) -> Result<Vec<DtcRecord>> {
+    let records = fields.rest(index);
+
+    #[allow(clippy::manual_is_multiple_of)] // `usize::is_multiple_of` needs Rust 1.87
+    if records.len() % 4 != 0 {
+        return Err(Error::OutOfBounds {
+            source: "DtcReport",
+            variable: "DTC And Status Record",
+        });
+    }
+
+    (0..records.len() / 4)
+        .map(|record| dtc_record(fields, index + record * 4))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        error::Error,
+        uds::{Dtc, DtcRecord, DtcReport, DtcReportRequest, DtcStatus, UdsRequest},
+    };
+
+    #[test]
+    fn test_dtc_report() {
+        assert_eq!(Dtc(0x01_23_00).to_string(), "P0123-00");
+        assert_eq!(Dtc(0x45_67_1C).to_string(), "C0567-1C");
+        assert_eq!(Dtc(0x9A_BC_FF).code(), "B1ABC");
+        assert_eq!(Dtc::from(0xFF_C1_00_87), Dtc(0xC1_00_87));
+
+        assert_eq!(DtcStatus(0x00).to_string(), "");
+        assert_eq!(DtcStatus(0xFF).names().len(), 8);
+        assert!(DtcStatus(0x2F).pending_dtc());
+        assert!(!DtcStatus(0x2F).warning_indicator_requested());
+
+        let request: Vec<u8> = UdsRequest::from(DtcReportRequest::ExtendedDataRecordByDtcNumber {
+            dtc: Dtc(0x01_23_00),
+            record_number: 0xFF,
+        })
+        .into();
+        assert_eq!(request, vec![0x19, 0x06, 0x01, 0x23, 0x00, 0xFF]);
+
+        assert_eq!(
+            DtcReport::try_from([0x59, 0x01, 0x7F, 0x01, 0x00, 0x02].as_slice()).unwrap(),
+            DtcReport::NumberOfDtcByStatusMask {
+                status_availability_mask: DtcStatus(0x7F),
+                format_identifier: 0x01,
+                count: 2,
+            }
+        );
+
+        assert_eq!(
+            DtcReport::try_from(
+                [0x59, 0x0A, 0xFF, 0xC1, 0x00, 0x87, 0x00, 0x01, 0x23, 0x00, 0x2F].as_slice()
+            )
+            .unwrap(),
+            DtcReport::SupportedDtc {
+                status_availability_mask: DtcStatus(0xFF),
+                records: vec![
+                    DtcRecord {
+                        dtc: Dtc(0xC1_00_87),
+                        status: DtcStatus(0x00),
+                    },
+                    DtcRecord {
+                        dtc: Dtc(0x01_23_00),
+                        status: DtcStatus(0x2F),
+                    },
+                ],
+            }
+        );
+
+        assert_eq!(
+            DtcReport::try_from(
+                [0x59, 0x04, 0x01, 0x23, 0x00, 0x09, 0x01, 0x01, 0xF1, 0x90, 0x41].as_slice()
+            )
+            .unwrap(),
+            DtcReport::SnapshotRecordByDtcNumber {
+                record: DtcRecord {
+                    dtc: Dtc(0x01_23_00),
+                    status: DtcStatus(0x09),
+                },
+                snapshot_records: vec![0x01, 0x01, 0xF1, 0x90, 0x41],
+            }
+        );
+
+        assert_eq!(
+            DtcReport::try_from([0x59, 0x06, 0x01, 0x23, 0x00, 0x09, 0x01, 0x05].as_slice())
+                .unwrap(),
+            DtcReport::ExtendedDataRecordByDtcNumber {
+                record: DtcRecord {
+                    dtc: Dtc(0x01_23_00),
+                    status: DtcStatus(0x09),
+                },
+                extended_data_records: vec![0x01, 0x05],
+            }
+        );
+
+        assert!(matches!(
+            DtcReport::try_from([0x59, 0x02, 0xFF, 0x01, 0x23].as_slice()),
+            Err(Error::OutOfBounds { .. })
+        ));
+        assert!(matches!(
+            DtcReport::try_from([0x59, 0x14, 0x00].as_slice()),
+            Err(Error::UnsupportedReportType { report_type: 0x14 })
+        ));
+        assert!(matches!(
+            DtcReport::try_from([0x7F, 0x19, 0x31].as_slice()),
+            Err(Error::NegativeResponse { .. })
+        ));
+    }
+}
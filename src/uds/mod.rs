@@ -0,0 +1,284 @@
+// region:      --- Modules
+
+mod data_identifier;
+#[cfg(feature = "description")]
+mod description;
+mod dtc;
+mod negative_response_code;
+mod programming;
+mod request;
+mod response;
+mod routine;
+mod security_access;
+mod service_id;
+mod sub_function;
+
+// -- Flatten
+
+pub use data_identifier::*;
+#[cfg(feature = "description")]
+pub use description::*;
+pub use dtc::*;
+pub use negative_response_code::*;
+pub use programming::*;
+pub use request::*;
+pub use response::*;
+pub use routine::*;
+pub use security_access::*;
+pub use service_id::*;
+pub use sub_function::*;
+
+// endregion:      --- Modules
+
+use core::ops::Range;
+
+use crate::error::{Error, Result};
+
+/// Bit of the sub-function byte suppressing the positive response of the server.
+pub const SUPPRESS_POSITIVE_RESPONSE: u8 = 0x80;
+
+/// Added to the request SID to form the SID of the positive response.
+pub const POSITIVE_RESPONSE_OFFSET: u8 = 0x40;
+
+/// SID of a negative response.
+pub const NEGATIVE_RESPONSE_SID: u8 = 0x7F;
+
+/// Reads the fields of a UDS message, naming `source` in the errors.
+#[derive(Debug)]
+struct Fields<'a> {
+    bytes: &'a [u8],
+    source: &'static str,
+}
+
+impl<'a> Fields<'a> {
+    fn new(bytes: &'a [u8], source: &'static str) -> Self {
+        Self { bytes, source }
+    }
+
+    fn get(&self, range: Range<usize>, variable: &'static str) -> Result<&'a [u8]> {
+        self.bytes.get(range).ok_or(Error::OutOfBounds {
+            source: self.source,
+            variable,
+        })
+    }
+
+    fn u8(&self, index: usize, variable: &'static str) -> Result<u8> {
+        Ok(self.get(index..index + 1, variable)?[0])
+    }
+
+    fn u16(&self, index: usize, variable: &'static str) -> Result<u16> {
+        Ok(u16::from_be_bytes(
+            self.get(index..index + 2, variable)?.try_into()?,
+        ))
+    }
+
+    /// Returns the sub-function at index 1 without and with the suppress
+    /// positive response bit.
+    fn sub_function(&self) -> Result<(u8, bool)> {
+        let sub_function = self.u8(1, "Sub Function")?;
+
+        Ok((
+            sub_function & !SUPPRESS_POSITIVE_RESPONSE,
+            sub_function & SUPPRESS_POSITIVE_RESPONSE != 0,
+        ))
+    }
+
+    /// Reads a big endian integer of 1 to 8 bytes.
+    fn uint(&self, index: usize, length: usize, variable: &'static str) -> Result<u64> {
+        if !(1..=8).contains(&length) {
+            return Err(Error::OutOfBounds {
+                source: self.source,
+                variable,
+            });
+        }
+
+        Ok(self
+            .get(index..index + length, variable)?
+            .iter()
+            .fold(0, |value, byte| value << 8 | u64::from(*byte)))
+    }
+
+    /// Returns the bytes from `index` to the end, which may be empty.
+    fn rest(&self, index: usize) -> Vec<u8> {
+        self.bytes.get(index..).unwrap_or_default().to_vec()
+    }
+}
+
+/// Returns the low `length` bytes of `value`, big endian, `length` being
+/// clamped to 1 to 8 bytes.
+fn uint_bytes(value: u64, length: usize) -> Vec<u8> {
+    value.to_be_bytes()[8 - length.clamp(1, 8)..].to_vec()
+}
+
+/// Returns the number of bytes needed to encode `value`, at least 1.
+fn uint_length(value: u64) -> usize {
+    8 - (value.leading_zeros() as usize / 8).min(7)
+}
+
+fn sub_function(value: u8, suppress_positive_response: bool) -> u8 {
+    if suppress_positive_response {
+        value | SUPPRESS_POSITIVE_RESPONSE
+    } else {
+        value
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        error::Error,
+        uds::{
+            CommunicationControlType, NegativeResponseCode, ResetType, RoutineControlType,
+            SessionType, UdsRequest, UdsResponse,
+        },
+    };
+
+    #[test]
+    fn test_request_roundtrip() {
+        let requests = [
+            (
+                vec![0x10, 0x83],
+                UdsRequest::DiagnosticSessionControl {
+                    session_type: SessionType::ExtendedDiagnostic,
+                    suppress_positive_response: true,
+                },
+            ),
+            (
+                vec![0x11, 0x01],
+                UdsRequest::EcuReset {
+                    reset_type: ResetType::HardReset,
+                    suppress_positive_response: false,
+                },
+            ),
+            (
+                vec![0x27, 0x02, 0xAA, 0xBB],
+                UdsRequest::SecurityAccess {
+                    security_access_type: 0x02,
+                    data: vec![0xAA, 0xBB],
+                },
+            ),
+            (
+                vec![0x28, 0x04, 0x01, 0x12, 0x34],
+                UdsRequest::CommunicationControl {
+                    control_type:
+                        CommunicationControlType::EnableRxAndDisableTxWithEnhancedAddressInformation,
+                    communication_type: 0x01,
+                    node_identification_number: Some(0x1234),
+                    suppress_positive_response: false,
+                },
+            ),
+            (
+                vec![0x3E, 0x80],
+                UdsRequest::TesterPresent {
+                    suppress_positive_response: true,
+                },
+            ),
+            (
+                vec![0x22, 0xF1, 0x90, 0xF1, 0x8C],
+                UdsRequest::ReadDataByIdentifier {
+                    identifiers: vec![0xF190, 0xF18C],
+                },
+            ),
+            (
+                vec![0x2E, 0xF1, 0x98, 0x01],
+                UdsRequest::WriteDataByIdentifier {
+                    identifier: 0xF198,
+                    data: vec![0x01],
+                },
+            ),
+            (
+                vec![0x31, 0x01, 0xFF, 0x00, 0x01],
+                UdsRequest::RoutineControl {
+                    control_type: RoutineControlType::StartRoutine,
+                    identifier: 0xFF00,
+                    option_record: vec![0x01],
+                    suppress_positive_response: false,
+                },
+            ),
+            (
+                vec![0x19, 0x02, 0x08],
+                UdsRequest::ReadDtcInformation {
+                    report_type: 0x02,
+                    record: vec![0x08],
+                },
+            ),
+            (
+                vec![0x14, 0xFF, 0xFF, 0xFF],
+                UdsRequest::ClearDiagnosticInformation {
+                    group_of_dtc: 0x00FF_FFFF,
+                    memory_selection: None,
+                },
+            ),
+        ];
+
+        for (bytes, request) in requests {
+            assert_eq!(UdsRequest::try_from(bytes.as_slice()).unwrap(), request);
+            assert_eq!(Vec::<u8>::from(request), bytes);
+        }
+    }
+
+    #[test]
+    fn test_response_roundtrip() {
+        let responses = [
+            (
+                vec![0x50, 0x03, 0x00, 0x32, 0x01, 0xF4],
+                UdsResponse::DiagnosticSessionControl {
+                    session_type: SessionType::ExtendedDiagnostic,
+                    p2_server_max: 50,
+                    p2_star_server_max: 500,
+                },
+            ),
+            (
+                vec![0x67, 0x01, 0x12, 0x34],
+                UdsResponse::SecurityAccess {
+                    security_access_type: 0x01,
+                    seed: vec![0x12, 0x34],
+                },
+            ),
+            (vec![0x7E, 0x00], UdsResponse::TesterPresent),
+            (
+                vec![0x62, 0xF1, 0x90, 0x57],
+                UdsResponse::ReadDataByIdentifier {
+                    records: vec![0xF1, 0x90, 0x57],
+                },
+            ),
+            (
+                vec![0x71, 0x03, 0xFF, 0x00, 0x02],
+                UdsResponse::RoutineControl {
+                    control_type: RoutineControlType::RequestRoutineResults,
+                    identifier: 0xFF00,
+                    status_record: vec![0x02],
+                },
+            ),
+            (vec![0x54], UdsResponse::ClearDiagnosticInformation),
+            (
+                vec![0x7F, 0x22, 0x31],
+                UdsResponse::Negative {
+                    service_id: 0x22,
+                    response_code: NegativeResponseCode::RequestOutOfRange,
+                },
+            ),
+        ];
+
+        for (bytes, response) in responses {
+            assert_eq!(UdsResponse::try_from(bytes.as_slice()).unwrap(), response);
+            assert_eq!(Vec::<u8>::from(response), bytes);
+        }
+    }
+
+    #[test]
+    fn test_invalid_messages() {
+        assert!(matches!(
+            UdsRequest::try_from([0x23, 0x00].as_slice()),
+            Err(Error::InvalidServiceId { value: 0x23 })
+        ));
+        assert!(matches!(
+            UdsRequest::try_from([0x22, 0xF1].as_slice()),
+            Err(Error::OutOfBounds { .. })
+        ));
+        assert!(matches!(
+            UdsResponse::try_from([0x50, 0x03].as_slice()),
+            Err(Error::OutOfBounds { .. })
+        ));
+    }
+}
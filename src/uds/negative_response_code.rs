@@ -0,0 +1,450 @@
+use crate::{
+    error::{Error, Result},
+    uds::{Fields, NEGATIVE_RESPONSE_SID},
+};
+
+/// Negative response codes of ISO 14229-1, sent in the third byte of a negative
+/// response `7F <SID> <NRC>`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NegativeResponseCode {
+    /// General Reject
+    GeneralReject,
+
+    /// Service Not Supported
+    ServiceNotSupported,
+
+    /// Sub Function Not Supported
+    SubFunctionNotSupported,
+
+    /// Incorrect Message Length Or Invalid Format
+    IncorrectMessageLengthOrInvalidFormat,
+
+    /// Response Too Long
+    ResponseTooLong,
+
+    /// Busy Repeat Request
+    BusyRepeatRequest,
+
+    /// Conditions Not Correct
+    ConditionsNotCorrect,
+
+    /// Request Sequence Error
+    RequestSequenceError,
+
+    /// No Response From Subnet Component
+    NoResponseFromSubnetComponent,
+
+    /// Failure Prevents Execution Of Requested Action
+    FailurePreventsExecutionOfRequestedAction,
+
+    /// Request Out Of Range
+    RequestOutOfRange,
+
+    /// Security Access Denied
+    SecurityAccessDenied,
+
+    /// Authentication Required
+    AuthenticationRequired,
+
+    /// Invalid Key
+    InvalidKey,
+
+    /// Exceeded Number Of Attempts
+    ExceededNumberOfAttempts,
+
+    /// Required Time Delay Not Expired
+    RequiredTimeDelayNotExpired,
+
+    /// Secure Data Transmission Required
+    SecureDataTransmissionRequired,
+
+    /// Secure Data Transmission Not Allowed
+    SecureDataTransmissionNotAllowed,
+
+    /// Secure Data Verification Failed
+    SecureDataVerificationFailed,
+
+    /// Certificate Verification Failed Invalid Time Period
+    CertificateVerificationFailedInvalidTimePeriod,
+
+    /// Certificate Verification Failed Invalid Signature
+    CertificateVerificationFailedInvalidSignature,
+
+    /// Certificate Verification Failed Invalid Chain Of Trust
+    CertificateVerificationFailedInvalidChainOfTrust,
+
+    /// Certificate Verification Failed Invalid Type
+    CertificateVerificationFailedInvalidType,
+
+    /// Certificate Verification Failed Invalid Format
+    CertificateVerificationFailedInvalidFormat,
+
+    /// Certificate Verification Failed Invalid Content
+    CertificateVerificationFailedInvalidContent,
+
+    /// Certificate Verification Failed Invalid Scope
+    CertificateVerificationFailedInvalidScope,
+
+    /// Certificate Verification Failed Invalid Certificate
+    CertificateVerificationFailedInvalidCertificate,
+
+    /// Ownership Verification Failed
+    OwnershipVerificationFailed,
+
+    /// Challenge Calculation Failed
+    ChallengeCalculationFailed,
+
+    /// Setting Access Rights Failed
+    SettingAccessRightsFailed,
+
+    /// Session Key Creation Derivation Failed
+    SessionKeyCreationDerivationFailed,
+
+    /// Configuration Data Usage Failed
+    ConfigurationDataUsageFailed,
+
+    /// De Authentication Failed
+    DeAuthenticationFailed,
+
+    /// Upload Download Not Accepted
+    UploadDownloadNotAccepted,
+
+    /// Transfer Data Suspended
+    TransferDataSuspended,
+
+    /// General Programming Failure
+    GeneralProgrammingFailure,
+
+    /// Wrong Block Sequence Counter
+    WrongBlockSequenceCounter,
+
+    /// Request Correctly Received Response Pending
+    RequestCorrectlyReceivedResponsePending,
+
+    /// Sub Function Not Supported In Active Session
+    SubFunctionNotSupportedInActiveSession,
+
+    /// Service Not Supported In Active Session
+    ServiceNotSupportedInActiveSession,
+
+    /// RPM Too High
+    RpmTooHigh,
+
+    /// RPM Too Low
+    RpmTooLow,
+
+    /// Engine Is Running
+    EngineIsRunning,
+
+    /// Engine Is Not Running
+    EngineIsNotRunning,
+
+    /// Engine Run Time Too Low
+    EngineRunTimeTooLow,
+
+    /// Temperature Too High
+    TemperatureTooHigh,
+
+    /// Temperature Too Low
+    TemperatureTooLow,
+
+    /// Vehicle Speed Too High
+    VehicleSpeedTooHigh,
+
+    /// Vehicle Speed Too Low
+    VehicleSpeedTooLow,
+
+    /// Throttle Pedal Too High
+    ThrottlePedalTooHigh,
+
+    /// Throttle Pedal Too Low
+    ThrottlePedalTooLow,
+
+    /// Transmission Range Not In Neutral
+    TransmissionRangeNotInNeutral,
+
+    /// Transmission Range Not In Gear
+    TransmissionRangeNotInGear,
+
+    /// Brake Switches Not Closed
+    BrakeSwitchesNotClosed,
+
+    /// Shifter Lever Not In Park
+    ShifterLeverNotInPark,
+
+    /// Torque Converter Clutch Locked
+    TorqueConverterClutchLocked,
+
+    /// Voltage Too High
+    VoltageTooHigh,
+
+    /// Voltage Too Low
+    VoltageTooLow,
+
+    /// Resource Temporarily Not Available
+    ResourceTemporarilyNotAvailable,
+
+    /// Vehicle Manufacturer Specific Conditions Not Correct, `0xF0` to `0xFE`
+    VehicleManufacturerSpecific(u8),
+
+    /// ISO SAE Reserved
+    Reserved(u8),
+}
+
+impl From<u8> for NegativeResponseCode {
+    fn from(value: u8) -> Self {
+        match value {
+            0x10 => NegativeResponseCode::GeneralReject,
+            0x11 => NegativeResponseCode::ServiceNotSupported,
+            0x12 => NegativeResponseCode::SubFunctionNotSupported,
+            0x13 => NegativeResponseCode::IncorrectMessageLengthOrInvalidFormat,
+            0x14 => NegativeResponseCode::ResponseTooLong,
+            0x21 => NegativeResponseCode::BusyRepeatRequest,
+            0x22 => NegativeResponseCode::ConditionsNotCorrect,
+            0x24 => NegativeResponseCode::RequestSequenceError,
+            0x25 => NegativeResponseCode::NoResponseFromSubnetComponent,
+            0x26 => NegativeResponseCode::FailurePreventsExecutionOfRequestedAction,
+            0x31 => NegativeResponseCode::RequestOutOfRange,
+            0x33 => NegativeResponseCode::SecurityAccessDenied,
+            0x34 => NegativeResponseCode::AuthenticationRequired,
+            0x35 => NegativeResponseCode::InvalidKey,
+            0x36 => NegativeResponseCode::ExceededNumberOfAttempts,
+            0x37 => NegativeResponseCode::RequiredTimeDelayNotExpired,
+            0x38 => NegativeResponseCode::SecureDataTransmissionRequired,
+            0x39 => NegativeResponseCode::SecureDataTransmissionNotAllowed,
+            0x3A => NegativeResponseCode::SecureDataVerificationFailed,
+            0x50 => NegativeResponseCode::CertificateVerificationFailedInvalidTimePeriod,
+            0x51 => NegativeResponseCode::CertificateVerificationFailedInvalidSignature,
+            0x52 => NegativeResponseCode::CertificateVerificationFailedInvalidChainOfTrust,
+            0x53 => NegativeResponseCode::CertificateVerificationFailedInvalidType,
+            0x54 => NegativeResponseCode::CertificateVerificationFailedInvalidFormat,
+            0x55 => NegativeResponseCode::CertificateVerificationFailedInvalidContent,
+            0x56 => NegativeResponseCode::CertificateVerificationFailedInvalidScope,
+            0x57 => NegativeResponseCode::CertificateVerificationFailedInvalidCertificate,
+            0x58 => NegativeResponseCode::OwnershipVerificationFailed,
+            0x59 => NegativeResponseCode::ChallengeCalculationFailed,
+            0x5A => NegativeResponseCode::SettingAccessRightsFailed,
+            0x5B => NegativeResponseCode::SessionKeyCreationDerivationFailed,
+            0x5C => NegativeResponseCode::ConfigurationDataUsageFailed,
+            0x5D => NegativeResponseCode::DeAuthenticationFailed,
+            0x70 => NegativeResponseCode::UploadDownloadNotAccepted,
+            0x71 => NegativeResponseCode::TransferDataSuspended,
+            0x72 => NegativeResponseCode::GeneralProgrammingFailure,
+            0x73 => NegativeResponseCode::WrongBlockSequenceCounter,
+            0x78 => NegativeResponseCode::RequestCorrectlyReceivedResponsePending,
+            0x7E => NegativeResponseCode::SubFunctionNotSupportedInActiveSession,
+            0x7F => NegativeResponseCode::ServiceNotSupportedInActiveSession,
+            0x81 => NegativeResponseCode::RpmTooHigh,
+            0x82 => NegativeResponseCode::RpmTooLow,
+            0x83 => NegativeResponseCode::EngineIsRunning,
+            0x84 => NegativeResponseCode::EngineIsNotRunning,
+            0x85 => NegativeResponseCode::EngineRunTimeTooLow,
+            0x86 => NegativeResponseCode::TemperatureTooHigh,
+            0x87 => NegativeResponseCode::TemperatureTooLow,
+            0x88 => NegativeResponseCode::VehicleSpeedTooHigh,
+            0x89 => NegativeResponseCode::VehicleSpeedTooLow,
+            0x8A => NegativeResponseCode::ThrottlePedalTooHigh,
+            0x8B => NegativeResponseCode::ThrottlePedalTooLow,
+            0x8C => NegativeResponseCode::TransmissionRangeNotInNeutral,
+            0x8D => NegativeResponseCode::TransmissionRangeNotInGear,
+            0x8F => NegativeResponseCode::BrakeSwitchesNotClosed,
+            0x90 => NegativeResponseCode::ShifterLeverNotInPark,
+            0x91 => NegativeResponseCode::TorqueConverterClutchLocked,
+            0x92 => NegativeResponseCode::VoltageTooHigh,
+            0x93 => NegativeResponseCode::VoltageTooLow,
+            0x94 => NegativeResponseCode::ResourceTemporarilyNotAvailable,
+            v @ 0xF0..=0xFE => NegativeResponseCode::VehicleManufacturerSpecific(v),
+            v => NegativeResponseCode::Reserved(v),
+        }
+    }
+}
+
+impl From<NegativeResponseCode> for u8 {
+    fn from(value: NegativeResponseCode) -> Self {
+        match value {
+            NegativeResponseCode::GeneralReject => 0x10,
+            NegativeResponseCode::ServiceNotSupported => 0x11,
+            NegativeResponseCode::SubFunctionNotSupported => 0x12,
+            NegativeResponseCode::IncorrectMessageLengthOrInvalidFormat => 0x13,
+            NegativeResponseCode::ResponseTooLong => 0x14,
+            NegativeResponseCode::BusyRepeatRequest => 0x21,
+            NegativeResponseCode::ConditionsNotCorrect => 0x22,
+            NegativeResponseCode::RequestSequenceError => 0x24,
+            NegativeResponseCode::NoResponseFromSubnetComponent => 0x25,
+            NegativeResponseCode::FailurePreventsExecutionOfRequestedAction => 0x26,
+            NegativeResponseCode::RequestOutOfRange => 0x31,
+            NegativeResponseCode::SecurityAccessDenied => 0x33,
+            NegativeResponseCode::AuthenticationRequired => 0x34,
+            NegativeResponseCode::InvalidKey => 0x35,
+            NegativeResponseCode::ExceededNumberOfAttempts => 0x36,
+            NegativeResponseCode::RequiredTimeDelayNotExpired => 0x37,
+            NegativeResponseCode::SecureDataTransmissionRequired => 0x38,
+            NegativeResponseCode::SecureDataTransmissionNotAllowed => 0x39,
+            NegativeResponseCode::SecureDataVerificationFailed => 0x3A,
+            NegativeResponseCode::CertificateVerificationFailedInvalidTimePeriod => 0x50,
+            NegativeResponseCode::CertificateVerificationFailedInvalidSignature => 0x51,
+            NegativeResponseCode::CertificateVerificationFailedInvalidChainOfTrust => 0x52,
+            NegativeResponseCode::CertificateVerificationFailedInvalidType => 0x53,
+            NegativeResponseCode::CertificateVerificationFailedInvalidFormat => 0x54,
+            NegativeResponseCode::CertificateVerificationFailedInvalidContent => 0x55,
+            NegativeResponseCode::CertificateVerificationFailedInvalidScope => 0x56,
+            NegativeResponseCode::CertificateVerificationFailedInvalidCertificate => 0x57,
+            NegativeResponseCode::OwnershipVerificationFailed => 0x58,
+            NegativeResponseCode::ChallengeCalculationFailed => 0x59,
+            NegativeResponseCode::SettingAccessRightsFailed => 0x5A,
+            NegativeResponseCode::SessionKeyCreationDerivationFailed => 0x5B,
+            NegativeResponseCode::ConfigurationDataUsageFailed => 0x5C,
+            NegativeResponseCode::DeAuthenticationFailed => 0x5D,
+            NegativeResponseCode::UploadDownloadNotAccepted => 0x70,
+            NegativeResponseCode::TransferDataSuspended => 0x71,
+            NegativeResponseCode::GeneralProgrammingFailure => 0x72,
+            NegativeResponseCode::WrongBlockSequenceCounter => 0x73,
+            NegativeResponseCode::RequestCorrectlyReceivedResponsePending => 0x78,
+            NegativeResponseCode::SubFunctionNotSupportedInActiveSession => 0x7E,
+            NegativeResponseCode::ServiceNotSupportedInActiveSession => 0x7F,
+            NegativeResponseCode::RpmTooHigh => 0x81,
+            NegativeResponseCode::RpmTooLow => 0x82,
+            NegativeResponseCode::EngineIsRunning => 0x83,
+            NegativeResponseCode::EngineIsNotRunning => 0x84,
+            NegativeResponseCode::EngineRunTimeTooLow => 0x85,
+            NegativeResponseCode::TemperatureTooHigh => 0x86,
+            NegativeResponseCode::TemperatureTooLow => 0x87,
+            NegativeResponseCode::VehicleSpeedTooHigh => 0x88,
+            NegativeResponseCode::VehicleSpeedTooLow => 0x89,
+            NegativeResponseCode::ThrottlePedalTooHigh => 0x8A,
+            NegativeResponseCode::ThrottlePedalTooLow => 0x8B,
+            NegativeResponseCode::TransmissionRangeNotInNeutral => 0x8C,
+            NegativeResponseCode::TransmissionRangeNotInGear => 0x8D,
+            NegativeResponseCode::BrakeSwitchesNotClosed => 0x8F,
+            NegativeResponseCode::ShifterLeverNotInPark => 0x90,
+            NegativeResponseCode::TorqueConverterClutchLocked => 0x91,
+            NegativeResponseCode::VoltageTooHigh => 0x92,
+            NegativeResponseCode::VoltageTooLow => 0x93,
+            NegativeResponseCode::ResourceTemporarilyNotAvailable => 0x94,
+            NegativeResponseCode::VehicleManufacturerSpecific(v)
+            | NegativeResponseCode::Reserved(v) => v,
+        }
+    }
+}
+
+/// A negative response `7F <SID> <NRC>` of a UDS server.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NegativeResponse {
+    /// Service identifier of the rejected request
+    pub service_id: u8,
+
+    /// Reason of the rejection
+    pub response_code: NegativeResponseCode,
+}
+
+impl TryFrom<&[u8]> for NegativeResponse {
+    type Error = Error;
+
+    fn try_from(value: &[u8]) -> Result<Self> {
+        let fields = Fields::new(value, "NegativeResponse");
+        let response_id = fields.u8(0, "Service Id")?;
+
+        if response_id != NEGATIVE_RESPONSE_SID {
+            return Err(Error::InvalidServiceId { value: response_id });
+        }
+
+        Ok(NegativeResponse {
+            service_id: fields.u8(1, "Request Service Id")?,
+            response_code: fields.u8(2, "Response Code")?.into(),
+        })
+    }
+}
+
+impl From<NegativeResponse> for [u8; 3] {
+    fn from(value: NegativeResponse) -> Self {
+        [
+            NEGATIVE_RESPONSE_SID,
+            value.service_id,
+            value.response_code.into(),
+        ]
+    }
+}
+
+impl From<NegativeResponse> for Error {
+    fn from(value: NegativeResponse) -> Self {
+        Error::NegativeResponse {
+            service_id: value.service_id,
+            response_code: value.response_code,
+        }
+    }
+}
+
+/// Returns the UDS `message` of a `DiagnosticMessage` unchanged when it is a
+/// positive response.
+///
+/// ```rust
+/// use doip_definitions::{error::Error, uds::{check_response, NegativeResponseCode}};
+///
+/// assert!(matches!(
+///     check_response(&[0x7F, 0x22, 0x31]),
+///     Err(Error::NegativeResponse {
+///         service_id: 0x22,
+///         response_code: NegativeResponseCode::RequestOutOfRange,
+///     })
+/// ));
+/// assert_eq!(check_response(&[0x62, 0xF1, 0x90])?, &[0x62, 0xF1, 0x90]);
+/// # Ok::<(), Error>(())
+/// ```
+///
+/// # Errors
+///
+/// Returns [`Error::NegativeResponse`] for a negative response and
+/// [`Error::OutOfBounds`] for an empty or truncated message.
+pub fn check_response(message: &[u8]) -> Result<&[u8]> {
+    match message.first() {
+        Some(&NEGATIVE_RESPONSE_SID) => Err(NegativeResponse::try_from(message)?.into()),
+        Some(_) => Ok(message),
+        None => Err(Error::OutOfBounds {
+            source: "UdsResponse",
+            variable: "Service Id",
+        }),
+    }
+}
+
+/// Returns true when the UDS `message` of a `DiagnosticMessage` is the negative
+/// response `requestCorrectlyReceived-ResponsePending`, announcing that the
+/// final response follows within `P2*Server_max`.
+///
+/// ```rust
+/// use doip_definitions::uds::is_response_pending;
+///
+/// assert!(is_response_pending(&[0x7F, 0x31, 0x78]));
+/// assert!(!is_response_pending(&[0x7F, 0x31, 0x31]));
+/// assert!(!is_response_pending(&[0x71, 0x01, 0xFF, 0x00]));
+/// ```
+#[must_use]
+pub fn is_response_pending(message: &[u8]) -> bool {
+    NegativeResponse::try_from(message).is_ok_and(|negative| {
+        negative.response_code == NegativeResponseCode::RequestCorrectlyReceivedResponsePending
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::uds::NegativeResponseCode;
+
+    #[test]
+    fn test_negative_response_codes() {
+        for value in 0..=u8::MAX {
+            assert_eq!(u8::from(NegativeResponseCode::from(value)), value);
+        }
+
+        assert_eq!(
+            NegativeResponseCode::from(0x78),
+            NegativeResponseCode::RequestCorrectlyReceivedResponsePending
+        );
+        assert_eq!(
+            NegativeResponseCode::from(0xF3),
+            NegativeResponseCode::VehicleManufacturerSpecific(0xF3)
+        );
+        assert_eq!(
+            NegativeResponseCode::from(0x15),
+            NegativeResponseCode::Reserved(0x15)
+        );
+    }
+}
@@ -0,0 +1,471 @@
+use std::io::Read;
+
+use crate::{
+    error::{Error, Result},
+    uds::{is_response_pending, uint_length, UdsRequest, UdsResponse},
+};
+
+/// Bytes of a `TransferData` request preceding the data, the service
+/// identifier and the block sequence counter.
+const TRANSFER_DATA_HEADER_LEN: usize = 2;
+
+/// Parameters of a [`ProgrammingSequence`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProgrammingConfig {
+    /// Compression method in the high and encryption method in the low nibble,
+    /// sent with `RequestDownload`
+    pub data_format_identifier: u8,
+
+    /// Length in bytes of the memory size in the high and of the memory address
+    /// in the low nibble, sent with `RequestDownload`
+    pub address_and_length_format_identifier: u8,
+
+    /// Largest UDS message the `DoIP` entity accepts, the `max_data_size` of its
+    /// `EntityStatusResponse`
+    pub max_data_size: Option<u32>,
+}
+
+impl Default for ProgrammingConfig {
+    /// Constructs a `ProgrammingConfig` with:
+    /// - Plain, uncompressed and unencrypted data
+    /// - A 4 byte memory address and a 4 byte memory size
+    /// - No limit besides the `maxNumberOfBlockLength` of the server
+    fn default() -> Self {
+        Self {
+            data_format_identifier: 0x00,
+            address_and_length_format_identifier: 0x44,
+            max_data_size: None,
+        }
+    }
+}
+
+/// Bytes downloaded by a [`ProgrammingSequence`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    /// Bytes acknowledged by the server
+    pub transferred: u64,
+
+    /// Bytes of the whole download
+    pub total: u64,
+}
+
+#[derive(Debug)]
+enum State {
+    Idle,
+    AwaitingDownload,
+    Transferring { length: usize },
+    AwaitingExit,
+    Finished(Option<Result<()>>),
+}
+
+/// Downloads data into the memory of a server with `RequestDownload`,
+/// `TransferData` and `RequestTransferExit`.
+///
+/// The data is read from `source` in blocks of the `maxNumberOfBlockLength`
+/// returned by the server, further capped by the `max_data_size` of the
+/// [`ProgrammingConfig`], and sent with a block sequence counter starting at 1
+/// and wrapping from `0xFF` to `0x00`.
+///
+/// The sequence performs no IO itself. [`ProgrammingSequence::poll_transmit`]
+/// returns the UDS requests to send to the target and their responses are passed
+/// to [`ProgrammingSequence::handle_response`], ignoring
+/// `requestCorrectlyReceived-ResponsePending` responses until the final one
+/// arrives. [`ProgrammingSequence::poll_progress`] reports each acknowledged
+/// block and [`ProgrammingSequence::poll_result`] the outcome once.
+/// [`ProgrammingSequence::run`] drives the sequence over any blocking transport.
+///
+/// ```rust
+/// use doip_definitions::uds::{ProgrammingSequence, Progress};
+///
+/// let image = [0xAA; 10];
+/// let mut sequence = ProgrammingSequence::new(0x0800_0000, 10, image.as_slice());
+/// let mut blocks = Vec::new();
+///
+/// let result = sequence.run(
+///     |request| match request {
+///         [0x34, 0x00, 0x44, ..] => Ok(vec![0x74, 0x10, 0x06]),
+///         [0x36, counter, ..] => Ok(vec![0x76, *counter]),
+///         [0x37] => Ok(vec![0x77]),
+///         _ => Ok(vec![0x7F, request[0], 0x22]),
+///     },
+///     |progress: Progress| blocks.push(progress.transferred),
+/// );
+///
+/// assert!(result.is_ok());
+/// assert_eq!(blocks, vec![4, 8, 10]);
+/// ```
+#[derive(Debug)]
+pub struct ProgrammingSequence<R> {
+    config: ProgrammingConfig,
+    memory_address: u64,
+    memory_size: u64,
+    source: R,
+    state: State,
+    block_length: usize,
+    block_sequence_counter: u8,
+    transferred: u64,
+    transmit: Option<Vec<u8>>,
+    progress: Option<Progress>,
+}
+
+impl<R: Read> ProgrammingSequence<R> {
+    /// Creates the download of `memory_size` bytes read from `source` to
+    /// `memory_address`.
+    #[must_use]
+    pub fn new(memory_address: u64, memory_size: u64, source: R) -> Self {
+        Self {
+            config: ProgrammingConfig::default(),
+            memory_address,
+            memory_size,
+            source,
+            state: State::Idle,
+            block_length: 0,
+            block_sequence_counter: 0,
+            transferred: 0,
+            transmit: None,
+            progress: None,
+        }
+    }
+
+    /// Sets the parameters of the download.
+    #[must_use]
+    pub fn config(mut self, config: ProgrammingConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Returns the bytes acknowledged by the server so far.
+    #[must_use]
+    pub fn progress(&self) -> Progress {
+        Progress {
+            transferred: self.transferred,
+            total: self.memory_size,
+        }
+    }
+
+    /// Starts the download by requesting it from the server.
+    pub fn start(&mut self) {
+        let format = self.config.address_and_length_format_identifier;
+        let address_length = usize::from(format & 0x0F);
+        let size_length = usize::from(format >> 4);
+
+        self.transferred = 0;
+        self.block_sequence_counter = 0;
+
+        if !(uint_length(self.memory_address)..=8).contains(&address_length) {
+            self.finish(Err(Error::OutOfBounds {
+                source: "ProgrammingSequence",
+                variable: "Memory Address",
+            }));
+            return;
+        }
+
+        if !(uint_length(self.memory_size)..=8).contains(&size_length) {
+            self.finish(Err(Error::OutOfBounds {
+                source: "ProgrammingSequence",
+                variable: "Memory Size",
+            }));
+            return;
+        }
+
+        self.state = State::AwaitingDownload;
+        self.transmit = Some(
+            UdsRequest::RequestDownload {
+                data_format_identifier: self.config.data_format_identifier,
+                address_and_length_format_identifier: format,
+                memory_address: self.memory_address,
+                memory_size: self.memory_size,
+            }
+            .into(),
+        );
+    }
+
+    fn finish(&mut self, result: Result<()>) {
+        self.state = State::Finished(Some(result));
+        self.transmit = None;
+    }
+
+    /// Processes the UDS `response` of the target to the last request.
+    ///
+    /// `requestCorrectlyReceived-ResponsePending` responses are ignored.
+    pub fn handle_response(&mut self, response: &[u8]) {
+        if is_response_pending(response) || matches!(self.state, State::Idle | State::Finished(_)) {
+            return;
+        }
+
+        let decoded = match UdsResponse::try_from(response) {
+            Ok(decoded) => decoded,
+            Err(err) => return self.finish(Err(err)),
+        };
+
+        match (&self.state, decoded) {
+            (
+                State::AwaitingDownload,
+                UdsResponse::RequestDownload {
+                    max_number_of_block_length,
+                },
+            ) => self.handle_download(max_number_of_block_length),
+            (
+                State::Transferring { length },
+                UdsResponse::TransferData {
+                    block_sequence_counter,
+                    ..
+                },
+            ) => {
+                if block_sequence_counter != self.block_sequence_counter {
+                    return self.finish(Err(Error::InvalidBlockSequenceCounter {
+                        expected: self.block_sequence_counter,
+                        received: block_sequence_counter,
+                    }));
+                }
+
+                self.transferred += *length as u64;
+                self.progress = Some(self.progress());
+                self.send_block();
+            }
+            (State::AwaitingExit, UdsResponse::RequestTransferExit { .. }) => {
+                self.finish(Ok(()));
+            }
+            (
+                _,
+                UdsResponse::Negative {
+                    service_id,
+                    response_code,
+                },
+            ) => self.finish(Err(Error::NegativeResponse {
+                service_id,
+                response_code,
+            })),
+            _ => self.finish(Err(Error::UnexpectedResponse {
+                service_id: response[0],
+            })),
+        }
+    }
+
+    fn handle_download(&mut self, max_number_of_block_length: u64) {
+        let mut limit = usize::try_from(max_number_of_block_length).unwrap_or(usize::MAX);
+        if let Some(max_data_size) = self.config.max_data_size {
+            limit = limit.min(usize::try_from(max_data_size).unwrap_or(usize::MAX));
+        }
+
+        self.block_length = limit.saturating_sub(TRANSFER_DATA_HEADER_LEN);
+        if self.block_length == 0 {
+            return self.finish(Err(Error::InvalidBlockLength {
+                max_number_of_block_length,
+            }));
+        }
+
+        self.send_block();
+    }
+
+    fn send_block(&mut self) {
+        let remaining = self.memory_size - self.transferred;
+
+        if remaining == 0 {
+            self.state = State::AwaitingExit;
+            self.transmit = Some(
+                UdsRequest::RequestTransferExit {
+                    parameter_record: Vec::new(),
+                }
+                .into(),
+            );
+            return;
+        }
+
+        let length = usize::try_from(remaining).map_or(self.block_length, |remaining| {
+            remaining.min(self.block_length)
+        });
+
+        let mut data = vec![0; length];
+        if let Err(err) = self.source.read_exact(&mut data) {
+            return self.finish(Err(err.into()));
+        }
+
+        self.block_sequence_counter = self.block_sequence_counter.wrapping_add(1);
+        self.state = State::Transferring { length };
+        self.transmit = Some(
+            UdsRequest::TransferData {
+                block_sequence_counter: self.block_sequence_counter,
+                data,
+            }
+            .into(),
+        );
+    }
+
+    /// Returns the next UDS request to send to the target.
+    pub fn poll_transmit(&mut self) -> Option<Vec<u8>> {
+        self.transmit.take()
+    }
+
+    /// Returns the progress once after each acknowledged block.
+    pub fn poll_progress(&mut self) -> Option<Progress> {
+        self.progress.take()
+    }
+
+    /// Returns the outcome of the download once it finished.
+    pub fn poll_result(&mut self) -> Option<Result<()>> {
+        match &mut self.state {
+            State::Finished(result) => result.take(),
+            _ => None,
+        }
+    }
+
+    /// Runs the download, sending each request with `send` which returns the
+    /// final response of the target, and passing the progress after each
+    /// acknowledged block to `progress`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of `send`, [`Error::NegativeResponse`] when the
+    /// target rejects a request, [`Error::Io`] when `source` holds less than
+    /// the memory size, [`Error::InvalidBlockLength`] and
+    /// [`Error::InvalidBlockSequenceCounter`] for invalid responses,
+    /// [`Error::UnexpectedResponse`] when the target answers another request
+    /// and [`Error::Timeout`] when `send` returns no final response.
+    pub fn run(
+        &mut self,
+        mut send: impl FnMut(&[u8]) -> Result<Vec<u8>>,
+        mut progress: impl FnMut(Progress),
+    ) -> Result<()> {
+        self.start();
+
+        loop {
+            if let Some(current) = self.poll_progress() {
+                progress(current);
+            }
+
+            if let Some(result) = self.poll_result() {
+                return result;
+            }
+
+            if let Some(request) = self.poll_transmit() {
+                let response = send(&request)?;
+                self.handle_response(&response);
+                continue;
+            }
+
+            // `send` returned a pending response instead of the final one
+            return Err(Error::Timeout);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        error::Error,
+        uds::{ProgrammingConfig, ProgrammingSequence, Progress, UdsRequest, UdsResponse},
+    };
+
+    #[test]
+    fn test_download_roundtrip() {
+        let request = UdsRequest::RequestDownload {
+            data_format_identifier: 0x00,
+            address_and_length_format_identifier: 0x24,
+            memory_address: 0x0800_0000,
+            memory_size: 0x1000,
+        };
+        let bytes: Vec<u8> = request.clone().into();
+        assert_eq!(
+            bytes,
+            vec![0x34, 0x00, 0x24, 0x08, 0x00, 0x00, 0x00, 0x10, 0x00]
+        );
+        assert_eq!(UdsRequest::try_from(bytes.as_slice()).unwrap(), request);
+
+        let response = UdsResponse::try_from([0x74, 0x20, 0x0F, 0xFA].as_slice()).unwrap();
+        assert_eq!(
+            response,
+            UdsResponse::RequestDownload {
+                max_number_of_block_length: 0x0FFA,
+            }
+        );
+        assert_eq!(Vec::<u8>::from(response), vec![0x74, 0x20, 0x0F, 0xFA]);
+
+        assert!(matches!(
+            UdsRequest::try_from([0x34, 0x00, 0x90, 0x00].as_slice()),
+            Err(Error::OutOfBounds { .. })
+        ));
+    }
+
+    #[test]
+    fn test_programming_sequence() {
+        let image: Vec<u8> = (0..=255).cycle().take(300).collect();
+        let mut sequence =
+            ProgrammingSequence::new(0x1000, 300, image.as_slice()).config(ProgrammingConfig {
+                max_data_size: Some(3),
+                ..ProgrammingConfig::default()
+            });
+
+        sequence.start();
+        assert_eq!(
+            sequence.poll_transmit(),
+            Some(vec![
+                0x34, 0x00, 0x44, 0x00, 0x00, 0x10, 0x00, 0x00, 0x00, 0x01, 0x2C
+            ])
+        );
+        sequence.handle_response(&[0x74, 0x20, 0x0F, 0xFA]);
+
+        let mut received = Vec::new();
+        let mut counters = Vec::new();
+        while let Some(request) = sequence.poll_transmit() {
+            if request == [0x37] {
+                sequence.handle_response(&[0x7F, 0x37, 0x78]);
+                sequence.handle_response(&[0x77]);
+                break;
+            }
+
+            assert_eq!(request.len(), 3);
+            counters.push(request[1]);
+            received.push(request[2]);
+
+            sequence.handle_response(&[0x7F, 0x36, 0x78]);
+            assert!(sequence.poll_transmit().is_none());
+            sequence.handle_response(&[0x76, request[1]]);
+
+            assert_eq!(
+                sequence.poll_progress(),
+                Some(Progress {
+                    transferred: received.len() as u64,
+                    total: 300,
+                })
+            );
+        }
+
+        assert!(matches!(sequence.poll_result(), Some(Ok(()))));
+        assert_eq!(received, image);
+        assert_eq!(&counters[254..258], &[0xFF, 0x00, 0x01, 0x02]);
+
+        let mut short = ProgrammingSequence::new(0x1000, 4, [0xAA; 2].as_slice());
+        assert!(matches!(
+            short.run(
+                |request| Ok(match request[0] {
+                    0x34 => vec![0x74, 0x10, 0x10],
+                    _ => vec![0x76, request[1]],
+                }),
+                |_| {}
+            ),
+            Err(Error::Io(_))
+        ));
+
+        let mut wrong_counter = ProgrammingSequence::new(0x1000, 4, [0xAA; 4].as_slice());
+        assert!(matches!(
+            wrong_counter.run(
+                |request| Ok(match request[0] {
+                    0x34 => vec![0x74, 0x10, 0x10],
+                    _ => vec![0x76, 0x05],
+                }),
+                |_| {}
+            ),
+            Err(Error::InvalidBlockSequenceCounter {
+                expected: 0x01,
+                received: 0x05,
+            })
+        ));
+
+        let mut too_large = ProgrammingSequence::new(0x1_0000_0000, 4, [0xAA; 4].as_slice());
+        assert!(matches!(
+            too_large.run(|_| Ok(vec![0x74, 0x10, 0x10]), |_| {}),
+            Err(Error::OutOfBounds { .. })
+        ));
+    }
+}
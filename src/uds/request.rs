@@ -0,0 +1,427 @@
+use crate::{
+    error::{Error, Result},
+    uds::{
+        sub_function, uint_bytes, CommunicationControlType, Fields, ResetType, RoutineControlType,
+        ServiceId, SessionType,
+    },
+};
+
+/// A request of one of the core UDS services of ISO 14229-1.
+///
+/// Converts from and into the `message` of a `DiagnosticMessage`.
+///
+/// ```rust
+/// use doip_definitions::uds::{SessionType, UdsRequest};
+///
+/// let request = UdsRequest::try_from([0x10, 0x03].as_slice())?;
+/// assert_eq!(
+///     request,
+///     UdsRequest::DiagnosticSessionControl {
+///         session_type: SessionType::ExtendedDiagnostic,
+///         suppress_positive_response: false,
+///     }
+/// );
+///
+/// let message: Vec<u8> = UdsRequest::ReadDataByIdentifier { identifiers: vec![0xF190] }.into();
+/// assert_eq!(message, vec![0x22, 0xF1, 0x90]);
+/// # Ok::<(), doip_definitions::error::Error>(())
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum UdsRequest {
+    /// Switches the server to another diagnostic session
+    DiagnosticSessionControl {
+        /// The requested session
+        session_type: SessionType,
+
+        /// Suppresses the positive response
+        suppress_positive_response: bool,
+    },
+
+    /// Resets the server
+    EcuReset {
+        /// The requested reset
+        reset_type: ResetType,
+
+        /// Suppresses the positive response
+        suppress_positive_response: bool,
+    },
+
+    /// Requests a seed with an odd access type, or sends the key of that seed
+    /// with the following even access type
+    SecurityAccess {
+        /// The security level and step
+        security_access_type: u8,
+
+        /// The security access data record of a seed request, or the key
+        data: Vec<u8>,
+    },
+
+    /// Enables or disables the transmission and reception of messages
+    CommunicationControl {
+        /// Selects the enabled directions
+        control_type: CommunicationControlType,
+
+        /// Selects the kind of messages and the subnets
+        communication_type: u8,
+
+        /// Node addressed by the control types with enhanced address information
+        node_identification_number: Option<u16>,
+
+        /// Suppresses the positive response
+        suppress_positive_response: bool,
+    },
+
+    /// Keeps the active diagnostic session alive
+    TesterPresent {
+        /// Suppresses the positive response
+        suppress_positive_response: bool,
+    },
+
+    /// Reads the data records of one or more data identifiers
+    ReadDataByIdentifier {
+        /// The requested data identifiers
+        identifiers: Vec<u16>,
+    },
+
+    /// Writes the data record of a data identifier
+    WriteDataByIdentifier {
+        /// The written data identifier
+        identifier: u16,
+
+        /// The data record
+        data: Vec<u8>,
+    },
+
+    /// Starts, stops or requests the results of a routine
+    RoutineControl {
+        /// The requested action
+        control_type: RoutineControlType,
+
+        /// The routine identifier
+        identifier: u16,
+
+        /// Routine specific options
+        option_record: Vec<u8>,
+
+        /// Suppresses the positive response
+        suppress_positive_response: bool,
+    },
+
+    /// Starts a download of data into the memory of the server
+    RequestDownload {
+        /// Compression method in the high and encryption method in the low
+        /// nibble, `0x00` for plain data
+        data_format_identifier: u8,
+
+        /// Length in bytes of `memory_size` in the high and of `memory_address`
+        /// in the low nibble
+        address_and_length_format_identifier: u8,
+
+        /// Start address of the download
+        memory_address: u64,
+
+        /// Number of bytes downloaded
+        memory_size: u64,
+    },
+
+    /// Transfers a block of a download
+    TransferData {
+        /// Counts the blocks from 1, wrapping from `0xFF` to `0x00`
+        block_sequence_counter: u8,
+
+        /// The data of the block
+        data: Vec<u8>,
+    },
+
+    /// Ends a download
+    RequestTransferExit {
+        /// Manufacturer specific data, e.g. a checksum
+        parameter_record: Vec<u8>,
+    },
+
+    /// Reads DTCs and their data with one of the report types
+    ReadDtcInformation {
+        /// The report type sub-function
+        report_type: u8,
+
+        /// Parameters of the report type, e.g. the DTC status mask
+        record: Vec<u8>,
+    },
+
+    /// Clears stored DTCs
+    ClearDiagnosticInformation {
+        /// The 3 byte DTC group, `0xFFFFFF` clears all groups
+        group_of_dtc: u32,
+
+        /// Optional memory selection of ISO 14229-1:2020
+        memory_selection: Option<u8>,
+    },
+}
+
+impl UdsRequest {
+    /// Returns the service identifier of the request.
+    #[must_use]
+    pub fn service_id(&self) -> ServiceId {
+        match self {
+            UdsRequest::DiagnosticSessionControl { .. } => ServiceId::DiagnosticSessionControl,
+            UdsRequest::EcuReset { .. } => ServiceId::EcuReset,
+            UdsRequest::SecurityAccess { .. } => ServiceId::SecurityAccess,
+            UdsRequest::CommunicationControl { .. } => ServiceId::CommunicationControl,
+            UdsRequest::TesterPresent { .. } => ServiceId::TesterPresent,
+            UdsRequest::ReadDataByIdentifier { .. } => ServiceId::ReadDataByIdentifier,
+            UdsRequest::WriteDataByIdentifier { .. } => ServiceId::WriteDataByIdentifier,
+            UdsRequest::RoutineControl { .. } => ServiceId::RoutineControl,
+            UdsRequest::RequestDownload { .. } => ServiceId::RequestDownload,
+            UdsRequest::TransferData { .. } => ServiceId::TransferData,
+            UdsRequest::RequestTransferExit { .. } => ServiceId::RequestTransferExit,
+            UdsRequest::ReadDtcInformation { .. } => ServiceId::ReadDtcInformation,
+            UdsRequest::ClearDiagnosticInformation { .. } => ServiceId::ClearDiagnosticInformation,
+        }
+    }
+
+    /// Returns whether the server is asked not to send a positive response.
+    #[must_use]
+    pub fn suppress_positive_response(&self) -> bool {
+        match self {
+            UdsRequest::DiagnosticSessionControl {
+                suppress_positive_response,
+                ..
+            }
+            | UdsRequest::EcuReset {
+                suppress_positive_response,
+                ..
+            }
+            | UdsRequest::CommunicationControl {
+                suppress_positive_response,
+                ..
+            }
+            | UdsRequest::TesterPresent {
+                suppress_positive_response,
+            }
+            | UdsRequest::RoutineControl {
+                suppress_positive_response,
+                ..
+            } => *suppress_positive_response,
+            _ => false,
+        }
+    }
+}
+
+impl TryFrom<&[u8]> for UdsRequest {
+    type Error = Error;
+
+    fn try_from(value: &[u8]) -> Result<Self> {
+        let fields = Fields::new(value, "UdsRequest");
+        let service_id = ServiceId::try_from(&fields.u8(0, "Service Id")?)?;
+
+        match service_id {
+            ServiceId::DiagnosticSessionControl => {
+                let (session_type, suppress_positive_response) = fields.sub_function()?;
+
+                Ok(UdsRequest::DiagnosticSessionControl {
+                    session_type: session_type.into(),
+                    suppress_positive_response,
+                })
+            }
+            ServiceId::EcuReset => {
+                let (reset_type, suppress_positive_response) = fields.sub_function()?;
+
+                Ok(UdsRequest::EcuReset {
+                    reset_type: reset_type.into(),
+                    suppress_positive_response,
+                })
+            }
+            ServiceId::SecurityAccess => Ok(UdsRequest::SecurityAccess {
+                security_access_type: fields.u8(1, "Security Access Type")?,
+                data: fields.rest(2),
+            }),
+            ServiceId::CommunicationControl => {
+                let (control_type, suppress_positive_response) = fields.sub_function()?;
+
+                Ok(UdsRequest::CommunicationControl {
+                    control_type: control_type.into(),
+                    communication_type: fields.u8(2, "Communication Type")?,
+                    node_identification_number: fields.u16(3, "Node Identification Number").ok(),
+                    suppress_positive_response,
+                })
+            }
+            ServiceId::TesterPresent => {
+                let (_, suppress_positive_response) = fields.sub_function()?;
+
+                Ok(UdsRequest::TesterPresent {
+                    suppress_positive_response,
+                })
+            }
+            ServiceId::ReadDataByIdentifier => {
+                let identifiers = fields.get(1..value.len(), "Data Identifier")?;
+
+                if identifiers.is_empty() || identifiers.len() % 2 != 0 {
+                    return Err(Error::OutOfBounds {
+                        source: "UdsRequest",
+                        variable: "Data Identifier",
+                    });
+                }
+
+                Ok(UdsRequest::ReadDataByIdentifier {
+                    identifiers: identifiers
+                        .chunks_exact(2)
+                        .map(|identifier| u16::from_be_bytes([identifier[0], identifier[1]]))
+                        .collect(),
+                })
+            }
+            ServiceId::WriteDataByIdentifier => Ok(UdsRequest::WriteDataByIdentifier {
+                identifier: fields.u16(1, "Data Identifier")?,
+                data: fields.rest(3),
+            }),
+            ServiceId::RoutineControl => {
+                let (control_type, suppress_positive_response) = fields.sub_function()?;
+
+                Ok(UdsRequest::RoutineControl {
+                    control_type: RoutineControlType::try_from(&control_type)?,
+                    identifier: fields.u16(2, "Routine Identifier")?,
+                    option_record: fields.rest(4),
+                    suppress_positive_response,
+                })
+            }
+            ServiceId::RequestDownload => {
+                let address_and_length_format_identifier =
+                    fields.u8(2, "Address And Length Format Identifier")?;
+                let address_length = usize::from(address_and_length_format_identifier & 0x0F);
+                let size_length = usize::from(address_and_length_format_identifier >> 4);
+
+                Ok(UdsRequest::RequestDownload {
+                    data_format_identifier: fields.u8(1, "Data Format Identifier")?,
+                    address_and_length_format_identifier,
+                    memory_address: fields.uint(3, address_length, "Memory Address")?,
+                    memory_size: fields.uint(3 + address_length, size_length, "Memory Size")?,
+                })
+            }
+            ServiceId::TransferData => Ok(UdsRequest::TransferData {
+                block_sequence_counter: fields.u8(1, "Block Sequence Counter")?,
+                data: fields.rest(2),
+            }),
+            ServiceId::RequestTransferExit => Ok(UdsRequest::RequestTransferExit {
+                parameter_record: fields.rest(1),
+            }),
+            ServiceId::ReadDtcInformation => Ok(UdsRequest::ReadDtcInformation {
+                report_type: fields.u8(1, "Report Type")?,
+                record: fields.rest(2),
+            }),
+            ServiceId::ClearDiagnosticInformation => {
+                let group = fields.get(1..4, "Group Of DTC")?;
+
+                Ok(UdsRequest::ClearDiagnosticInformation {
+                    group_of_dtc: u32::from_be_bytes([0, group[0], group[1], group[2]]),
+                    memory_selection: fields.u8(4, "Memory Selection").ok(),
+                })
+            }
+        }
+    }
+}
+
+impl From<UdsRequest> for Vec<u8> {
+    fn from(value: UdsRequest) -> Self {
+        let mut bytes = vec![value.service_id().into()];
+        let suppress_positive_response = value.suppress_positive_response();
+
+        match value {
+            UdsRequest::DiagnosticSessionControl { session_type, .. } => {
+                bytes.push(sub_function(
+                    session_type.into(),
+                    suppress_positive_response,
+                ));
+            }
+            UdsRequest::EcuReset { reset_type, .. } => {
+                bytes.push(sub_function(reset_type.into(), suppress_positive_response));
+            }
+            UdsRequest::SecurityAccess {
+                security_access_type,
+                data,
+            } => {
+                bytes.push(security_access_type);
+                bytes.extend(data);
+            }
+            UdsRequest::CommunicationControl {
+                control_type,
+                communication_type,
+                node_identification_number,
+                ..
+            } => {
+                bytes.push(sub_function(
+                    control_type.into(),
+                    suppress_positive_response,
+                ));
+                bytes.push(communication_type);
+                if let Some(node) = node_identification_number {
+                    bytes.extend(node.to_be_bytes());
+                }
+            }
+            UdsRequest::TesterPresent { .. } => {
+                bytes.push(sub_function(0x00, suppress_positive_response));
+            }
+            UdsRequest::ReadDataByIdentifier { identifiers } => {
+                bytes.extend(identifiers.into_iter().flat_map(u16::to_be_bytes));
+            }
+            UdsRequest::WriteDataByIdentifier { identifier, data } => {
+                bytes.extend(identifier.to_be_bytes());
+                bytes.extend(data);
+            }
+            UdsRequest::RoutineControl {
+                control_type,
+                identifier,
+                option_record,
+                ..
+            } => {
+                bytes.push(sub_function(
+                    control_type.into(),
+                    suppress_positive_response,
+                ));
+                bytes.extend(identifier.to_be_bytes());
+                bytes.extend(option_record);
+            }
+            UdsRequest::RequestDownload {
+                data_format_identifier,
+                address_and_length_format_identifier,
+                memory_address,
+                memory_size,
+            } => {
+                bytes.push(data_format_identifier);
+                bytes.push(address_and_length_format_identifier);
+                bytes.extend(uint_bytes(
+                    memory_address,
+                    usize::from(address_and_length_format_identifier & 0x0F),
+                ));
+                bytes.extend(uint_bytes(
+                    memory_size,
+                    usize::from(address_and_length_format_identifier >> 4),
+                ));
+            }
+            UdsRequest::TransferData {
+                block_sequence_counter,
+                data,
+            } => {
+                bytes.push(block_sequence_counter);
+                bytes.extend(data);
+            }
+            UdsRequest::RequestTransferExit { parameter_record } => {
+                bytes.extend(parameter_record);
+            }
+            UdsRequest::ReadDtcInformation {
+                report_type,
+                record,
+            } => {
+                bytes.push(report_type);
+                bytes.extend(record);
+            }
+            UdsRequest::ClearDiagnosticInformation {
+                group_of_dtc,
+                memory_selection,
+            } => {
+                bytes.extend(&group_of_dtc.to_be_bytes()[1..]);
+                bytes.extend(memory_selection);
+            }
+        }
+
+        bytes
+    }
+}
@@ -0,0 +1,330 @@
+use crate::{
+    error::{Error, Result},
+    uds::{
+        uint_bytes, uint_length, CommunicationControlType, Fields, NegativeResponse,
+        NegativeResponseCode, ResetType, RoutineControlType, ServiceId, SessionType,
+        NEGATIVE_RESPONSE_SID, POSITIVE_RESPONSE_OFFSET,
+    },
+};
+
+/// A response to one of the core UDS services of ISO 14229-1.
+///
+/// Converts from and into the `message` of a `DiagnosticMessage`.
+///
+/// ```rust
+/// use doip_definitions::uds::{NegativeResponseCode, UdsResponse};
+///
+/// let response = UdsResponse::try_from([0x7F, 0x22, 0x31].as_slice())?;
+/// assert_eq!(
+///     response,
+///     UdsResponse::Negative {
+///         service_id: 0x22,
+///         response_code: NegativeResponseCode::RequestOutOfRange,
+///     }
+/// );
+/// # Ok::<(), doip_definitions::error::Error>(())
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum UdsResponse {
+    /// The server switched to another diagnostic session
+    DiagnosticSessionControl {
+        /// The active session
+        session_type: SessionType,
+
+        /// `P2Server_max` in 1 ms resolution
+        p2_server_max: u16,
+
+        /// `P2*Server_max` in 10 ms resolution
+        p2_star_server_max: u16,
+    },
+
+    /// The server is about to reset
+    EcuReset {
+        /// The performed reset
+        reset_type: ResetType,
+
+        /// Seconds until power down, only sent for `EnableRapidPowerShutDown`
+        power_down_time: Option<u8>,
+    },
+
+    /// The seed of a seed request, or an empty seed once the key is accepted
+    SecurityAccess {
+        /// The security level and step
+        security_access_type: u8,
+
+        /// The seed, all zeros when the level is already unlocked
+        seed: Vec<u8>,
+    },
+
+    /// The communication was enabled or disabled
+    CommunicationControl {
+        /// The enabled directions
+        control_type: CommunicationControlType,
+    },
+
+    /// The active diagnostic session was kept alive
+    TesterPresent,
+
+    /// The requested data records
+    ReadDataByIdentifier {
+        /// Each data identifier followed by its data record, which can only be
+        /// split knowing the length of each record
+        records: Vec<u8>,
+    },
+
+    /// The data record was written
+    WriteDataByIdentifier {
+        /// The written data identifier
+        identifier: u16,
+    },
+
+    /// The routine was started, stopped or reported its results
+    RoutineControl {
+        /// The performed action
+        control_type: RoutineControlType,
+
+        /// The routine identifier
+        identifier: u16,
+
+        /// Routine specific status or results
+        status_record: Vec<u8>,
+    },
+
+    /// The server accepted a download
+    RequestDownload {
+        /// Largest `TransferData` request the server accepts, including the
+        /// service identifier and the block sequence counter
+        max_number_of_block_length: u64,
+    },
+
+    /// The server stored a block of a download
+    TransferData {
+        /// Counter of the stored block
+        block_sequence_counter: u8,
+
+        /// Manufacturer specific data
+        parameter_record: Vec<u8>,
+    },
+
+    /// The server ended a download
+    RequestTransferExit {
+        /// Manufacturer specific data
+        parameter_record: Vec<u8>,
+    },
+
+    /// The requested DTC report
+    ReadDtcInformation {
+        /// The report type sub-function
+        report_type: u8,
+
+        /// The report, starting after the report type
+        record: Vec<u8>,
+    },
+
+    /// The DTCs were cleared
+    ClearDiagnosticInformation,
+
+    /// The server rejected the request
+    Negative {
+        /// Service identifier of the rejected request
+        service_id: u8,
+
+        /// Reason of the rejection
+        response_code: NegativeResponseCode,
+    },
+}
+
+impl TryFrom<&[u8]> for UdsResponse {
+    type Error = Error;
+
+    fn try_from(value: &[u8]) -> Result<Self> {
+        let fields = Fields::new(value, "UdsResponse");
+        let response_id = fields.u8(0, "Service Id")?;
+
+        if response_id == NEGATIVE_RESPONSE_SID {
+            let negative = NegativeResponse::try_from(value)?;
+
+            return Ok(UdsResponse::Negative {
+                service_id: negative.service_id,
+                response_code: negative.response_code,
+            });
+        }
+
+        let service_id = response_id
+            .checked_sub(POSITIVE_RESPONSE_OFFSET)
+            .ok_or(Error::InvalidServiceId { value: response_id })?;
+
+        match ServiceId::try_from(&service_id)? {
+            ServiceId::DiagnosticSessionControl => Ok(UdsResponse::DiagnosticSessionControl {
+                session_type: fields.u8(1, "Session Type")?.into(),
+                p2_server_max: fields.u16(2, "P2 Server Max")?,
+                p2_star_server_max: fields.u16(4, "P2* Server Max")?,
+            }),
+            ServiceId::EcuReset => Ok(UdsResponse::EcuReset {
+                reset_type: fields.u8(1, "Reset Type")?.into(),
+                power_down_time: fields.u8(2, "Power Down Time").ok(),
+            }),
+            ServiceId::SecurityAccess => Ok(UdsResponse::SecurityAccess {
+                security_access_type: fields.u8(1, "Security Access Type")?,
+                seed: fields.rest(2),
+            }),
+            ServiceId::CommunicationControl => Ok(UdsResponse::CommunicationControl {
+                control_type: fields.u8(1, "Control Type")?.into(),
+            }),
+            ServiceId::TesterPresent => {
+                fields.u8(1, "Zero Sub Function")?;
+
+                Ok(UdsResponse::TesterPresent)
+            }
+            ServiceId::ReadDataByIdentifier => Ok(UdsResponse::ReadDataByIdentifier {
+                records: fields.get(1..value.len(), "Data Record")?.to_vec(),
+            }),
+            ServiceId::WriteDataByIdentifier => Ok(UdsResponse::WriteDataByIdentifier {
+                identifier: fields.u16(1, "Data Identifier")?,
+            }),
+            ServiceId::RoutineControl => Ok(UdsResponse::RoutineControl {
+                control_type: RoutineControlType::try_from(&fields.u8(1, "Control Type")?)?,
+                identifier: fields.u16(2, "Routine Identifier")?,
+                status_record: fields.rest(4),
+            }),
+            ServiceId::RequestDownload => {
+                let length_format_identifier = fields.u8(1, "Length Format Identifier")?;
+
+                Ok(UdsResponse::RequestDownload {
+                    max_number_of_block_length: fields.uint(
+                        2,
+                        usize::from(length_format_identifier >> 4),
+                        "Max Number Of Block Length",
+                    )?,
+                })
+            }
+            ServiceId::TransferData => Ok(UdsResponse::TransferData {
+                block_sequence_counter: fields.u8(1, "Block Sequence Counter")?,
+                parameter_record: fields.rest(2),
+            }),
+            ServiceId::RequestTransferExit => Ok(UdsResponse::RequestTransferExit {
+                parameter_record: fields.rest(1),
+            }),
+            ServiceId::ReadDtcInformation => Ok(UdsResponse::ReadDtcInformation {
+                report_type: fields.u8(1, "Report Type")?,
+                record: fields.rest(2),
+            }),
+            ServiceId::ClearDiagnosticInformation => Ok(UdsResponse::ClearDiagnosticInformation),
+        }
+    }
+}
+
+impl From<UdsResponse> for Vec<u8> {
+    #[allow(clippy::too_many_lines)]
+    fn from(value: UdsResponse) -> Self {
+        let positive =
+            |service_id: ServiceId| vec![u8::from(service_id) + POSITIVE_RESPONSE_OFFSET];
+
+        match value {
+            UdsResponse::DiagnosticSessionControl {
+                session_type,
+                p2_server_max,
+                p2_star_server_max,
+            } => {
+                let mut bytes = positive(ServiceId::DiagnosticSessionControl);
+                bytes.push(session_type.into());
+                bytes.extend(p2_server_max.to_be_bytes());
+                bytes.extend(p2_star_server_max.to_be_bytes());
+                bytes
+            }
+            UdsResponse::EcuReset {
+                reset_type,
+                power_down_time,
+            } => {
+                let mut bytes = positive(ServiceId::EcuReset);
+                bytes.push(reset_type.into());
+                bytes.extend(power_down_time);
+                bytes
+            }
+            UdsResponse::SecurityAccess {
+                security_access_type,
+                seed,
+            } => {
+                let mut bytes = positive(ServiceId::SecurityAccess);
+                bytes.push(security_access_type);
+                bytes.extend(seed);
+                bytes
+            }
+            UdsResponse::CommunicationControl { control_type } => {
+                let mut bytes = positive(ServiceId::CommunicationControl);
+                bytes.push(control_type.into());
+                bytes
+            }
+            UdsResponse::TesterPresent => {
+                let mut bytes = positive(ServiceId::TesterPresent);
+                bytes.push(0x00);
+                bytes
+            }
+            UdsResponse::ReadDataByIdentifier { records } => {
+                let mut bytes = positive(ServiceId::ReadDataByIdentifier);
+                bytes.extend(records);
+                bytes
+            }
+            UdsResponse::WriteDataByIdentifier { identifier } => {
+                let mut bytes = positive(ServiceId::WriteDataByIdentifier);
+                bytes.extend(identifier.to_be_bytes());
+                bytes
+            }
+            UdsResponse::RoutineControl {
+                control_type,
+                identifier,
+                status_record,
+            } => {
+                let mut bytes = positive(ServiceId::RoutineControl);
+                bytes.push(control_type.into());
+                bytes.extend(identifier.to_be_bytes());
+                bytes.extend(status_record);
+                bytes
+            }
+            UdsResponse::RequestDownload {
+                max_number_of_block_length,
+            } => {
+                let length = uint_length(max_number_of_block_length);
+                let mut bytes = positive(ServiceId::RequestDownload);
+                #[allow(clippy::cast_possible_truncation)]
+                bytes.push((length as u8) << 4);
+                bytes.extend(uint_bytes(max_number_of_block_length, length));
+                bytes
+            }
+            UdsResponse::TransferData {
+                block_sequence_counter,
+                parameter_record,
+            } => {
+                let mut bytes = positive(ServiceId::TransferData);
+                bytes.push(block_sequence_counter);
+                bytes.extend(parameter_record);
+                bytes
+            }
+            UdsResponse::RequestTransferExit { parameter_record } => {
+                let mut bytes = positive(ServiceId::RequestTransferExit);
+                bytes.extend(parameter_record);
+                bytes
+            }
+            UdsResponse::ReadDtcInformation {
+                report_type,
+                record,
+            } => {
+                let mut bytes = positive(ServiceId::ReadDtcInformation);
+                bytes.push(report_type);
+                bytes.extend(record);
+                bytes
+            }
+            UdsResponse::ClearDiagnosticInformation => {
+                positive(ServiceId::ClearDiagnosticInformation)
+            }
+            UdsResponse::Negative {
+                service_id,
+                response_code,
+            } => <[u8; 3]>::from(NegativeResponse {
+                service_id,
+                response_code,
+            })
+            .to_vec(),
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    error::{Error, Result},
+    uds::{check_response, DataCodec, DataValue, RoutineControlType, UdsRequest, UdsResponse},
+};
+
+/// Name and record codecs of a routine identifier.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RoutineDefinition {
+    /// Name of the routine
+    pub name: String,
+
+    /// Encoding of the routine control option record sent with the request
+    pub option_record: DataCodec,
+
+    /// Encoding of the routine status record received with the response
+    pub status_record: DataCodec,
+}
+
+impl RoutineDefinition {
+    /// Creates a definition whose option and status records are raw bytes.
+    #[must_use]
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            option_record: DataCodec::Raw { length: None },
+            status_record: DataCodec::Raw { length: None },
+        }
+    }
+}
+
+/// A `RoutineControl` response, decoded by a [`RoutineRegistry`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct RoutineRecord {
+    /// The routine identifier
+    pub identifier: u16,
+
+    /// Name of the routine
+    pub name: String,
+
+    /// The performed action
+    pub control_type: RoutineControlType,
+
+    /// The decoded status record, `None` when the server sent none
+    pub status: Option<DataValue>,
+}
+
+/// Maps routine identifiers to their name and record codecs.
+///
+/// Encodes the option record of `RoutineControl` requests and decodes the
+/// status record of their responses.
+///
+/// ```rust
+/// use doip_definitions::uds::{
+///     DataCodec, DataValue, RoutineControlType, RoutineDefinition, RoutineRegistry,
+/// };
+///
+/// let mut registry = RoutineRegistry::new();
+/// registry.insert(
+///     0xFF00,
+///     RoutineDefinition {
+///         status_record: DataCodec::Unsigned { length: 1, scale: 1.0, offset: 0.0 },
+///         ..RoutineDefinition::new("Erase Memory")
+///     },
+/// );
+///
+/// let record = registry.decode_response(&[0x71, 0x03, 0xFF, 0x00, 0x02])?;
+/// assert_eq!(record.control_type, RoutineControlType::RequestRoutineResults);
+/// assert_eq!(record.status, Some(DataValue::Number(2.0)));
+/// # Ok::<(), doip_definitions::error::Error>(())
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RoutineRegistry {
+    definitions: BTreeMap<u16, RoutineDefinition>,
+}
+
+impl RoutineRegistry {
+    /// Creates an empty `RoutineRegistry`.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `identifier` with `definition`, replacing any previous one.
+    pub fn insert(&mut self, identifier: u16, definition: RoutineDefinition) {
+        self.definitions.insert(identifier, definition);
+    }
+
+    /// Removes the definition of `identifier`.
+    pub fn remove(&mut self, identifier: u16) {
+        self.definitions.remove(&identifier);
+    }
+
+    /// Returns the definition of `identifier`.
+    #[must_use]
+    pub fn get(&self, identifier: u16) -> Option<&RoutineDefinition> {
+        self.definitions.get(&identifier)
+    }
+
+    fn definition(&self, identifier: u16) -> Result<&RoutineDefinition> {
+        self.get(identifier)
+            .ok_or(Error::UnknownRoutineIdentifier { identifier })
+    }
+
+    /// Builds the `RoutineControl` request of `identifier`, encoding `option`
+    /// into the option record.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::UnknownRoutineIdentifier`] when `identifier` is not
+    /// registered and [`Error::InvalidDataRecord`] when `option` does not match
+    /// the option record codec.
+    pub fn request(
+        &self,
+        identifier: u16,
+        control_type: RoutineControlType,
+        option: Option<&DataValue>,
+    ) -> Result<UdsRequest> {
+        let definition = self.definition(identifier)?;
+
+        let option_record = match option {
+            Some(option) => definition
+                .option_record
+                .encode(option)
+                .ok_or(Error::InvalidDataRecord { identifier })?,
+            None => Vec::new(),
+        };
+
+        Ok(UdsRequest::RoutineControl {
+            control_type,
+            identifier,
+            option_record,
+            suppress_positive_response: false,
+        })
+    }
+
+    /// Decodes the UDS message of a `RoutineControl` response.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::NegativeResponse`] for a negative response,
+    /// [`Error::InvalidServiceId`] for the response of another service,
+    /// [`Error::UnknownRoutineIdentifier`] when the routine is not registered
+    /// and [`Error::InvalidDataRecord`] when the status record does not match
+    /// its codec.
+    pub fn decode_response(&self, response: &[u8]) -> Result<RoutineRecord> {
+        let UdsResponse::RoutineControl {
+            control_type,
+            identifier,
+            status_record,
+        } = UdsResponse::try_from(check_response(response)?)?
+        else {
+            return Err(Error::InvalidServiceId { value: response[0] });
+        };
+
+        let definition = self.definition(identifier)?;
+
+        let status = if status_record.is_empty() {
+            None
+        } else {
+            Some(
+                definition
+                    .status_record
+                    .decode(&status_record)
+                    .ok_or(Error::InvalidDataRecord { identifier })?,
+            )
+        };
+
+        Ok(RoutineRecord {
+            identifier,
+            name: definition.name.clone(),
+            control_type,
+            status,
+        })
+    }
+}
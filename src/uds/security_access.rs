@@ -0,0 +1,366 @@
+use std::time::{Duration, Instant};
+
+use crate::{
+    error::{Error, Result},
+    uds::{is_response_pending, NegativeResponseCode, UdsRequest, UdsResponse},
+};
+
+/// Calculates the key of a `SecurityAccess` seed, implemented by each OEM.
+///
+/// Implemented for closures taking the level and the seed.
+pub trait KeyAlgorithm {
+    /// Returns the key for `seed` of the `requestSeed` sub-function `level`, or
+    /// `None` when no key can be calculated.
+    fn compute_key(&mut self, level: u8, seed: &[u8]) -> Option<Vec<u8>>;
+}
+
+impl<F> KeyAlgorithm for F
+where
+    F: FnMut(u8, &[u8]) -> Option<Vec<u8>>,
+{
+    fn compute_key(&mut self, level: u8, seed: &[u8]) -> Option<Vec<u8>> {
+        self(level, seed)
+    }
+}
+
+/// How a [`SecurityAccess`] reacts to rejected keys and locked access.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Number of new seeds requested after `invalidKey` (0x35)
+    pub invalid_key_retries: u8,
+
+    /// Number of new seeds requested after `exceededNumberOfAttempts` (0x36) or
+    /// `requiredTimeDelayNotExpired` (0x37), each after waiting `delay`
+    pub delay_retries: u8,
+
+    /// Time waited for the security access delay of the server to expire
+    pub delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    /// Constructs a `RetryPolicy` with:
+    /// - One retry after an invalid key
+    /// - One retry after a delay of 10 seconds when access is locked
+    fn default() -> Self {
+        Self {
+            invalid_key_retries: 1,
+            delay_retries: 1,
+            delay: Duration::from_secs(10),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum State {
+    Idle,
+    AwaitingSeed,
+    AwaitingKey,
+    Delayed { deadline: Instant },
+    Finished(Option<Result<()>>),
+}
+
+/// The `requestSeed` / `sendKey` exchange of the UDS `SecurityAccess` service.
+///
+/// The exchange performs no IO itself. [`SecurityAccess::poll_transmit`] returns
+/// the UDS requests to send to the target and their responses are passed to
+/// [`SecurityAccess::handle_response`]. While waiting for the security access
+/// delay of the server, [`SecurityAccess::poll_timeout`] returns when to call
+/// [`SecurityAccess::handle_timeout`]. The outcome is returned once by
+/// [`SecurityAccess::poll_result`]. [`SecurityAccess::run`] drives the exchange
+/// over any blocking transport.
+///
+/// ```rust
+/// use doip_definitions::uds::SecurityAccess;
+///
+/// let mut access = SecurityAccess::new(0x01, |_level: u8, seed: &[u8]| {
+///     Some(seed.iter().map(|byte| byte ^ 0xFF).collect())
+/// });
+///
+/// let result = access.run(|request| match request {
+///     [0x27, 0x01] => Ok(vec![0x67, 0x01, 0x12, 0x34]),
+///     [0x27, 0x02, 0xED, 0xCB] => Ok(vec![0x67, 0x02]),
+///     _ => Ok(vec![0x7F, 0x27, 0x35]),
+/// });
+///
+/// assert!(result.is_ok());
+/// ```
+#[derive(Debug)]
+pub struct SecurityAccess<A> {
+    level: u8,
+    algorithm: A,
+    policy: RetryPolicy,
+    invalid_key_retries: u8,
+    delay_retries: u8,
+    state: State,
+    transmit: Option<Vec<u8>>,
+}
+
+impl<A: KeyAlgorithm> SecurityAccess<A> {
+    /// Creates the exchange for the odd `requestSeed` sub-function `level`,
+    /// sending the key with `level + 1`.
+    #[must_use]
+    pub fn new(level: u8, algorithm: A) -> Self {
+        Self {
+            level,
+            algorithm,
+            policy: RetryPolicy::default(),
+            invalid_key_retries: 0,
+            delay_retries: 0,
+            state: State::Idle,
+            transmit: None,
+        }
+    }
+
+    /// Sets the reaction to rejected keys and locked access.
+    #[must_use]
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Returns the `requestSeed` sub-function.
+    #[must_use]
+    pub fn level(&self) -> u8 {
+        self.level
+    }
+
+    /// Starts the exchange by requesting a seed, resetting the retries.
+    pub fn start(&mut self, _now: Instant) {
+        self.invalid_key_retries = self.policy.invalid_key_retries;
+        self.delay_retries = self.policy.delay_retries;
+        self.request_seed();
+    }
+
+    fn request_seed(&mut self) {
+        self.state = State::AwaitingSeed;
+        self.transmit = Some(
+            UdsRequest::SecurityAccess {
+                security_access_type: self.level,
+                data: Vec::new(),
+            }
+            .into(),
+        );
+    }
+
+    fn finish(&mut self, result: Result<()>) {
+        self.state = State::Finished(Some(result));
+        self.transmit = None;
+    }
+
+    /// Processes the UDS `response` of the target to the last request.
+    ///
+    /// `requestCorrectlyReceived-ResponsePending` responses are ignored.
+    pub fn handle_response(&mut self, now: Instant, response: &[u8]) {
+        if is_response_pending(response) {
+            return;
+        }
+
+        let awaiting_key = match self.state {
+            State::AwaitingSeed => false,
+            State::AwaitingKey => true,
+            _ => return,
+        };
+
+        match UdsResponse::try_from(response) {
+            Ok(UdsResponse::SecurityAccess {
+                security_access_type,
+                seed,
+            }) if !awaiting_key && security_access_type == self.level => {
+                self.handle_seed(&seed);
+            }
+            Ok(UdsResponse::SecurityAccess {
+                security_access_type,
+                ..
+            }) if awaiting_key && security_access_type == self.level.wrapping_add(1) => {
+                self.finish(Ok(()));
+            }
+            Ok(UdsResponse::Negative {
+                service_id,
+                response_code,
+            }) => self.handle_negative(now, service_id, response_code, awaiting_key),
+            Ok(_) => self.finish(Err(Error::UnexpectedResponse {
+                service_id: response[0],
+            })),
+            Err(err) => self.finish(Err(err)),
+        }
+    }
+
+    fn handle_seed(&mut self, seed: &[u8]) {
+        // An all zero seed signals that the level is already unlocked
+        if seed.iter().all(|byte| *byte == 0) {
+            self.finish(Ok(()));
+            return;
+        }
+
+        let Some(key) = self.algorithm.compute_key(self.level, seed) else {
+            self.finish(Err(Error::KeyUnavailable { level: self.level }));
+            return;
+        };
+
+        self.state = State::AwaitingKey;
+        self.transmit = Some(
+            UdsRequest::SecurityAccess {
+                security_access_type: self.level.wrapping_add(1),
+                data: key,
+            }
+            .into(),
+        );
+    }
+
+    fn handle_negative(
+        &mut self,
+        now: Instant,
+        service_id: u8,
+        response_code: NegativeResponseCode,
+        awaiting_key: bool,
+    ) {
+        match response_code {
+            NegativeResponseCode::InvalidKey if awaiting_key && self.invalid_key_retries > 0 => {
+                self.invalid_key_retries -= 1;
+                self.request_seed();
+            }
+            NegativeResponseCode::ExceededNumberOfAttempts
+            | NegativeResponseCode::RequiredTimeDelayNotExpired
+                if self.delay_retries > 0 =>
+            {
+                self.delay_retries -= 1;
+                self.state = State::Delayed {
+                    deadline: now + self.policy.delay,
+                };
+            }
+            _ => self.finish(Err(Error::NegativeResponse {
+                service_id,
+                response_code,
+            })),
+        }
+    }
+
+    /// Requests a new seed once the security access delay expired at or before
+    /// `now`.
+    pub fn handle_timeout(&mut self, now: Instant) {
+        if let State::Delayed { deadline } = self.state {
+            if deadline <= now {
+                self.request_seed();
+            }
+        }
+    }
+
+    /// Returns the next instant at which [`SecurityAccess::handle_timeout`] must
+    /// be called.
+    #[must_use]
+    pub fn poll_timeout(&self) -> Option<Instant> {
+        match self.state {
+            State::Delayed { deadline } => Some(deadline),
+            _ => None,
+        }
+    }
+
+    /// Returns the next UDS request to send to the target.
+    pub fn poll_transmit(&mut self) -> Option<Vec<u8>> {
+        self.transmit.take()
+    }
+
+    /// Returns the outcome of the exchange once it finished.
+    pub fn poll_result(&mut self) -> Option<Result<()>> {
+        match &mut self.state {
+            State::Finished(result) => result.take(),
+            _ => None,
+        }
+    }
+
+    /// Runs the exchange, sending each request with `send` which returns the
+    /// final response of the target, and sleeping through security access
+    /// delays.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of `send`, [`Error::NegativeResponse`] when the
+    /// target rejects the exchange once the retries are used up,
+    /// [`Error::KeyUnavailable`] when the algorithm calculates no key and
+    /// [`Error::UnexpectedResponse`] when the target answers another request
+    /// and [`Error::Timeout`] when `send` returns no final response.
+    pub fn run(&mut self, mut send: impl FnMut(&[u8]) -> Result<Vec<u8>>) -> Result<()> {
+        self.start(Instant::now());
+
+        loop {
+            if let Some(result) = self.poll_result() {
+                return result;
+            }
+
+            if let Some(request) = self.poll_transmit() {
+                let response = send(&request)?;
+                self.handle_response(Instant::now(), &response);
+                continue;
+            }
+
+            if let Some(deadline) = self.poll_timeout() {
+                std::thread::sleep(deadline.saturating_duration_since(Instant::now()));
+                self.handle_timeout(Instant::now());
+                continue;
+            }
+
+            // `send` returned a pending response instead of the final one
+            return Err(Error::Timeout);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, Instant};
+
+    use crate::{
+        error::Error,
+        uds::{NegativeResponseCode, RetryPolicy, SecurityAccess},
+    };
+
+    #[test]
+    fn test_security_access_retries() {
+        let now = Instant::now();
+        let delay = Duration::from_secs(10);
+        let mut access = SecurityAccess::new(0x11, |_: u8, seed: &[u8]| Some(seed.to_vec()))
+            .retry_policy(RetryPolicy {
+                invalid_key_retries: 1,
+                delay_retries: 1,
+                delay,
+            });
+
+        access.start(now);
+        assert_eq!(access.poll_transmit(), Some(vec![0x27, 0x11]));
+
+        access.handle_response(now, &[0x7F, 0x27, 0x37]);
+        assert_eq!(access.poll_transmit(), None);
+        assert_eq!(access.poll_timeout(), Some(now + delay));
+
+        access.handle_timeout(now + delay);
+        assert_eq!(access.poll_transmit(), Some(vec![0x27, 0x11]));
+
+        access.handle_response(now + delay, &[0x7F, 0x27, 0x78]);
+        access.handle_response(now + delay, &[0x67, 0x11, 0xAB]);
+        assert_eq!(access.poll_transmit(), Some(vec![0x27, 0x12, 0xAB]));
+
+        access.handle_response(now + delay, &[0x7F, 0x27, 0x35]);
+        assert_eq!(access.poll_transmit(), Some(vec![0x27, 0x11]));
+        access.handle_response(now + delay, &[0x67, 0x11, 0xCD]);
+        assert_eq!(access.poll_transmit(), Some(vec![0x27, 0x12, 0xCD]));
+
+        access.handle_response(now + delay, &[0x7F, 0x27, 0x36]);
+        assert!(matches!(
+            access.poll_result(),
+            Some(Err(Error::NegativeResponse {
+                service_id: 0x27,
+                response_code: NegativeResponseCode::ExceededNumberOfAttempts,
+            }))
+        ));
+        assert!(access.poll_result().is_none());
+
+        let mut unlocked = SecurityAccess::new(0x01, |_: u8, _: &[u8]| None);
+        assert!(unlocked.run(|_| Ok(vec![0x67, 0x01, 0x00, 0x00])).is_ok());
+
+        let mut unavailable = SecurityAccess::new(0x01, |_: u8, _: &[u8]| None);
+        assert!(matches!(
+            unavailable.run(|_| Ok(vec![0x67, 0x01, 0x12])),
+            Err(Error::KeyUnavailable { level: 0x01 })
+        ));
+    }
+}
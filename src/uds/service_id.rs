@@ -0,0 +1,86 @@
+use crate::error::{Error, Result};
+
+/// Service identifiers of the UDS services modelled by
+/// [`UdsRequest`](crate::uds::UdsRequest) and [`UdsResponse`](crate::uds::UdsResponse).
+///
+/// The positive response of a service is identified by the request SID plus
+/// [`POSITIVE_RESPONSE_OFFSET`](crate::uds::POSITIVE_RESPONSE_OFFSET).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ServiceId {
+    /// Diagnostic Session Control
+    DiagnosticSessionControl = 0x10,
+
+    /// ECU Reset
+    EcuReset = 0x11,
+
+    /// Clear Diagnostic Information
+    ClearDiagnosticInformation = 0x14,
+
+    /// Read DTC Information
+    ReadDtcInformation = 0x19,
+
+    /// Read Data By Identifier
+    ReadDataByIdentifier = 0x22,
+
+    /// Security Access
+    SecurityAccess = 0x27,
+
+    /// Communication Control
+    CommunicationControl = 0x28,
+
+    /// Write Data By Identifier
+    WriteDataByIdentifier = 0x2E,
+
+    /// Routine Control
+    RoutineControl = 0x31,
+
+    /// Request Download
+    RequestDownload = 0x34,
+
+    /// Transfer Data
+    TransferData = 0x36,
+
+    /// Request Transfer Exit
+    RequestTransferExit = 0x37,
+
+    /// Tester Present
+    TesterPresent = 0x3E,
+}
+
+impl From<ServiceId> for u8 {
+    fn from(value: ServiceId) -> Self {
+        value as u8
+    }
+}
+
+impl TryFrom<&u8> for ServiceId {
+    type Error = Error;
+
+    fn try_from(value: &u8) -> Result<Self> {
+        let val = *value;
+
+        match val {
+            v if v == ServiceId::DiagnosticSessionControl as u8 => {
+                Ok(ServiceId::DiagnosticSessionControl)
+            }
+            v if v == ServiceId::EcuReset as u8 => Ok(ServiceId::EcuReset),
+            v if v == ServiceId::ClearDiagnosticInformation as u8 => {
+                Ok(ServiceId::ClearDiagnosticInformation)
+            }
+            v if v == ServiceId::ReadDtcInformation as u8 => Ok(ServiceId::ReadDtcInformation),
+            v if v == ServiceId::ReadDataByIdentifier as u8 => Ok(ServiceId::ReadDataByIdentifier),
+            v if v == ServiceId::SecurityAccess as u8 => Ok(ServiceId::SecurityAccess),
+            v if v == ServiceId::CommunicationControl as u8 => Ok(ServiceId::CommunicationControl),
+            v if v == ServiceId::WriteDataByIdentifier as u8 => {
+                Ok(ServiceId::WriteDataByIdentifier)
+            }
+            v if v == ServiceId::RoutineControl as u8 => Ok(ServiceId::RoutineControl),
+            v if v == ServiceId::RequestDownload as u8 => Ok(ServiceId::RequestDownload),
+            v if v == ServiceId::TransferData as u8 => Ok(ServiceId::TransferData),
+            v if v == ServiceId::RequestTransferExit as u8 => Ok(ServiceId::RequestTransferExit),
+            v if v == ServiceId::TesterPresent as u8 => Ok(ServiceId::TesterPresent),
+            v => Err(Error::InvalidServiceId { value: v }),
+        }
+    }
+}
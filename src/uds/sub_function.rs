@@ -0,0 +1,185 @@
+use crate::error::{Error, Result};
+
+/// Sub-function of `DiagnosticSessionControl` selecting the session.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SessionType {
+    /// Default Session
+    Default,
+
+    /// Programming Session
+    Programming,
+
+    /// Extended Diagnostic Session
+    ExtendedDiagnostic,
+
+    /// Safety System Diagnostic Session
+    SafetySystemDiagnostic,
+
+    /// Reserved or vehicle manufacturer specific session
+    Other(u8),
+}
+
+impl From<u8> for SessionType {
+    fn from(value: u8) -> Self {
+        match value {
+            0x01 => SessionType::Default,
+            0x02 => SessionType::Programming,
+            0x03 => SessionType::ExtendedDiagnostic,
+            0x04 => SessionType::SafetySystemDiagnostic,
+            v => SessionType::Other(v),
+        }
+    }
+}
+
+impl From<SessionType> for u8 {
+    fn from(value: SessionType) -> Self {
+        match value {
+            SessionType::Default => 0x01,
+            SessionType::Programming => 0x02,
+            SessionType::ExtendedDiagnostic => 0x03,
+            SessionType::SafetySystemDiagnostic => 0x04,
+            SessionType::Other(v) => v,
+        }
+    }
+}
+
+/// Sub-function of `ECUReset` selecting the kind of reset.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResetType {
+    /// Hard Reset
+    HardReset,
+
+    /// Key Off On Reset
+    KeyOffOnReset,
+
+    /// Soft Reset
+    SoftReset,
+
+    /// Enable Rapid Power Shut Down
+    EnableRapidPowerShutDown,
+
+    /// Disable Rapid Power Shut Down
+    DisableRapidPowerShutDown,
+
+    /// Reserved or vehicle manufacturer specific reset
+    Other(u8),
+}
+
+impl From<u8> for ResetType {
+    fn from(value: u8) -> Self {
+        match value {
+            0x01 => ResetType::HardReset,
+            0x02 => ResetType::KeyOffOnReset,
+            0x03 => ResetType::SoftReset,
+            0x04 => ResetType::EnableRapidPowerShutDown,
+            0x05 => ResetType::DisableRapidPowerShutDown,
+            v => ResetType::Other(v),
+        }
+    }
+}
+
+impl From<ResetType> for u8 {
+    fn from(value: ResetType) -> Self {
+        match value {
+            ResetType::HardReset => 0x01,
+            ResetType::KeyOffOnReset => 0x02,
+            ResetType::SoftReset => 0x03,
+            ResetType::EnableRapidPowerShutDown => 0x04,
+            ResetType::DisableRapidPowerShutDown => 0x05,
+            ResetType::Other(v) => v,
+        }
+    }
+}
+
+/// Sub-function of `CommunicationControl` selecting which directions are
+/// enabled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CommunicationControlType {
+    /// Enable Rx And Tx
+    EnableRxAndTx,
+
+    /// Enable Rx And Disable Tx
+    EnableRxAndDisableTx,
+
+    /// Disable Rx And Enable Tx
+    DisableRxAndEnableTx,
+
+    /// Disable Rx And Tx
+    DisableRxAndTx,
+
+    /// Enable Rx And Disable Tx With Enhanced Address Information
+    EnableRxAndDisableTxWithEnhancedAddressInformation,
+
+    /// Enable Rx And Tx With Enhanced Address Information
+    EnableRxAndTxWithEnhancedAddressInformation,
+
+    /// Reserved or vehicle manufacturer specific control type
+    Other(u8),
+}
+
+impl From<u8> for CommunicationControlType {
+    fn from(value: u8) -> Self {
+        match value {
+            0x00 => CommunicationControlType::EnableRxAndTx,
+            0x01 => CommunicationControlType::EnableRxAndDisableTx,
+            0x02 => CommunicationControlType::DisableRxAndEnableTx,
+            0x03 => CommunicationControlType::DisableRxAndTx,
+            0x04 => CommunicationControlType::EnableRxAndDisableTxWithEnhancedAddressInformation,
+            0x05 => CommunicationControlType::EnableRxAndTxWithEnhancedAddressInformation,
+            v => CommunicationControlType::Other(v),
+        }
+    }
+}
+
+impl From<CommunicationControlType> for u8 {
+    fn from(value: CommunicationControlType) -> Self {
+        match value {
+            CommunicationControlType::EnableRxAndTx => 0x00,
+            CommunicationControlType::EnableRxAndDisableTx => 0x01,
+            CommunicationControlType::DisableRxAndEnableTx => 0x02,
+            CommunicationControlType::DisableRxAndTx => 0x03,
+            CommunicationControlType::EnableRxAndDisableTxWithEnhancedAddressInformation => 0x04,
+            CommunicationControlType::EnableRxAndTxWithEnhancedAddressInformation => 0x05,
+            CommunicationControlType::Other(v) => v,
+        }
+    }
+}
+
+/// Sub-function of `RoutineControl`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum RoutineControlType {
+    /// Start Routine
+    StartRoutine = 0x01,
+
+    /// Stop Routine
+    StopRoutine = 0x02,
+
+    /// Request Routine Results
+    RequestRoutineResults = 0x03,
+}
+
+impl From<RoutineControlType> for u8 {
+    fn from(value: RoutineControlType) -> Self {
+        value as u8
+    }
+}
+
+impl TryFrom<&u8> for RoutineControlType {
+    type Error = Error;
+
+    fn try_from(value: &u8) -> Result<Self> {
+        let val = *value;
+
+        match val {
+            v if v == RoutineControlType::StartRoutine as u8 => {
+                Ok(RoutineControlType::StartRoutine)
+            }
+            v if v == RoutineControlType::StopRoutine as u8 => Ok(RoutineControlType::StopRoutine),
+            v if v == RoutineControlType::RequestRoutineResults as u8 => {
+                Ok(RoutineControlType::RequestRoutineResults)
+            }
+            v => Err(Error::InvalidRoutineControlType { value: v }),
+        }
+    }
+}